@@ -0,0 +1,173 @@
+//! `#[derive(Alias)]` and `#[derive(AliasColumns)]`, re-exported by
+//! the `alias` crate under its `derive` feature; see the documentation
+//! there.
+
+extern crate proc_macro;
+
+use proc_macro::{Delimiter, TokenStream, TokenTree};
+
+/// Generate a struct of per-field cell references, and an `alias`
+/// method creating it.
+#[proc_macro_derive(Alias)]
+pub fn derive_alias(input: TokenStream) -> TokenStream {
+    let out = match parse_struct(input) {
+        Ok(s) => generate(&s),
+        Err(msg) => format!("compile_error!({:?});", format!("#[derive(Alias)]: {}", msg)),
+    };
+    out.parse().unwrap()
+}
+
+/// Generate a view of a slice of cells of the struct with a column
+/// of per-field cell references for each field.
+#[proc_macro_derive(AliasColumns)]
+pub fn derive_alias_columns(input: TokenStream) -> TokenStream {
+    let out = match parse_struct(input) {
+        Ok(s) => generate_columns(&s),
+        Err(msg) => format!("compile_error!({:?});", format!("#[derive(AliasColumns)]: {}", msg)),
+    };
+    out.parse().unwrap()
+}
+
+struct Struct {
+    vis: String,
+    name: String,
+    fields: Vec<Field>,
+}
+
+struct Field {
+    vis: String,
+    name: String,
+    ty: String,
+}
+
+/// Skip any `#[...]` attributes at the front of `tokens`.
+fn skip_attrs(tokens: &[TokenTree]) -> &[TokenTree] {
+    let mut tokens = tokens;
+    while let [TokenTree::Punct(p), TokenTree::Group(g), rest @ ..] = tokens {
+        if p.as_char() != '#' || g.delimiter() != Delimiter::Bracket {
+            break
+        }
+        tokens = rest;
+    }
+    tokens
+}
+
+/// Split a visibility, like `pub` or `pub(crate)`, off the front of
+/// `tokens`.
+fn split_vis(tokens: &[TokenTree]) -> (String, &[TokenTree]) {
+    match tokens {
+        [TokenTree::Ident(i), TokenTree::Group(g), rest @ ..]
+            if i.to_string() == "pub" && g.delimiter() == Delimiter::Parenthesis =>
+            (format!("pub {}", g), rest),
+        [TokenTree::Ident(i), rest @ ..] if i.to_string() == "pub" => ("pub".to_string(), rest),
+        _ => (String::new(), tokens),
+    }
+}
+
+fn parse_struct(input: TokenStream) -> Result<Struct, &'static str> {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    let (vis, tokens) = split_vis(skip_attrs(&tokens));
+    let (name, body) = match tokens {
+        [TokenTree::Ident(kw), TokenTree::Ident(name), TokenTree::Group(body)]
+            if kw.to_string() == "struct" && body.delimiter() == Delimiter::Brace =>
+            (name.to_string(), body),
+        [TokenTree::Ident(kw), TokenTree::Ident(_), TokenTree::Punct(p), ..]
+            if kw.to_string() == "struct" && p.as_char() == '<' =>
+            return Err("generic structs are not supported"),
+        [TokenTree::Ident(kw), ..] if kw.to_string() == "struct" =>
+            return Err("only structs with named fields are supported"),
+        _ => return Err("only structs are supported"),
+    };
+
+    let body: Vec<TokenTree> = body.stream().into_iter().collect();
+    let mut rest = &body[..];
+    let mut fields = vec![];
+    while !rest.is_empty() {
+        let (vis, after_vis) = split_vis(skip_attrs(rest));
+        let (name, ty_start) = match after_vis {
+            [TokenTree::Ident(name), TokenTree::Punct(colon), ty @ ..] if colon.as_char() == ':' =>
+                (name.to_string(), ty),
+            _ => return Err("unexpected tokens in struct body"),
+        };
+        // the type runs until the next comma outside any `<...>`.
+        let mut depth = 0;
+        let mut prev = ' ';
+        let mut end = ty_start.len();
+        for (i, t) in ty_start.iter().enumerate() {
+            if let TokenTree::Punct(p) = t {
+                match p.as_char() {
+                    '<' => depth += 1,
+                    // not the `>` of `->`
+                    '>' if prev != '-' => depth -= 1,
+                    ',' if depth == 0 => {
+                        end = i;
+                        break
+                    }
+                    _ => {}
+                }
+                prev = p.as_char();
+            } else {
+                prev = ' ';
+            }
+        }
+        let ty: TokenStream = ty_start[..end].iter().cloned().collect();
+        fields.push(Field { vis, name, ty: ty.to_string() });
+        rest = ty_start.get(end + 1..).unwrap_or(&[]);
+    }
+    Ok(Struct { vis, name, fields })
+}
+
+fn generate(s: &Struct) -> String {
+    let cells = format!("{}Cells", s.name);
+    let mut decls = String::new();
+    let mut inits = String::new();
+    for f in &s.fields {
+        decls += &format!("{} {}: &'a ::std::cell::Cell<{}>,", f.vis, f.name, f.ty);
+        inits += &format!("{0}: ::alias::one(&mut self.{0}),", f.name);
+    }
+    format!("
+        #[doc = \"Aliased references to each field of a `{name}`.\"]
+        #[derive(Clone, Copy)]
+        {vis} struct {cells}<'a> {{ {decls} }}
+
+        impl {name} {{
+            #[doc = \"Allow each field to be mutated while aliased, independently.\"]
+            {vis} fn alias(&mut self) -> {cells}<'_> {{
+                {cells} {{ {inits} }}
+            }}
+        }}",
+        name = s.name, vis = s.vis, cells = cells, decls = decls, inits = inits)
+}
+
+fn generate_columns(s: &Struct) -> String {
+    let columns = format!("{}Columns", s.name);
+    let mut methods = String::new();
+    for f in &s.fields {
+        methods += &format!("
+            #[doc = \"The `{field}` field of each element, in order.\"]
+            {vis} fn {field}(&self) -> impl ::std::iter::DoubleEndedIterator<Item = &'a ::std::cell::Cell<{ty}>>
+                + ::std::iter::ExactSizeIterator + 'a
+            {{
+                self.rows.iter().map(|c| ::alias::cell_project!(c, {name}, {field}))
+            }}",
+            vis = f.vis, field = f.name, ty = f.ty, name = s.name);
+    }
+    format!("
+        #[doc = \"A column-wise view of aliased `{name}`s.\"]
+        #[derive(Clone, Copy)]
+        {vis} struct {columns}<'a> {{
+            rows: &'a [::std::cell::Cell<{name}>],
+        }}
+
+        impl {name} {{
+            #[doc = \"View `rows` as a column of cells for each field.\"]
+            {vis} fn columns(rows: &[::std::cell::Cell<{name}>]) -> {columns}<'_> {{
+                {columns} {{ rows }}
+            }}
+        }}
+
+        impl<'a> {columns}<'a> {{
+            {methods}
+        }}",
+        name = s.name, vis = s.vis, columns = columns, methods = methods)
+}
@@ -0,0 +1,199 @@
+use std::cell::Cell;
+
+use {array, one, slice};
+
+/// Types that can be viewed as aliased and mutable, for writing code
+/// that is generic over the shape of the data.
+///
+/// This is implemented for the primitive scalars (viewed as a `Cell`
+/// of themselves), slices and arrays (viewed element-wise, as by
+/// `alias::slice` and `alias::array`) and tuples of up to 12 elements
+/// (viewed as a `Cell` of the whole tuple). Other types can implement
+/// it in terms of those functions and `alias::one`.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::Aliasable;
+///
+/// struct Sprite {
+///     frame: u32,
+/// }
+///
+/// impl Aliasable for Sprite {
+///     type Aliased = std::cell::Cell<u32>;
+///     fn as_aliased(&mut self) -> &std::cell::Cell<u32> {
+///         alias::one(&mut self.frame)
+///     }
+/// }
+///
+/// // works for anything that can be aliased, whatever its shape
+/// fn count<A: Aliasable + ?Sized>(data: &mut A) -> usize {
+///     std::mem::size_of_val(alias::of(data))
+/// }
+///
+/// assert_eq!(count(&mut 0u16), 2);
+/// assert_eq!(count(&mut [0u8; 5][..]), 5);
+/// assert_eq!(count(&mut Sprite { frame: 0 }), 4);
+/// ```
+pub trait Aliasable {
+    /// The aliased view.
+    type Aliased: ?Sized;
+
+    /// Allow `self` to be mutated while aliased.
+    fn as_aliased(&mut self) -> &Self::Aliased;
+}
+
+/// Allow `data`, of any `Aliasable` type, to be mutated while
+/// aliased.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut x = 1u32;
+/// let mut y = [1, 2, 3];
+///
+/// alias::of(&mut x).set(2);
+/// alias::of(&mut y)[0].set(4);
+/// alias::of(&mut y[1..])[0].set(5);
+///
+/// assert_eq!((x, y), (2, [4, 5, 3]));
+/// ```
+pub fn of<A: Aliasable + ?Sized>(data: &mut A) -> &A::Aliased {
+    data.as_aliased()
+}
+
+/// Method syntax for `alias::of`, so that aliasing can be written in
+/// the middle of a chain of calls.
+///
+/// This is implemented for every `Aliasable` type: scalars, tuples,
+/// slices and arrays, and any type that implements `Aliasable`
+/// itself.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::AliasExt;
+///
+/// let mut xs = [3, 1, 2];
+/// let max = xs.aliased().iter().map(|c| c.get()).max();
+/// assert_eq!(max, Some(3));
+///
+/// let mut v = vec![1, 2, 3, 4];
+/// for pair in v[..].aliased().windows(2) {
+///     pair[1].set(pair[0].get() + pair[1].get());
+/// }
+/// assert_eq!(v, [1, 3, 6, 10]);
+///
+/// let mut n = 1.5f64;
+/// n.aliased().set(2.5);
+/// assert_eq!(n, 2.5);
+/// ```
+pub trait AliasExt: Aliasable {
+    /// Allow `self` to be mutated while aliased.
+    fn aliased(&mut self) -> &Self::Aliased {
+        self.as_aliased()
+    }
+}
+
+impl<A: Aliasable + ?Sized> AliasExt for A {}
+
+macro_rules! scalars {
+    ($($t: ty),*) => {
+        $(
+            impl Aliasable for $t {
+                type Aliased = Cell<$t>;
+                fn as_aliased(&mut self) -> &Cell<$t> {
+                    one(self)
+                }
+            }
+        )*
+    }
+}
+scalars!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, bool, char);
+
+impl<T> Aliasable for [T] {
+    type Aliased = [Cell<T>];
+    fn as_aliased(&mut self) -> &[Cell<T>] {
+        slice(self)
+    }
+}
+
+impl<T, const N: usize> Aliasable for [T; N] {
+    type Aliased = [Cell<T>; N];
+    fn as_aliased(&mut self) -> &[Cell<T>; N] {
+        array(self)
+    }
+}
+
+macro_rules! tuples {
+    ($(($($t: ident),*))*) => {
+        $(
+            impl<$($t),*> Aliasable for ($($t,)*) {
+                type Aliased = Cell<($($t,)*)>;
+                fn as_aliased(&mut self) -> &Cell<($($t,)*)> {
+                    one(self)
+                }
+            }
+        )*
+    }
+}
+tuples! {
+    ()
+    (A)
+    (A, B)
+    (A, B, C)
+    (A, B, C, D)
+    (A, B, C, D, E)
+    (A, B, C, D, E, F)
+    (A, B, C, D, E, F, G)
+    (A, B, C, D, E, F, G, H)
+    (A, B, C, D, E, F, G, H, I)
+    (A, B, C, D, E, F, G, H, I, J)
+    (A, B, C, D, E, F, G, H, I, J, K)
+    (A, B, C, D, E, F, G, H, I, J, K, L)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bump<A: Aliasable<Aliased = Cell<u8>> + ?Sized>(a: &mut A) {
+        let c = of(a);
+        c.set(c.get() + 1);
+    }
+
+    #[test]
+    fn smoke_aliasable() {
+        let mut x = 1u8;
+        bump(&mut x);
+        assert_eq!(x, 2);
+
+        let mut t = (1, "a".to_string());
+        {
+            let c = of(&mut t);
+            let (n, s) = c.take();
+            c.set((n + 1, s + "b"));
+        }
+        assert_eq!(t, (2, "ab".to_string()));
+
+        let mut arr = [[1, 2], [3, 4]];
+        {
+            let rows: &[Cell<[i32; 2]>; 2] = of(&mut arr);
+            rows[0].swap(&rows[1]);
+        }
+        assert_eq!(arr, [[3, 4], [1, 2]]);
+        of(&mut ()).set(());
+    }
+
+    #[test]
+    fn smoke_alias_ext() {
+        let mut x = [1u8, 2, 3];
+        x.aliased()[0].set(4);
+        x[1..].aliased()[1].set(5);
+        bump(&mut x[1]);
+        let mut y = (1, 'a');
+        y.aliased().set((2, 'b'));
+        assert_eq!((x, y), ([4, 3, 5], (2, 'b')));
+    }
+}
@@ -0,0 +1,446 @@
+//! Aliasing through atomics, for sharing between threads.
+//!
+//! The `Cell`-based functions at the top level give `!Sync`
+//! references, which can't be used from more than one thread. The
+//! functions here are their thread-safe equivalents: a unique `&mut`
+//! to an integer, `bool` or raw pointer becomes a shared reference to
+//! the corresponding atomic type, like the `from_mut` methods on the
+//! standard atomics.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use std::sync::atomic::Ordering;
+//! use std::thread;
+//!
+//! let mut hits = 0u32;
+//! {
+//!     let counter = alias::atomic::one(&mut hits);
+//!     thread::scope(|s| {
+//!         for _ in 0..4 {
+//!             s.spawn(|| counter.fetch_add(1, Ordering::Relaxed));
+//!         }
+//!     });
+//! }
+//! assert_eq!(hits, 4);
+//! ```
+
+use std::fmt;
+use std::iter;
+use std::mem;
+use std::ops::Index;
+use std::slice as std_slice;
+use std::sync::atomic::*;
+
+/// Types with a corresponding atomic type of the same size and bit
+/// validity.
+///
+/// This is implemented for the primitive integers, `bool` and `*mut
+/// T`, on platforms that support atomics of that size, and cannot be
+/// implemented outside this crate.
+pub trait HasAtomic: private::Sealed {
+    /// The atomic version of this type.
+    type Atomic;
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+macro_rules! has_atomic {
+    ($($width: tt: $($t: ty => $a: ty),*;)*) => {
+        $($(
+            #[cfg(target_has_atomic = $width)]
+            impl HasAtomic for $t {
+                type Atomic = $a;
+            }
+            #[cfg(target_has_atomic = $width)]
+            impl private::Sealed for $t {}
+        )*)*
+    }
+}
+
+has_atomic! {
+    "8": bool => AtomicBool, u8 => AtomicU8, i8 => AtomicI8;
+    "16": u16 => AtomicU16, i16 => AtomicI16;
+    "32": u32 => AtomicU32, i32 => AtomicI32;
+    "64": u64 => AtomicU64, i64 => AtomicI64;
+    "ptr": usize => AtomicUsize, isize => AtomicIsize;
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T> HasAtomic for *mut T {
+    type Atomic = AtomicPtr<T>;
+}
+#[cfg(target_has_atomic = "ptr")]
+impl<T> private::Sealed for *mut T {}
+
+/// Allow `data` to be mutated while aliased, including from other
+/// threads.
+///
+/// # Panics
+///
+/// Panics if `data` isn't sufficiently aligned to be accessed
+/// atomically. This can only happen for 64-bit types, which are only
+/// 4-byte aligned on some 32-bit platforms where `AtomicU64` and
+/// `AtomicI64` need 8 bytes.
+pub fn one<T: HasAtomic>(data: &mut T) -> &T::Atomic {
+    debug_assert_eq!(mem::size_of::<T>(), mem::size_of::<T::Atomic>());
+    assert_eq!(data as *mut T as usize % mem::align_of::<T::Atomic>(), 0,
+               "alias::atomic::one: insufficiently aligned data");
+    // same size and bit validity, and alignment checked above.
+    unsafe { &*(data as *mut T as *const T::Atomic) }
+}
+
+/// Allow the contents of `data` to be mutated while aliased,
+/// including from other threads.
+///
+/// # Panics
+///
+/// Panics if `data` isn't sufficiently aligned to be accessed
+/// atomically, as for `one`.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::sync::atomic::Ordering;
+/// use std::thread;
+///
+/// let mut histogram = [0u32; 4];
+/// let samples = [3, 1, 3, 0, 2, 3, 1, 3];
+/// {
+///     let bins = alias::atomic::slice(&mut histogram);
+///     thread::scope(|s| {
+///         for chunk in samples.chunks(2) {
+///             s.spawn(move || for &x in chunk {
+///                 bins[x].fetch_add(1, Ordering::Relaxed);
+///             });
+///         }
+///     });
+/// }
+/// assert_eq!(histogram, [1, 2, 1, 4]);
+/// ```
+pub fn slice<T: HasAtomic>(data: &mut [T]) -> &[T::Atomic] {
+    debug_assert_eq!(mem::size_of::<T>(), mem::size_of::<T::Atomic>());
+    assert_eq!(data.as_ptr() as usize % mem::align_of::<T::Atomic>(), 0,
+               "alias::atomic::slice: insufficiently aligned data");
+    // same size and bit validity, and alignment checked above.
+    unsafe { &*(data as *mut [T] as *const [T::Atomic]) }
+}
+
+/// Allow the `len` elements starting at `ptr` to be accessed
+/// atomically while aliased, including from other threads.
+///
+/// This is for memory that is shared with code that doesn't go
+/// through Rust references, such as a region of WebAssembly shared
+/// linear memory that other workers access with JavaScript's
+/// `Atomics`, or memory shared between processes. Every access
+/// through the result is atomic, so it is sound under those memory
+/// models as long as every other access is atomic too.
+///
+/// # Safety
+///
+/// For the lifetime `'a`:
+///
+/// - `ptr` must be valid for reads and writes of `len` contiguous
+///   `T`s, with the total size at most `isize::MAX`, and the memory
+///   must not be deallocated, and
+/// - every other access to the memory, through Rust or otherwise,
+///   must be atomic, or must happen-before or happen-after every
+///   access through the result.
+///
+/// # Panics
+///
+/// Panics if `ptr` isn't sufficiently aligned, as for `one`.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::sync::atomic::Ordering;
+///
+/// // e.g. the offset of a state buffer that JavaScript workers share
+/// let mut state = vec![0u32; 16];
+/// let ptr = state.as_mut_ptr();
+///
+/// let words = unsafe { alias::atomic::from_raw_parts(ptr, 16) };
+/// words[3].store(7, Ordering::Release);
+/// words[3].fetch_add(1, Ordering::AcqRel);
+/// assert_eq!(words[3].load(Ordering::Acquire), 8);
+/// # assert_eq!(state[3], 8);
+/// ```
+pub unsafe fn from_raw_parts<'a, T: HasAtomic>(ptr: *mut T, len: usize) -> &'a [T::Atomic] {
+    debug_assert_eq!(mem::size_of::<T>(), mem::size_of::<T::Atomic>());
+    assert_eq!(ptr as usize % mem::align_of::<T::Atomic>(), 0,
+               "alias::atomic::from_raw_parts: insufficiently aligned data");
+    // valid and only accessed atomically, guaranteed by the caller.
+    std_slice::from_raw_parts(ptr as *const T::Atomic, len)
+}
+
+/// Split `data` into `n` contiguous shards of atomics, for handing
+/// out to `n` threads.
+///
+/// The shards cover `data` in order, and their lengths differ by at
+/// most one, with the longer ones first.
+///
+/// # Panics
+///
+/// Panics if `n` is zero, or if `data` isn't sufficiently aligned, as
+/// for `one`.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::sync::atomic::Ordering;
+/// use std::thread;
+///
+/// let mut counts = [0u64; 10];
+/// {
+///     let shards = alias::atomic::shards(&mut counts, 3);
+///     assert_eq!(shards.len(), 3);
+///     thread::scope(|s| {
+///         for (t, shard) in shards.enumerate() {
+///             s.spawn(move || for c in shard.iter() {
+///                 c.fetch_add(t as u64, Ordering::Relaxed);
+///             });
+///         }
+///     });
+/// }
+/// assert_eq!(counts, [0, 0, 0, 0, 1, 1, 1, 2, 2, 2]);
+/// ```
+pub fn shards<T: HasAtomic>(data: &mut [T], n: usize) -> Shards<'_, T::Atomic> {
+    assert!(n != 0, "alias::atomic::shards: cannot split into 0 shards");
+    Shards { data: slice(data), n, next: 0, interleaved: false }
+}
+
+/// Split `data` into `n` interleaved shards of atomics, for handing
+/// out to `n` threads.
+///
+/// Shard `k` holds elements `k`, `k + n`, `k + 2 * n` and so on, so
+/// neighbouring elements are in different shards.
+///
+/// # Panics
+///
+/// Panics if `n` is zero, or if `data` isn't sufficiently aligned, as
+/// for `one`.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::sync::atomic::Ordering;
+/// use std::thread;
+///
+/// let mut owner = [0u32; 7];
+/// {
+///     let shards = alias::atomic::shards_interleaved(&mut owner, 3);
+///     thread::scope(|s| {
+///         for (t, shard) in shards.enumerate() {
+///             s.spawn(move || for c in shard.iter() {
+///                 c.store(t as u32, Ordering::Relaxed);
+///             });
+///         }
+///     });
+/// }
+/// assert_eq!(owner, [0, 1, 2, 0, 1, 2, 0]);
+/// ```
+pub fn shards_interleaved<T: HasAtomic>(data: &mut [T], n: usize) -> Shards<'_, T::Atomic> {
+    assert!(n != 0, "alias::atomic::shards_interleaved: cannot split into 0 shards");
+    Shards { data: slice(data), n, next: 0, interleaved: true }
+}
+
+/// An iterator over the shards of a slice of atomics.
+///
+/// Created by `shards` and `shards_interleaved`.
+#[derive(Clone)]
+pub struct Shards<'a, A: 'a> {
+    data: &'a [A],
+    n: usize,
+    next: usize,
+    interleaved: bool,
+}
+
+impl<'a, A> Iterator for Shards<'a, A> {
+    type Item = Shard<'a, A>;
+
+    fn next(&mut self) -> Option<Shard<'a, A>> {
+        if self.next == self.n {
+            return None
+        }
+        let (k, n, len) = (self.next, self.n, self.data.len());
+        self.next += 1;
+        Some(if self.interleaved {
+            Shard { data: &self.data[k.min(len)..], step: n }
+        } else {
+            let start = |k: usize| k * (len / n) + k.min(len % n);
+            Shard { data: &self.data[start(k)..start(k + 1)], step: 1 }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.n - self.next;
+        (n, Some(n))
+    }
+}
+
+impl<'a, A> ExactSizeIterator for Shards<'a, A> {}
+
+impl<'a, A> fmt::Debug for Shards<'a, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Shards")
+            .field("remaining", &self.len())
+            .field("interleaved", &self.interleaved)
+            .finish()
+    }
+}
+
+/// One shard of a slice of atomics, from `shards` or
+/// `shards_interleaved`.
+///
+/// This is `Send` and `Sync` whenever the atomic type is, so can be
+/// moved into another thread.
+pub struct Shard<'a, A: 'a> {
+    data: &'a [A],
+    step: usize,
+}
+
+impl<'a, A> Shard<'a, A> {
+    /// The number of elements in the shard.
+    pub fn len(&self) -> usize {
+        self.data.len().div_ceil(self.step)
+    }
+
+    /// Whether the shard is empty.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Element `i` of the shard, if it is in bounds.
+    pub fn get(&self, i: usize) -> Option<&'a A> {
+        self.data.get(i.checked_mul(self.step)?)
+    }
+
+    /// Iterate over the elements of the shard, in order.
+    pub fn iter(&self) -> iter::StepBy<std_slice::Iter<'a, A>> {
+        self.data.iter().step_by(self.step)
+    }
+}
+
+impl<'a, A> Clone for Shard<'a, A> {
+    fn clone(&self) -> Shard<'a, A> {
+        *self
+    }
+}
+
+impl<'a, A> Copy for Shard<'a, A> {}
+
+impl<'a, A> Index<usize> for Shard<'a, A> {
+    type Output = A;
+
+    fn index(&self, i: usize) -> &A {
+        match self.get(i) {
+            Some(a) => a,
+            None => panic!("Shard: index {} out of bounds for length {}", i, self.len()),
+        }
+    }
+}
+
+impl<'a, A: fmt::Debug> fmt::Debug for Shard<'a, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+    use std::thread;
+
+    #[test]
+    fn smoke_one() {
+        let mut a = false;
+        let mut b = 0i16;
+        let mut c = 0u64;
+        let mut x = 5;
+        let mut d: *mut i32 = ptr::null_mut();
+        {
+            let a = one(&mut a);
+            let b = one(&mut b);
+            let c = one(&mut c);
+            let d = one(&mut d);
+            thread::scope(|s| {
+                for i in 0..8 {
+                    s.spawn(move || {
+                        a.fetch_or(i == 3, Ordering::Relaxed);
+                        b.fetch_sub(1, Ordering::Relaxed);
+                        c.fetch_add(i, Ordering::Relaxed);
+                    });
+                }
+            });
+            let p = &mut x as *mut i32;
+            d.store(p, Ordering::Relaxed);
+        }
+        assert!(a);
+        assert_eq!((b, c), (-8, 28));
+        assert_eq!(unsafe { *d }, 5);
+    }
+
+    #[test]
+    fn smoke_slice() {
+        let mut x = vec![0usize; 1000];
+        let mut flags = [false; 10];
+        {
+            let cells = slice(&mut x);
+            let flags = slice(&mut flags);
+            thread::scope(|s| {
+                for t in 0..10 {
+                    s.spawn(move || {
+                        // overlapping, data-dependent indices.
+                        for i in 0..1000 {
+                            cells[(i * 7 + t) % 1000].fetch_add(1, Ordering::Relaxed);
+                        }
+                        flags[t].store(true, Ordering::Relaxed);
+                    });
+                }
+            });
+            assert_eq!(slice::<u8>(&mut []).len(), 0);
+        }
+        assert!(x.iter().all(|&n| n == 10));
+        assert_eq!(flags, [true; 10]);
+    }
+
+    #[test]
+    fn smoke_from_raw_parts() {
+        let mut x = [0i64; 4];
+        // as an address, like an offset into shared memory
+        let addr = x.as_mut_ptr() as usize;
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(move || {
+                    let words = unsafe { from_raw_parts(addr as *mut i64, 4) };
+                    for w in words {
+                        w.fetch_sub(1, Ordering::Relaxed);
+                    }
+                });
+            }
+        });
+        assert_eq!(x, [-4; 4]);
+    }
+
+    #[test]
+    fn smoke_shards() {
+        let mut x: Vec<u16> = (0..5).collect();
+        let lens: Vec<_> = shards(&mut x, 3).map(|s| s.len()).collect();
+        assert_eq!(lens, [2, 2, 1]);
+        let lens: Vec<_> = shards(&mut x, 7).map(|s| s.len()).collect();
+        assert_eq!(lens, [1, 1, 1, 1, 1, 0, 0]);
+
+        let s: Vec<_> = shards_interleaved(&mut x, 2).collect();
+        assert_eq!((s[0].len(), s[1].len()), (3, 2));
+        s[0][2].store(40, Ordering::Relaxed);
+        assert_eq!(s[1].get(1).unwrap().load(Ordering::Relaxed), 3);
+        assert!(s[1].get(2).is_none() && s[1].get(usize::MAX).is_none());
+        assert_eq!(format!("{:?}", s[0]), "[0, 2, 40]");
+        let empty: Vec<_> = shards_interleaved(&mut x, 7).map(|s| s.len()).collect();
+        assert_eq!(empty, [1, 1, 1, 1, 1, 0, 0]);
+    }
+}
@@ -0,0 +1,622 @@
+use std::cell::Cell;
+use std::fmt;
+use std::ops::{BitAnd, BitOr, BitXor, Not, Shl};
+
+use one;
+
+/// Read runs of bits from aliased bytes.
+///
+/// Bits are numbered least-significant first within each byte, and
+/// multi-bit values are assembled least-significant bit first, as in
+/// DEFLATE and most other bit-packed formats.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::BitReader;
+///
+/// let mut data = [0b1010_0111, 0b0000_0001];
+/// let mut r = BitReader::new(alias::slice(&mut data));
+/// assert_eq!(r.read_bits(3), Some(0b111));
+/// assert_eq!(r.read_bit(), Some(false));
+/// assert_eq!(r.read_bits(5), Some(0b1_1010));
+/// assert_eq!(r.remaining_bits(), 7);
+/// ```
+#[derive(Clone, Debug)]
+pub struct BitReader<'a> {
+    data: &'a [Cell<u8>],
+    pos: usize,
+}
+
+/// Overwrite runs of bits in aliased bytes.
+///
+/// Bits are ordered in the same way as for `BitReader`. Only the bits
+/// actually written are changed, so fields can be patched in place
+/// without disturbing their neighbours.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::{BitReader, BitWriter};
+///
+/// let mut data = [0xFF, 0xFF];
+/// {
+///     let bytes = alias::slice(&mut data);
+///     let mut w = BitWriter::new(bytes);
+///     w.seek_bits(6);
+///     w.write_bits(0, 4).unwrap();
+///
+///     let mut r = BitReader::new(bytes);
+///     r.seek_bits(4);
+///     assert_eq!(r.read_bits(8), Some(0b1100_0011));
+/// }
+/// assert_eq!(data, [0b0011_1111, 0b1111_1100]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct BitWriter<'a> {
+    data: &'a [Cell<u8>],
+    pos: usize,
+}
+
+macro_rules! bit_position {
+    () => {
+        /// The current position, in bits from the start.
+        pub fn bit_position(&self) -> usize {
+            self.pos
+        }
+
+        /// Move to bit `pos`, which may be past the end (in which case
+        /// further operations fail).
+        pub fn seek_bits(&mut self, pos: usize) {
+            self.pos = pos
+        }
+
+        /// The number of bits after the current position.
+        pub fn remaining_bits(&self) -> usize {
+            (self.data.len() * 8).saturating_sub(self.pos)
+        }
+
+        /// Move forward to the next byte boundary, if not already on
+        /// one.
+        pub fn align_to_byte(&mut self) {
+            self.pos = self.pos.div_ceil(8) * 8
+        }
+    }
+}
+
+impl<'a> BitReader<'a> {
+    /// Create a reader at the first bit of `data`.
+    pub fn new(data: &'a [Cell<u8>]) -> BitReader<'a> {
+        BitReader { data, pos: 0 }
+    }
+
+    bit_position!();
+
+    /// Read the next `n` bits, or return `None` (and not move) if
+    /// there aren't that many left.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n > 64`.
+    pub fn read_bits(&mut self, n: u32) -> Option<u64> {
+        assert!(n <= 64, "BitReader::read_bits: cannot read {} bits", n);
+        if n as usize > self.remaining_bits() {
+            return None
+        }
+        let mut value = 0;
+        let mut done = 0;
+        while done < n {
+            let byte = self.data[self.pos / 8].get();
+            let shift = (self.pos % 8) as u32;
+            let take = (8 - shift).min(n - done);
+            let bits = (byte >> shift) as u64 & ((1 << take) - 1);
+            value |= bits << done;
+            done += take;
+            self.pos += take as usize;
+        }
+        Some(value)
+    }
+
+    /// Read the next bit.
+    pub fn read_bit(&mut self) -> Option<bool> {
+        self.read_bits(1).map(|b| b == 1)
+    }
+}
+
+impl<'a> BitWriter<'a> {
+    /// Create a writer at the first bit of `data`.
+    pub fn new(data: &'a [Cell<u8>]) -> BitWriter<'a> {
+        BitWriter { data, pos: 0 }
+    }
+
+    bit_position!();
+
+    /// Write the low `n` bits of `value`, or return it (and not move)
+    /// if there isn't enough space left. Higher bits of `value` are
+    /// ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n > 64`.
+    pub fn write_bits(&mut self, value: u64, n: u32) -> Result<(), u64> {
+        assert!(n <= 64, "BitWriter::write_bits: cannot write {} bits", n);
+        if n as usize > self.remaining_bits() {
+            return Err(value)
+        }
+        let mut done = 0;
+        while done < n {
+            let cell = &self.data[self.pos / 8];
+            let shift = (self.pos % 8) as u32;
+            let take = (8 - shift).min(n - done);
+            let mask = (((1u16 << take) - 1) << shift) as u8;
+            let bits = ((value >> done) << shift) as u8 & mask;
+            cell.set(cell.get() & !mask | bits);
+            done += take;
+            self.pos += take as usize;
+        }
+        Ok(())
+    }
+
+    /// Write a single bit.
+    pub fn write_bit(&mut self, bit: bool) -> Result<(), bool> {
+        self.write_bits(bit as u64, 1).map_err(|_| bit)
+    }
+}
+
+/// Unsigned integer types that can be used as a word of bits.
+///
+/// This is implemented for each primitive unsigned integer type, and
+/// cannot be implemented outside this crate.
+pub trait BitWord: Copy + Eq + fmt::Binary + private::Sealed
+    + BitAnd<Output = Self> + BitOr<Output = Self> + BitXor<Output = Self>
+    + Not<Output = Self> + Shl<u32, Output = Self>
+{
+    /// The number of bits in the word.
+    const BITS: u32;
+    /// The value with no bits set.
+    const ZERO: Self;
+    /// The value with only the lowest bit set.
+    const ONE: Self;
+
+    /// The number of bits that are set.
+    fn count_ones(self) -> u32;
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+macro_rules! bit_word {
+    ($($t: ty),*) => {
+        $(
+            impl private::Sealed for $t {}
+
+            impl BitWord for $t {
+                const BITS: u32 = <$t>::BITS;
+                const ZERO: $t = 0;
+                const ONE: $t = 1;
+
+                fn count_ones(self) -> u32 {
+                    <$t>::count_ones(self)
+                }
+            }
+        )*
+    }
+}
+
+bit_word!(u8, u16, u32, u64, u128, usize);
+
+/// Per-bit access to an aliased word of flags.
+///
+/// Bits are numbered from the least significant, starting at 0.
+///
+/// Created by `alias::bits`.
+pub struct BitCells<'a, W: 'a> {
+    word: &'a Cell<W>,
+}
+
+/// Allow the individual bits of `data` to be mutated while aliased.
+///
+/// # Examples
+///
+/// ```rust
+/// const READY: u32 = 1 << 0;
+/// const BUSY: u32 = 1 << 1;
+///
+/// let mut flags = READY;
+/// {
+///     let f = alias::bits(&mut flags);
+///     let start = || { f.clear_mask(READY); f.set_bit(1, true); };
+///     let finish = || f.toggle_mask(READY | BUSY);
+///
+///     start();
+///     assert!(f.get_bit(1) && !f.any(READY));
+///     finish();
+///     f.toggle(31);
+/// }
+/// assert_eq!(flags, READY | 1 << 31);
+/// ```
+pub fn bits<W: BitWord>(data: &mut W) -> BitCells<'_, W> {
+    BitCells::new(one(data))
+}
+
+impl<'a, W: BitWord> BitCells<'a, W> {
+    /// Access the bits of the aliased word `word`.
+    pub fn new(word: &'a Cell<W>) -> BitCells<'a, W> {
+        BitCells { word }
+    }
+
+    fn bit(i: u32) -> W {
+        assert!(i < W::BITS, "BitCells: bit {} out of range for {}-bit word", i, W::BITS);
+        W::ONE << i
+    }
+
+    /// The whole word.
+    pub fn get(&self) -> W {
+        self.word.get()
+    }
+
+    /// Replace the whole word with `value`.
+    pub fn set(&self, value: W) {
+        self.word.set(value)
+    }
+
+    /// Whether bit `i` is set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is not less than the number of bits in the word.
+    pub fn get_bit(&self, i: u32) -> bool {
+        self.any(Self::bit(i))
+    }
+
+    /// Set bit `i` to `value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is not less than the number of bits in the word.
+    pub fn set_bit(&self, i: u32, value: bool) {
+        if value {
+            self.set_mask(Self::bit(i))
+        } else {
+            self.clear_mask(Self::bit(i))
+        }
+    }
+
+    /// Flip bit `i`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is not less than the number of bits in the word.
+    pub fn toggle(&self, i: u32) {
+        self.toggle_mask(Self::bit(i))
+    }
+
+    /// Set every bit that is set in `mask`.
+    pub fn set_mask(&self, mask: W) {
+        self.word.set(self.word.get() | mask)
+    }
+
+    /// Clear every bit that is set in `mask`.
+    pub fn clear_mask(&self, mask: W) {
+        self.word.set(self.word.get() & !mask)
+    }
+
+    /// Flip every bit that is set in `mask`.
+    pub fn toggle_mask(&self, mask: W) {
+        self.word.set(self.word.get() ^ mask)
+    }
+
+    /// Whether any bit that is set in `mask` is set.
+    pub fn any(&self, mask: W) -> bool {
+        self.word.get() & mask != W::ZERO
+    }
+
+    /// Whether every bit that is set in `mask` is set.
+    pub fn all(&self, mask: W) -> bool {
+        self.word.get() & mask == mask
+    }
+}
+
+impl<'a, W> Clone for BitCells<'a, W> {
+    fn clone(&self) -> BitCells<'a, W> {
+        *self
+    }
+}
+
+impl<'a, W> Copy for BitCells<'a, W> {}
+
+impl<'a, W: BitWord> fmt::Debug for BitCells<'a, W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BitCells({:#b})", self.get())
+    }
+}
+
+/// A fixed-size set of small integers, stored as bits in an aliased
+/// slice of words.
+///
+/// Integer `i` is bit `i % W::BITS` of word `i / W::BITS`.
+///
+/// Created by `alias::bitset`.
+pub struct CellBitSet<'a, W: 'a = usize> {
+    words: &'a [Cell<W>],
+}
+
+/// Allow `data` to be used as a set of bits while aliased.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut visited = [0u64; 2];
+/// let edges = [(0, 3), (3, 70), (70, 0), (3, 5)];
+/// {
+///     let seen = alias::bitset(&mut visited);
+///     let mut stack = vec![0];
+///     seen.insert(0);
+///     while let Some(n) = stack.pop() {
+///         let mut visit = |m| if seen.insert(m) { stack.push(m) };
+///         for &(a, b) in &edges {
+///             if a == n { visit(b) }
+///         }
+///     }
+///     assert_eq!(seen.count_ones(), 4);
+///     assert!(seen.test(70) && !seen.test(1));
+/// }
+/// assert_eq!(visited, [1 << 0 | 1 << 3 | 1 << 5, 1 << 6]);
+/// ```
+pub fn bitset<W: BitWord>(data: &mut [W]) -> CellBitSet<'_, W> {
+    CellBitSet::new(::slice(data))
+}
+
+impl<'a, W: BitWord> CellBitSet<'a, W> {
+    /// Use the bits of the aliased words `words` as a set.
+    pub fn new(words: &'a [Cell<W>]) -> CellBitSet<'a, W> {
+        CellBitSet { words }
+    }
+
+    /// The underlying words.
+    pub fn words(&self) -> &'a [Cell<W>] {
+        self.words
+    }
+
+    /// The number of bits, one more than the largest integer the set
+    /// can hold.
+    pub fn capacity(&self) -> usize {
+        self.words.len() * W::BITS as usize
+    }
+
+    fn locate(&self, i: usize) -> (&'a Cell<W>, W) {
+        let bits = W::BITS as usize;
+        assert!(i < self.capacity(),
+                "CellBitSet: bit {} out of range for capacity {}", i, self.capacity());
+        (&self.words[i / bits], W::ONE << (i % bits) as u32)
+    }
+
+    /// Whether `i` is in the set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is not less than the capacity.
+    pub fn test(&self, i: usize) -> bool {
+        let (w, bit) = self.locate(i);
+        w.get() & bit != W::ZERO
+    }
+
+    /// Add `i` to the set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is not less than the capacity.
+    pub fn set(&self, i: usize) {
+        self.insert(i);
+    }
+
+    /// Add `i` to the set, returning whether it was newly added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is not less than the capacity.
+    pub fn insert(&self, i: usize) -> bool {
+        let (w, bit) = self.locate(i);
+        let old = w.get();
+        w.set(old | bit);
+        old & bit == W::ZERO
+    }
+
+    /// Remove `i` from the set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is not less than the capacity.
+    pub fn clear(&self, i: usize) {
+        let (w, bit) = self.locate(i);
+        w.set(w.get() & !bit)
+    }
+
+    /// Add `i` to the set if it isn't there, or remove it if it is.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is not less than the capacity.
+    pub fn toggle(&self, i: usize) {
+        let (w, bit) = self.locate(i);
+        w.set(w.get() ^ bit)
+    }
+
+    /// The number of integers in the set.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.get().count_ones() as usize).sum()
+    }
+
+    /// Whether the set is empty.
+    pub fn is_clear(&self) -> bool {
+        self.words.iter().all(|w| w.get() == W::ZERO)
+    }
+
+    /// Remove everything from the set.
+    pub fn clear_all(&self) {
+        for w in self.words {
+            w.set(W::ZERO)
+        }
+    }
+
+    fn combine<F: Fn(W, W) -> W>(&self, other: &CellBitSet<W>, what: &str, f: F) {
+        assert_eq!(self.words.len(), other.words.len(), "CellBitSet::{}: mismatched lengths", what);
+        for (a, b) in self.words.iter().zip(other.words) {
+            a.set(f(a.get(), b.get()))
+        }
+    }
+
+    /// Add everything in `other` to the set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` has a different number of words.
+    pub fn union_with(&self, other: &CellBitSet<W>) {
+        self.combine(other, "union_with", |a, b| a | b)
+    }
+
+    /// Remove everything not in `other` from the set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` has a different number of words.
+    pub fn intersect_with(&self, other: &CellBitSet<W>) {
+        self.combine(other, "intersect_with", |a, b| a & b)
+    }
+
+    /// Remove everything in `other` from the set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` has a different number of words.
+    pub fn difference_with(&self, other: &CellBitSet<W>) {
+        self.combine(other, "difference_with", |a, b| a & !b)
+    }
+
+    /// Iterate over the integers in the set, in increasing order.
+    ///
+    /// Each word is read as it is reached.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + 'a {
+        let words = self.words;
+        (0..self.capacity()).filter(move |&i| {
+            let bits = W::BITS as usize;
+            words[i / bits].get() & W::ONE << (i % bits) as u32 != W::ZERO
+        })
+    }
+}
+
+impl<'a, W> Clone for CellBitSet<'a, W> {
+    fn clone(&self) -> CellBitSet<'a, W> {
+        *self
+    }
+}
+
+impl<'a, W> Copy for CellBitSet<'a, W> {}
+
+impl<'a, W: BitWord> fmt::Debug for CellBitSet<'a, W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slice;
+
+    #[test]
+    fn smoke_bits() {
+        let mut x = [0u8; 10];
+        {
+            let bytes = slice(&mut x);
+            let mut w = BitWriter::new(bytes);
+            w.write_bit(true).unwrap();
+            w.write_bits(0x1234_5678_9ABC_DEF0, 64).unwrap();
+            w.write_bits(0b1111_0101, 3).unwrap();
+            assert_eq!(w.bit_position(), 68);
+            assert_eq!(w.write_bits(0, 13), Err(0));
+            w.align_to_byte();
+            assert_eq!(w.bit_position(), 72);
+            w.write_bits(0xAB, 8).unwrap();
+            assert_eq!(w.write_bit(false), Err(false));
+
+            let mut r = BitReader::new(bytes);
+            assert_eq!(r.read_bit(), Some(true));
+            assert_eq!(r.read_bits(64), Some(0x1234_5678_9ABC_DEF0));
+            assert_eq!(r.read_bits(3), Some(0b101));
+            assert_eq!(r.read_bits(0), Some(0));
+            assert_eq!(r.read_bits(13), None);
+            assert_eq!(r.bit_position(), 68);
+            r.align_to_byte();
+            assert_eq!(r.read_bits(8), Some(0xAB));
+            assert_eq!(r.read_bit(), None);
+        }
+        assert_eq!(x[0], 0xE1);
+        assert_eq!(x[9], 0xAB);
+    }
+
+    #[test]
+    fn preserves_neighbours() {
+        let mut x = [0b1010_1010u8; 3];
+        {
+            let mut w = BitWriter::new(slice(&mut x));
+            w.seek_bits(3);
+            w.write_bits(!0, 15).unwrap();
+        }
+        assert_eq!(x, [0b1111_1010, 0xFF, 0b1010_1011]);
+    }
+
+    #[test]
+    fn smoke_bit_cells() {
+        let mut x = 0b1001u8;
+        {
+            let b = bits(&mut x);
+            let c = b;
+            assert!(b.get_bit(0) && !b.get_bit(1) && b.all(0b1001) && !b.all(0b1011));
+            c.set_bit(0, false);
+            c.set_bit(7, true);
+            b.toggle(1);
+            assert_eq!(format!("{:?}", c), "BitCells(0b10001010)");
+        }
+        assert_eq!(x, 0b1000_1010);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn bit_out_of_range() {
+        let mut x = 0u16;
+        bits(&mut x).toggle(16);
+    }
+
+    #[test]
+    fn smoke_bitset() {
+        let mut a = [0u8; 2];
+        let mut b = [0u8; 2];
+        {
+            let (a, b) = (bitset(&mut a), bitset(&mut b));
+            assert_eq!(a.capacity(), 16);
+            assert!(a.insert(3) && !a.insert(3));
+            a.set(9);
+            a.toggle(15);
+            b.set(9);
+            b.set(0);
+            a.union_with(&b);
+            assert_eq!(format!("{:?}", a), "{0, 3, 9, 15}");
+            a.difference_with(&b);
+            a.clear(15);
+            assert_eq!((a.count_ones(), a.test(3)), (1, true));
+            a.intersect_with(&b);
+            assert!(a.is_clear());
+            b.clear_all();
+        }
+        assert_eq!((a, b), ([0; 2], [0; 2]));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn bitset_out_of_range() {
+        let mut a = [0u32];
+        bitset(&mut a).test(32);
+    }
+}
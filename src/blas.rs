@@ -0,0 +1,145 @@
+//! BLAS level-1 style vector operations over aliased float slices.
+//!
+//! Since the arguments are cells, they are allowed to overlap (or be
+//! the same slice entirely); in that case the operations behave as if
+//! performed one element at a time, in order. When the arguments are
+//! disjoint, a fast path operating on plain slices is taken, which the
+//! compiler is able to vectorise.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use alias::blas;
+//!
+//! let mut x = [1.0, 2.0, 3.0];
+//! let mut y = [10.0, 20.0, 30.0];
+//! let x = alias::slice(&mut x);
+//! let y = alias::slice(&mut y);
+//!
+//! blas::axpy(2.0, x, y);
+//! assert_eq!(y[2].get(), 36.0);
+//!
+//! blas::scale(0.5, y);
+//! assert_eq!(blas::dot(x, y), 6.0 + 24.0 + 54.0);
+//! ```
+
+use std::cell::Cell;
+use std::ops::{Add, Mul};
+use std::slice;
+
+/// Floating point types supported by this module.
+///
+/// This is implemented for `f32` and `f64`, and cannot be implemented
+/// outside this crate.
+pub trait Float: Copy + Add<Output = Self> + Mul<Output = Self> + private::Sealed {
+    /// Zero.
+    fn zero() -> Self;
+}
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for f32 {}
+    impl Sealed for f64 {}
+}
+
+impl Float for f32 {
+    fn zero() -> f32 { 0.0 }
+}
+impl Float for f64 {
+    fn zero() -> f64 { 0.0 }
+}
+
+fn overlaps<T>(a: &[Cell<T>], b: &[Cell<T>]) -> bool {
+    let a = a.as_ptr_range();
+    let b = b.as_ptr_range();
+    a.start < b.end && b.start < a.end
+}
+
+// These are sound for the duration of a call into this module: cells
+// are `!Sync`, and `Float` is sealed, so the arithmetic is that of
+// `f32` or `f64` and no user code runs while the plain slices are
+// live. Nothing else can observe or mutate the data through an alias.
+unsafe fn as_plain<T>(x: &[Cell<T>]) -> &[T] {
+    slice::from_raw_parts(x.as_ptr() as *const T, x.len())
+}
+#[allow(clippy::mut_from_ref)]
+unsafe fn as_plain_mut<T>(x: &[Cell<T>]) -> &mut [T] {
+    slice::from_raw_parts_mut(x.as_ptr() as *mut T, x.len())
+}
+
+/// Compute `y = alpha * x + y`.
+///
+/// # Panics
+///
+/// Panics if `x` and `y` have different lengths.
+pub fn axpy<T: Float>(alpha: T, x: &[Cell<T>], y: &[Cell<T>]) {
+    assert_eq!(x.len(), y.len(), "blas::axpy: mismatched lengths");
+    if overlaps(x, y) {
+        for (a, b) in x.iter().zip(y) {
+            b.set(alpha * a.get() + b.get());
+        }
+    } else {
+        let (x, y) = unsafe { (as_plain(x), as_plain_mut(y)) };
+        for (a, b) in x.iter().zip(y) {
+            *b = alpha * *a + *b;
+        }
+    }
+}
+
+/// Compute the dot product of `x` and `y`.
+///
+/// # Panics
+///
+/// Panics if `x` and `y` have different lengths.
+pub fn dot<T: Float>(x: &[Cell<T>], y: &[Cell<T>]) -> T {
+    assert_eq!(x.len(), y.len(), "blas::dot: mismatched lengths");
+    // nothing is written, so overlap doesn't matter.
+    let (x, y) = unsafe { (as_plain(x), as_plain(y)) };
+    x.iter().zip(y).fold(T::zero(), |acc, (&a, &b)| acc + a * b)
+}
+
+/// Compute `x = alpha * x`.
+pub fn scale<T: Float>(alpha: T, x: &[Cell<T>]) {
+    for a in unsafe { as_plain_mut(x) } {
+        *a = alpha * *a;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slice;
+
+    #[test]
+    fn smoke_blas() {
+        let mut x = [1.0f64, 2.0, 3.0, 4.0];
+        let x = slice(&mut x);
+
+        // fully aliased
+        axpy(1.0, x, x);
+        assert_eq!(x.iter().map(Cell::get).collect::<Vec<_>>(), [2.0, 4.0, 6.0, 8.0]);
+
+        // partially overlapping, element-by-element semantics
+        axpy(1.0, &x[..3], &x[1..]);
+        assert_eq!(x.iter().map(Cell::get).collect::<Vec<_>>(), [2.0, 6.0, 12.0, 20.0]);
+
+        assert_eq!(dot(&x[..2], &x[2..]), 24.0 + 120.0);
+
+        scale(0.5, &x[1..]);
+        assert_eq!(x.iter().map(Cell::get).collect::<Vec<_>>(), [2.0, 3.0, 6.0, 10.0]);
+
+        let mut y = [1.0f32; 4];
+        let y = slice(&mut y);
+        let mut z = [1.0f32, 2.0, 3.0, 4.0];
+        axpy(-1.0, slice(&mut z), y);
+        assert_eq!(y.iter().map(Cell::get).collect::<Vec<_>>(), [0.0, -1.0, -2.0, -3.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "mismatched lengths")]
+    fn axpy_mismatch() {
+        let mut x = [1.0f32; 4];
+        let x = slice(&mut x);
+        axpy(1.0, x, &x[1..]);
+    }
+}
@@ -0,0 +1,319 @@
+use std::cell::Cell;
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+
+/// A `RefCell`-style view of a uniquely borrowed value, handing out
+/// shared or exclusive borrows checked at runtime.
+///
+/// This is for shared state that occasionally needs `&mut` access,
+/// such as to call methods taking `&mut self`, which a `Cell` can't
+/// provide. Any number of `borrow`s can coexist, but a `borrow_mut`
+/// can't overlap with anything else.
+///
+/// Created by `alias::dyn_borrow`.
+pub struct AliasRefCell<'a, T: 'a> {
+    ptr: *mut T,
+    // the number of shared borrows, or -1 while mutably borrowed.
+    borrows: Cell<isize>,
+    _marker: PhantomData<&'a mut T>,
+}
+
+/// The error returned when a borrow conflicts with an existing one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AlreadyBorrowed {
+    /// Whether the existing borrow is exclusive.
+    pub exclusive: bool,
+}
+
+impl fmt::Display for AlreadyBorrowed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.exclusive {
+            f.write_str("value is already mutably borrowed")
+        } else {
+            f.write_str("value is already borrowed")
+        }
+    }
+}
+
+impl Error for AlreadyBorrowed {}
+
+/// Allow `data` to be borrowed, immutably or mutably, while aliased,
+/// with the borrows checked at runtime.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut names = vec!["b".to_string(), "a".to_string()];
+/// {
+///     let shared = alias::dyn_borrow(&mut names);
+///     let (view, editor) = (&shared, &shared);
+///
+///     editor.borrow_mut().sort();
+///     {
+///         let first = view.borrow();
+///         assert_eq!(first[0], "a");
+///         // can't mutate while `first` is alive
+///         assert!(editor.try_borrow_mut().is_err());
+///     }
+///     editor.borrow_mut().push("c".to_string());
+/// }
+/// assert_eq!(names, ["a", "b", "c"]);
+/// ```
+pub fn dyn_borrow<T>(data: &mut T) -> AliasRefCell<'_, T> {
+    AliasRefCell { ptr: data, borrows: Cell::new(0), _marker: PhantomData }
+}
+
+impl<'a, T> AliasRefCell<'a, T> {
+    /// Borrow the value immutably, failing if it is mutably borrowed.
+    pub fn try_borrow(&self) -> Result<AliasRef<'_, T>, AlreadyBorrowed> {
+        let n = self.borrows.get();
+        if n < 0 {
+            return Err(AlreadyBorrowed { exclusive: true })
+        }
+        self.borrows.set(n + 1);
+        Ok(AliasRef { value: unsafe { &*self.ptr }, borrows: &self.borrows })
+    }
+
+    /// Borrow the value mutably, failing if it is borrowed at all.
+    pub fn try_borrow_mut(&self) -> Result<AliasRefMut<'_, T>, AlreadyBorrowed> {
+        match self.borrows.get() {
+            0 => {
+                self.borrows.set(-1);
+                Ok(AliasRefMut { value: unsafe { &mut *self.ptr }, borrows: &self.borrows })
+            }
+            n => Err(AlreadyBorrowed { exclusive: n < 0 }),
+        }
+    }
+
+    /// Borrow the value immutably.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is mutably borrowed.
+    pub fn borrow(&self) -> AliasRef<'_, T> {
+        match self.try_borrow() {
+            Ok(r) => r,
+            Err(e) => panic!("AliasRefCell::borrow: {}", e),
+        }
+    }
+
+    /// Borrow the value mutably.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is borrowed.
+    pub fn borrow_mut(&self) -> AliasRefMut<'_, T> {
+        match self.try_borrow_mut() {
+            Ok(r) => r,
+            Err(e) => panic!("AliasRefCell::borrow_mut: {}", e),
+        }
+    }
+
+    /// Call `f` with a reference to the value, returning its result.
+    ///
+    /// Unlike `Cell::get`, this doesn't copy the value, so reading one
+    /// field of a large struct only touches that field. (For a plain
+    /// `&Cell` of a struct, `cell_project!` does the same.) Writes
+    /// through other references to this `AliasRefCell` while `f` runs
+    /// will panic.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is mutably borrowed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #[derive(Clone, Copy)]
+    /// struct Frame {
+    ///     samples: [f32; 64],
+    ///     seq: u32,
+    /// }
+    ///
+    /// let mut frame = Frame { samples: [0.0; 64], seq: 7 };
+    /// {
+    ///     let f = alias::dyn_borrow(&mut frame);
+    ///     let (reader, writer) = (&f, &f);
+    ///
+    ///     assert_eq!(reader.with(|fr| fr.seq), 7);
+    ///     writer.set(Frame { samples: [1.0; 64], seq: 8 });
+    ///     let peak = reader.with(|fr| fr.samples.iter().cloned().fold(0.0, f32::max));
+    ///     assert_eq!(peak, 1.0);
+    /// }
+    /// assert_eq!(frame.seq, 8);
+    /// ```
+    pub fn with<R, F: FnOnce(&T) -> R>(&self, f: F) -> R {
+        match self.try_borrow() {
+            Ok(r) => f(&r),
+            Err(e) => panic!("AliasRefCell::with: {}", e),
+        }
+    }
+
+    /// Replace the value with `value`, returning the old one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is borrowed.
+    pub fn replace(&self, value: T) -> T {
+        match self.try_borrow_mut() {
+            Ok(mut r) => mem::replace(&mut *r, value),
+            Err(e) => panic!("AliasRefCell::replace: {}", e),
+        }
+    }
+
+    /// Set the value to `value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is borrowed.
+    pub fn set(&self, value: T) {
+        // the old value is dropped once the borrow has ended, in case
+        // its destructor uses this `AliasRefCell`.
+        match self.try_borrow_mut() {
+            Ok(mut r) => {
+                let old = mem::replace(&mut *r, value);
+                drop(r);
+                drop(old)
+            }
+            Err(e) => panic!("AliasRefCell::set: {}", e),
+        }
+    }
+
+    /// The value, without any checks, as nothing else can be
+    /// borrowing it.
+    pub fn get_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.ptr }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for AliasRefCell<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.try_borrow() {
+            Ok(r) => f.debug_tuple("AliasRefCell").field(&*r).finish(),
+            Err(_) => f.write_str("AliasRefCell(<borrowed>)"),
+        }
+    }
+}
+
+/// A shared borrow of the value in an `AliasRefCell`.
+///
+/// Created by `AliasRefCell::borrow`.
+pub struct AliasRef<'b, T: 'b> {
+    value: &'b T,
+    borrows: &'b Cell<isize>,
+}
+
+impl<'b, T> Deref for AliasRef<'b, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<'b, T> Drop for AliasRef<'b, T> {
+    fn drop(&mut self) {
+        self.borrows.set(self.borrows.get() - 1)
+    }
+}
+
+impl<'b, T: fmt::Debug> fmt::Debug for AliasRef<'b, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+/// An exclusive borrow of the value in an `AliasRefCell`.
+///
+/// Created by `AliasRefCell::borrow_mut`.
+pub struct AliasRefMut<'b, T: 'b> {
+    value: &'b mut T,
+    borrows: &'b Cell<isize>,
+}
+
+impl<'b, T> Deref for AliasRefMut<'b, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<'b, T> DerefMut for AliasRefMut<'b, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value
+    }
+}
+
+impl<'b, T> Drop for AliasRefMut<'b, T> {
+    fn drop(&mut self) {
+        self.borrows.set(0)
+    }
+}
+
+impl<'b, T: fmt::Debug> fmt::Debug for AliasRefMut<'b, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_dyn_borrow() {
+        let mut x = String::from("a");
+        {
+            let mut c = dyn_borrow(&mut x);
+            {
+                let a = c.borrow();
+                let b = c.borrow();
+                assert_eq!((&**a, &**b), ("a", "a"));
+                assert_eq!(c.try_borrow_mut().unwrap_err(), AlreadyBorrowed { exclusive: false });
+                assert_eq!(format!("{:?}", c), r#"AliasRefCell("a")"#);
+            }
+            {
+                let mut m = c.borrow_mut();
+                m.push('b');
+                assert_eq!(c.try_borrow().unwrap_err().to_string(),
+                           "value is already mutably borrowed");
+                assert_eq!(format!("{:?} {:?}", c, m), r#"AliasRefCell(<borrowed>) "ab""#);
+            }
+            c.get_mut().push('c');
+            assert_eq!(*c.borrow(), "abc");
+        }
+        assert_eq!(x, "abc");
+    }
+
+    #[test]
+    fn smoke_with() {
+        let mut x = vec![1, 2];
+        {
+            let c = dyn_borrow(&mut x);
+            assert_eq!(c.with(|v| v.len()), 2);
+            assert_eq!(c.replace(vec![3]), [1, 2]);
+            c.set(vec![4, 5, 6]);
+            assert_eq!(c.with(|v| c.with(|w| v[0] + w[2])), 10);
+        }
+        assert_eq!(x, [4, 5, 6]);
+    }
+
+    #[test]
+    #[should_panic(expected = "AliasRefCell::set: value is already borrowed")]
+    fn set_while_reading() {
+        let mut x = 0;
+        let c = dyn_borrow(&mut x);
+        c.with(|_| c.set(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "already borrowed")]
+    fn borrow_mut_while_borrowed() {
+        let mut x = 0;
+        let c = dyn_borrow(&mut x);
+        let _a = c.borrow();
+        c.borrow_mut();
+    }
+}
@@ -0,0 +1,190 @@
+//! Aliased mutation of non-`Copy` data, checked at compile time by a
+//! branded token.
+//!
+//! A `Cell` can only move whole values in and out, which is awkward
+//! for data that isn't `Copy`, like the nodes of a graph holding
+//! `String`s and `Vec`s. A `BrandedCell<'brand, T>` can be aliased
+//! just as freely, but hands out plain `&T` and `&mut T` references:
+//! reading needs a shared borrow of the one `AliasToken<'brand>`, and
+//! writing needs a unique borrow of it. The borrow checker then
+//! ensures a `&mut T` from one cell never coexists with any other
+//! reference into any cell of the same brand.
+//!
+//! Each call to `with_token` creates a new brand, a lifetime that
+//! can't be unified with any other, so cells of one brand can't be
+//! unlocked by the token of another. This is the "GhostCell"
+//! technique.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use alias::branded::{self, BrandedCell};
+//!
+//! let mut names = vec![String::from("ada"), String::from("grace")];
+//! branded::with_token(|mut token| {
+//!     let cells = BrandedCell::from_mut_slice(&mut names);
+//!     // any number of aliased handles to the same element
+//!     let (first, also_first) = (&cells[0], &cells[0]);
+//!
+//!     first.borrow_mut(&mut token).push_str(" lovelace");
+//!     also_first.borrow_mut(&mut token).make_ascii_uppercase();
+//!     let len = cells[1].borrow(&token).len();
+//!     cells[1].borrow_mut(&mut token).truncate(len - 2);
+//!     assert_eq!(first.borrow(&token), "ADA LOVELACE");
+//! });
+//! assert_eq!(names, ["ADA LOVELACE", "gra"]);
+//! ```
+//!
+//! A mutable reference keeps the token borrowed, so it can't be held
+//! alongside any other reference of the same brand:
+//!
+//! ```rust,compile_fail
+//! use alias::branded::{self, BrandedCell};
+//!
+//! let mut x = String::new();
+//! branded::with_token(|mut token| {
+//!     let c = BrandedCell::from_mut(&mut x);
+//!     let a = c.borrow_mut(&mut token);
+//!     let b = c.borrow(&token);
+//!     a.push_str(b);
+//! });
+//! ```
+//!
+//! and a token can't unlock cells of another brand:
+//!
+//! ```rust,compile_fail
+//! use alias::branded::{self, BrandedCell};
+//!
+//! let mut x = 0;
+//! branded::with_token(|_outer| {
+//!     let c = BrandedCell::from_mut(&mut x);
+//!     branded::with_token(|mut inner| {
+//!         *c.borrow_mut(&mut inner) += 1;
+//!     });
+//! });
+//! ```
+
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// An invariant lifetime, so that brands can't be shortened or
+/// lengthened into each other.
+type Brand<'brand> = PhantomData<fn(&'brand ()) -> &'brand ()>;
+
+/// The key to every `BrandedCell` of the brand `'brand`.
+///
+/// There is exactly one token for each brand, created by
+/// `with_token`.
+pub struct AliasToken<'brand> {
+    _brand: Brand<'brand>,
+}
+
+/// Call `f` with the token of a new, unique brand.
+pub fn with_token<R, F>(f: F) -> R
+    where F: for<'brand> FnOnce(AliasToken<'brand>) -> R
+{
+    f(AliasToken { _brand: PhantomData })
+}
+
+impl<'brand> fmt::Debug for AliasToken<'brand> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("AliasToken")
+    }
+}
+
+/// A value that can be aliased, and read or written through the
+/// `AliasToken` of brand `'brand`.
+#[repr(transparent)]
+pub struct BrandedCell<'brand, T: ?Sized> {
+    _brand: Brand<'brand>,
+    value: UnsafeCell<T>,
+}
+
+// accessing the value needs the token, which is borrowed shared for
+// reads and uniquely for writes, like the value itself would be.
+unsafe impl<'brand, T: ?Sized + Send> Send for BrandedCell<'brand, T> {}
+unsafe impl<'brand, T: ?Sized + Send + Sync> Sync for BrandedCell<'brand, T> {}
+
+impl<'brand, T> BrandedCell<'brand, T> {
+    /// Create a cell holding `value`.
+    pub fn new(value: T) -> BrandedCell<'brand, T> {
+        BrandedCell { _brand: PhantomData, value: UnsafeCell::new(value) }
+    }
+
+    /// Take the value out of the cell.
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    /// Allow the elements of `data` to be aliased, and read or written
+    /// with the token.
+    pub fn from_mut_slice(data: &mut [T]) -> &[BrandedCell<'brand, T>] {
+        // `BrandedCell<T>` has the same layout as `T`.
+        unsafe { &*(data as *mut [T] as *const [BrandedCell<'brand, T>]) }
+    }
+}
+
+impl<'brand, T: ?Sized> BrandedCell<'brand, T> {
+    /// Allow `data` to be aliased, and read or written with the
+    /// token.
+    pub fn from_mut(data: &mut T) -> &BrandedCell<'brand, T> {
+        // `BrandedCell<T>` has the same layout as `T`, and `data` is
+        // borrowed uniquely for as long as the result.
+        unsafe { &*(data as *mut T as *const BrandedCell<'brand, T>) }
+    }
+
+    /// A reference to the value, for as long as the token is borrowed.
+    pub fn borrow<'a>(&'a self, _token: &'a AliasToken<'brand>) -> &'a T {
+        // no `&mut` into any cell of this brand can exist while the
+        // token is borrowed shared.
+        unsafe { &*self.value.get() }
+    }
+
+    /// A mutable reference to the value, for as long as the token is
+    /// borrowed.
+    pub fn borrow_mut<'a>(&'a self, _token: &'a mut AliasToken<'brand>) -> &'a mut T {
+        // no other reference into any cell of this brand can exist
+        // while the token is borrowed uniquely.
+        unsafe { &mut *self.value.get() }
+    }
+
+    /// A mutable reference to the value, through unique access to the
+    /// cell itself.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+}
+
+impl<'brand, T: ?Sized> fmt::Debug for BrandedCell<'brand, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // the value can't be read without the token.
+        f.write_str("BrandedCell { .. }")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_branded() {
+        let mut adj = vec![vec![1], vec![], vec![0]];
+        with_token(|mut token| {
+            let nodes = BrandedCell::from_mut_slice(&mut adj);
+            // append each node's edges to those of the nodes it
+            // points at
+            for n in nodes {
+                for j in n.borrow(&token).clone() {
+                    let extra = n.borrow(&token).clone();
+                    nodes[j].borrow_mut(&mut token).extend(extra);
+                }
+            }
+            let mut own = BrandedCell::new(String::from("x"));
+            own.get_mut().push('y');
+            assert_eq!(format!("{:?} {:?}", token, own), "AliasToken BrandedCell { .. }");
+            assert_eq!(own.into_inner(), "xy");
+        });
+        assert_eq!(adj, [vec![1, 0], vec![1, 1], vec![0]]);
+    }
+}
@@ -0,0 +1,773 @@
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::ops::{Bound, Range, RangeBounds};
+use std::ptr;
+use std::slice;
+
+/// The error returned by `set_from_iter_exact` when the iterator's
+/// length doesn't match the slice's.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IterLengthError {
+    /// The iterator ran out after this many elements.
+    TooFew(usize),
+    /// The iterator had elements left once the slice was full.
+    TooMany,
+}
+
+impl fmt::Display for IterLengthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IterLengthError::TooFew(n) => write!(f, "iterator ended after {} elements", n),
+            IterLengthError::TooMany => f.write_str("iterator is longer than the slice"),
+        }
+    }
+}
+
+impl Error for IterLengthError {}
+
+/// Write the elements of `iter` into `data` in order, until either
+/// runs out, returning how many were written.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut x = [0; 5];
+/// let n = alias::set_from_iter(alias::slice(&mut x), (1..).map(|i| i * i));
+/// assert_eq!(n, 5);
+/// assert_eq!(x, [1, 4, 9, 16, 25]);
+/// ```
+pub fn set_from_iter<T, I>(data: &[Cell<T>], iter: I) -> usize
+    where I: IntoIterator<Item = T>
+{
+    let mut n = 0;
+    for (c, v) in data.iter().zip(iter) {
+        c.set(v);
+        n += 1;
+    }
+    n
+}
+
+/// Write the elements of `iter` into `data`, requiring it to yield
+/// exactly `data.len()` of them.
+///
+/// On error, the elements already yielded have still been written.
+/// At most one element past the end of `data` is pulled from the
+/// iterator.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::IterLengthError;
+///
+/// let mut x = [0; 3];
+/// let cells = alias::slice(&mut x);
+/// assert_eq!(alias::set_from_iter_exact(cells, 1..4), Ok(()));
+/// assert_eq!(alias::set_from_iter_exact(cells, 7..9), Err(IterLengthError::TooFew(2)));
+/// assert_eq!(alias::set_from_iter_exact(cells, 0..), Err(IterLengthError::TooMany));
+/// ```
+pub fn set_from_iter_exact<T, I>(data: &[Cell<T>], iter: I) -> Result<(), IterLengthError>
+    where I: IntoIterator<Item = T>
+{
+    let mut iter = iter.into_iter();
+    let n = set_from_iter(data, iter.by_ref());
+    if n < data.len() {
+        Err(IterLengthError::TooFew(n))
+    } else if iter.next().is_some() {
+        Err(IterLengthError::TooMany)
+    } else {
+        Ok(())
+    }
+}
+
+/// Bulk operations and searches on aliased slices, working with the
+/// contents of the cells.
+///
+/// Slices already have inherent `fill`, `copy_from_slice`, `to_vec`
+/// and `contains` methods that work with `Cell`s rather than their
+/// contents (and would be chosen over trait methods of the same
+/// name), so these are named differently.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::CellSliceExt;
+///
+/// let mut frame = [0u8; 8];
+/// let cells = alias::slice(&mut frame);
+///
+/// cells[..4].set_all(0xFF);
+/// cells[4..].copy_from(&[1, 2, 3, 4]);
+/// cells[2..6].copy_from(&cells[4..].to_plain_vec());
+///
+/// let mut out = [0; 8];
+/// cells.copy_to(&mut out);
+/// assert_eq!(out, [0xFF, 0xFF, 1, 2, 3, 4, 3, 4]);
+/// ```
+pub trait CellSliceExt<T: Copy> {
+    /// Set every element to `value`.
+    fn set_all(&self, value: T);
+
+    /// Copy all of `src` into `self`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths differ.
+    fn copy_from(&self, src: &[T]);
+
+    /// Copy all of `self` into `dst`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths differ.
+    fn copy_to(&self, dst: &mut [T]);
+
+    /// Copy the contents into a new `Vec`.
+    #[doc(alias = "snapshot")]
+    fn to_plain_vec(&self) -> Vec<T>;
+
+    /// Swap the elements at indices `a` and `b`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either index is out of bounds.
+    fn swap(&self, a: usize, b: usize);
+
+    /// Reverse the order of the elements.
+    fn reverse(&self);
+
+    /// Rotate the elements so the one at index `mid` becomes the
+    /// first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mid` is larger than the length.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use alias::CellSliceExt;
+    ///
+    /// let mut x = [1, 2, 3, 4, 5];
+    /// let cells = alias::slice(&mut x);
+    /// cells.rotate_left(2);
+    /// cells[..2].reverse();
+    /// cells.swap(0, 4);
+    /// assert_eq!(x, [2, 3, 5, 1, 4]);
+    /// ```
+    fn rotate_left(&self, mid: usize);
+
+    /// Rotate the elements so the one `k` from the end becomes the
+    /// first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is larger than the length.
+    fn rotate_right(&self, k: usize);
+
+    /// Copy the elements in `src` to start at index `dest`, as
+    /// `slice::copy_within`.
+    ///
+    /// The ranges may overlap: the result is as if `src` were copied
+    /// out first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` is out of bounds, or `dest` is too close to the
+    /// end for all of `src` to fit.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use alias::CellSliceExt;
+    ///
+    /// let mut x = *b"abcdef";
+    /// let cells = alias::slice(&mut x);
+    /// cells.copy_within(..4, 2);
+    /// assert_eq!(&x, b"ababcd");
+    /// ```
+    fn copy_within<R: RangeBounds<usize>>(&self, src: R, dest: usize);
+
+    /// Swap the elements in `a` with the same number of elements
+    /// starting at index `b`.
+    ///
+    /// The ranges may overlap, in which case this behaves like
+    /// `ptr::swap`: `a` is copied out, the elements at `b` are copied
+    /// (as by `copy_within`) into `a`'s place, and then the copy of `a`
+    /// is written starting at `b`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` is out of bounds, or `b` is too close to the end
+    /// for a range the length of `a` to fit.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use alias::CellSliceExt;
+    ///
+    /// let mut x = [1, 2, 3, 4, 5, 6];
+    /// let cells = alias::slice(&mut x);
+    /// cells.swap_ranges(..2, 4);
+    /// assert_eq!(cells.to_plain_vec(), [5, 6, 3, 4, 1, 2]);
+    /// cells.swap_ranges(0..3, 1);
+    /// assert_eq!(x, [6, 5, 6, 3, 1, 2]);
+    /// ```
+    fn swap_ranges<R: RangeBounds<usize>>(&self, a: R, b: usize);
+
+    /// Call `f` with the elements in `bounds` as a plain mutable slice,
+    /// returning its result.
+    ///
+    /// The elements are copied out into a temporary buffer, which `f`
+    /// can process with ordinary (and vectorisable) slice code, and
+    /// then copied back, both at `memcpy` speed. Any writes to those
+    /// cells through other references while `f` runs are overwritten.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bounds` extends past the end of the slice.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use alias::CellSliceExt;
+    ///
+    /// let mut samples = [0.5f32, -1.0, 0.25, 2.0];
+    /// let cells = alias::slice(&mut samples);
+    ///
+    /// let peak = cells.apply(1.., |s| {
+    ///     for x in s.iter_mut() {
+    ///         *x *= 0.5;
+    ///     }
+    ///     s.iter().cloned().fold(0.0, f32::max)
+    /// });
+    /// assert_eq!(peak, 1.0);
+    /// assert_eq!(samples, [0.5, -0.5, 0.125, 1.0]);
+    /// ```
+    fn apply<R, U, F>(&self, bounds: R, f: F) -> U
+        where R: RangeBounds<usize>, F: FnOnce(&mut [T]) -> U;
+
+    /// Call `f` with the elements in `bounds` as a plain mutable slice,
+    /// in place, returning its result.
+    ///
+    /// This is `apply` without the copies.
+    ///
+    /// # Safety
+    ///
+    /// While `f` runs, the cells in `bounds` must not be read or written
+    /// through any other reference, such as one captured by `f`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bounds` extends past the end of the slice.
+    unsafe fn apply_unchecked<R, U, F>(&self, bounds: R, f: F) -> U
+        where R: RangeBounds<usize>, F: FnOnce(&mut [T]) -> U;
+
+    /// Iterate over copies of the elements.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use alias::CellSliceExt;
+    ///
+    /// let mut x = [1, 2, 3];
+    /// let cells = alias::slice(&mut x);
+    /// let total: i32 = cells.iter_copied().sum();
+    /// for c in cells {
+    ///     c.set(c.get() * 10 + total);
+    /// }
+    /// assert_eq!(cells.iter_copied().max(), Some(36));
+    /// assert_eq!(cells.iter_copied().collect::<Vec<_>>(), [16, 26, 36]);
+    /// ```
+    fn iter_copied(&self) -> IterCopied<'_, T>;
+
+    /// Whether any element is equal to `value`.
+    fn contains_value(&self, value: &T) -> bool
+        where T: PartialEq
+    {
+        self.position(value).is_some()
+    }
+
+    /// The index of the first element equal to `value`, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use alias::CellSliceExt;
+    ///
+    /// let mut x = ['a', 'b', 'c', 'b'];
+    /// let cells = alias::slice(&mut x);
+    /// assert_eq!(cells.position(&'b'), Some(1));
+    /// assert!(!cells.contains_value(&'d'));
+    /// ```
+    fn position(&self, value: &T) -> Option<usize>
+        where T: PartialEq;
+
+    /// Binary search a sorted slice with the comparison function `f`,
+    /// as `slice::binary_search_by`, but passing the elements by
+    /// value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use alias::CellSliceExt;
+    ///
+    /// let mut x = [1, 3, 5, 7];
+    /// let cells = alias::slice(&mut x);
+    /// assert_eq!(cells.binary_search_by_copy(|v| v.cmp(&5)), Ok(2));
+    /// assert_eq!(cells.binary_search_by_copy(|v| v.cmp(&4)), Err(2));
+    /// ```
+    fn binary_search_by_copy<F>(&self, f: F) -> Result<usize, usize>
+        where F: FnMut(T) -> Ordering;
+
+    /// The largest element, or the last of several equal ones, or
+    /// `None` if empty.
+    fn max_copy(&self) -> Option<T>
+        where T: Ord
+    {
+        self.max_by_copy(T::cmp)
+    }
+
+    /// The smallest element, or the first of several equal ones, or
+    /// `None` if empty.
+    fn min_copy(&self) -> Option<T>
+        where T: Ord
+    {
+        self.min_by_copy(T::cmp)
+    }
+
+    /// The largest element according to `compare`, or the last of
+    /// several equal ones, or `None` if empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use alias::CellSliceExt;
+    ///
+    /// let mut x = [0.5f64, -2.0, 1.5];
+    /// let cells = alias::slice(&mut x);
+    /// assert_eq!(cells.max_by_copy(|a, b| a.abs().total_cmp(&b.abs())), Some(-2.0));
+    /// assert_eq!(cells.min_by_copy(f64::total_cmp), Some(-2.0));
+    /// ```
+    fn max_by_copy<F>(&self, compare: F) -> Option<T>
+        where F: FnMut(&T, &T) -> Ordering;
+
+    /// The smallest element according to `compare`, or the first of
+    /// several equal ones, or `None` if empty.
+    fn min_by_copy<F>(&self, compare: F) -> Option<T>
+        where F: FnMut(&T, &T) -> Ordering;
+}
+
+impl<T: Copy> CellSliceExt<T> for [Cell<T>] {
+    fn set_all(&self, value: T) {
+        for c in self {
+            c.set(value)
+        }
+    }
+
+    fn copy_from(&self, src: &[T]) {
+        assert_eq!(self.len(), src.len(), "CellSliceExt::copy_from: mismatched lengths");
+        // `src` can't overlap any cells in `self`: a plain `&[T]` to
+        // them can't exist while `self` does.
+        unsafe { ptr::copy_nonoverlapping(src.as_ptr(), self.as_ptr() as *mut T, src.len()) }
+    }
+
+    fn copy_to(&self, dst: &mut [T]) {
+        assert_eq!(self.len(), dst.len(), "CellSliceExt::copy_to: mismatched lengths");
+        unsafe { ptr::copy_nonoverlapping(self.as_ptr() as *const T, dst.as_mut_ptr(), dst.len()) }
+    }
+
+    fn to_plain_vec(&self) -> Vec<T> {
+        let mut v = Vec::with_capacity(self.len());
+        unsafe {
+            ptr::copy_nonoverlapping(self.as_ptr() as *const T, v.as_mut_ptr(), self.len());
+            v.set_len(self.len());
+        }
+        v
+    }
+
+    fn copy_within<R: RangeBounds<usize>>(&self, src: R, dest: usize) {
+        let src = range(src, self.len(), "CellSliceExt::copy_within");
+        let n = src.end - src.start;
+        assert!(dest <= self.len() - n, "CellSliceExt::copy_within: dest out of bounds");
+        let p = self.as_ptr() as *mut T;
+        // in bounds, checked above; `ptr::copy` allows overlap.
+        unsafe { ptr::copy(p.add(src.start), p.add(dest), n) }
+    }
+
+    fn swap_ranges<R: RangeBounds<usize>>(&self, a: R, b: usize) {
+        let a = range(a, self.len(), "CellSliceExt::swap_ranges");
+        let n = a.end - a.start;
+        assert!(b <= self.len() - n, "CellSliceExt::swap_ranges: b out of bounds");
+        if a.end <= b || b + n <= a.start {
+            for i in 0..n {
+                self[a.start + i].swap(&self[b + i]);
+            }
+        } else {
+            let tmp = self[a.clone()].to_plain_vec();
+            self.copy_within(b..b + n, a.start);
+            self[b..b + n].copy_from(&tmp);
+        }
+    }
+
+    fn apply<R, U, F>(&self, bounds: R, f: F) -> U
+        where R: RangeBounds<usize>, F: FnOnce(&mut [T]) -> U
+    {
+        let cells = &self[range(bounds, self.len(), "CellSliceExt::apply")];
+        let mut buf = cells.to_plain_vec();
+        let ret = f(&mut buf);
+        cells.copy_from(&buf);
+        ret
+    }
+
+    unsafe fn apply_unchecked<R, U, F>(&self, bounds: R, f: F) -> U
+        where R: RangeBounds<usize>, F: FnOnce(&mut [T]) -> U
+    {
+        let cells = &self[range(bounds, self.len(), "CellSliceExt::apply_unchecked")];
+        f(slice::from_raw_parts_mut(cells.as_ptr() as *mut T, cells.len()))
+    }
+
+    fn iter_copied(&self) -> IterCopied<'_, T> {
+        IterCopied { iter: self.iter() }
+    }
+
+    fn swap(&self, a: usize, b: usize) {
+        self[a].swap(&self[b])
+    }
+
+    fn reverse(&self) {
+        let n = self.len();
+        for i in 0..n / 2 {
+            self[i].swap(&self[n - 1 - i]);
+        }
+    }
+
+    fn rotate_left(&self, mid: usize) {
+        assert!(mid <= self.len(), "CellSliceExt::rotate_left: mid out of bounds");
+        self[..mid].reverse();
+        self[mid..].reverse();
+        self.reverse();
+    }
+
+    fn rotate_right(&self, k: usize) {
+        assert!(k <= self.len(), "CellSliceExt::rotate_right: k out of bounds");
+        self.rotate_left(self.len() - k)
+    }
+
+    fn position(&self, value: &T) -> Option<usize>
+        where T: PartialEq
+    {
+        self.iter().position(|c| c.get() == *value)
+    }
+
+    fn binary_search_by_copy<F>(&self, mut f: F) -> Result<usize, usize>
+        where F: FnMut(T) -> Ordering
+    {
+        self.binary_search_by(|c| f(c.get()))
+    }
+
+    fn max_by_copy<F>(&self, compare: F) -> Option<T>
+        where F: FnMut(&T, &T) -> Ordering
+    {
+        self.iter().map(Cell::get).max_by(compare)
+    }
+
+    fn min_by_copy<F>(&self, compare: F) -> Option<T>
+        where F: FnMut(&T, &T) -> Ordering
+    {
+        self.iter().map(Cell::get).min_by(compare)
+    }
+}
+
+/// Moving values in and out of aliased slices of any type, not just
+/// `Copy` ones.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::CellSliceMoveExt;
+///
+/// let mut queue = vec![Some("parse".to_string()), Some("check".to_string()), None];
+/// let run = {
+///     let q = alias::slice(&mut queue);
+///     let steal = |i| q.take(i);
+///     let reschedule = |i, job: &str| q.replace(i, Some(format!("{} (again)", job)));
+///
+///     let job = steal(0).unwrap();
+///     assert_eq!(reschedule(2, &job), None);
+///     q.swap_remove_with_default(1).unwrap()
+/// };
+/// assert_eq!(run, "check");
+/// assert_eq!(queue, [None, Some("parse (again)".to_string()), None]);
+/// ```
+pub trait CellSliceMoveExt<T> {
+    /// Take element `i`, leaving `T::default()` in its place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds.
+    fn take(&self, i: usize) -> T
+        where T: Default;
+
+    /// Replace element `i` with `value`, returning the old value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds.
+    fn replace(&self, i: usize, value: T) -> T;
+
+    /// Take element `i`, moving the last element into its place and
+    /// leaving `T::default()` at the end.
+    ///
+    /// This is `Vec::swap_remove` for a slice that can't shrink: the
+    /// elements before the end stay packed, in a different order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds.
+    fn swap_remove_with_default(&self, i: usize) -> T
+        where T: Default;
+}
+
+impl<T> CellSliceMoveExt<T> for [Cell<T>] {
+    fn take(&self, i: usize) -> T
+        where T: Default
+    {
+        self[i].take()
+    }
+
+    fn replace(&self, i: usize, value: T) -> T {
+        self[i].replace(value)
+    }
+
+    fn swap_remove_with_default(&self, i: usize) -> T
+        where T: Default
+    {
+        let last = self.len().saturating_sub(1);
+        self[i].swap(&self[last]);
+        self[last].take()
+    }
+}
+
+/// Resolve `r` to a range within `0..len`, panicking with a message
+/// starting with `what` if it isn't.
+fn range<R: RangeBounds<usize>>(r: R, len: usize, what: &str) -> Range<usize> {
+    let start = match r.start_bound() {
+        Bound::Included(&s) => s,
+        Bound::Excluded(&s) => s.checked_add(1).unwrap_or_else(|| panic!("{}: range overflow", what)),
+        Bound::Unbounded => 0,
+    };
+    let end = match r.end_bound() {
+        Bound::Included(&e) => e.checked_add(1).unwrap_or_else(|| panic!("{}: range overflow", what)),
+        Bound::Excluded(&e) => e,
+        Bound::Unbounded => len,
+    };
+    assert!(start <= end && end <= len,
+            "{}: range {}..{} out of bounds for length {}", what, start, end, len);
+    start..end
+}
+
+/// An iterator over copies of the elements of an aliased slice.
+///
+/// Created by `CellSliceExt::iter_copied`. Each element is read as it
+/// is reached, so writes to later elements are seen.
+#[derive(Clone)]
+pub struct IterCopied<'a, T: 'a> {
+    iter: slice::Iter<'a, Cell<T>>,
+}
+
+impl<'a, T: Copy> Iterator for IterCopied<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.iter.next().map(Cell::get)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T: Copy> DoubleEndedIterator for IterCopied<'a, T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.iter.next_back().map(Cell::get)
+    }
+}
+
+impl<'a, T: Copy> ExactSizeIterator for IterCopied<'a, T> {}
+
+impl<'a, T> fmt::Debug for IterCopied<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("IterCopied").field("remaining", &self.iter.len()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slice;
+
+    #[test]
+    fn smoke_set_from_iter() {
+        let mut x = [0; 4];
+        {
+            let c = slice(&mut x);
+            assert_eq!(set_from_iter(c, vec![1, 2]), 2);
+            assert_eq!(set_from_iter(&c[3..], 9..), 1);
+            assert_eq!(set_from_iter(c, None), 0);
+            assert_eq!(c.iter().map(Cell::get).collect::<Vec<_>>(), [1, 2, 0, 9]);
+
+            let mut it = 10..20;
+            assert_eq!(set_from_iter_exact(&c[1..], &mut it), Err(IterLengthError::TooMany));
+            // one extra element was consumed
+            assert_eq!(it.next(), Some(14));
+            assert_eq!(set_from_iter_exact(&c[..0], None), Ok(()));
+            assert_eq!(IterLengthError::TooFew(1).to_string(), "iterator ended after 1 elements");
+        }
+        assert_eq!(x, [1, 10, 11, 12]);
+    }
+
+    #[test]
+    fn smoke_cell_slice_ext() {
+        let mut x = [0u16; 6];
+        {
+            let c = slice(&mut x);
+            c[1..].set_all(7);
+            c[..0].set_all(1);
+            c[3..5].copy_from(&[1, 2]);
+            assert_eq!(c.to_plain_vec(), [0, 7, 7, 1, 2, 7]);
+            let mut out = [0; 2];
+            c[4..].copy_to(&mut out);
+            assert_eq!(out, [2, 7]);
+            assert!(c[6..].to_plain_vec().is_empty());
+        }
+        assert_eq!(x, [0, 7, 7, 1, 2, 7]);
+    }
+
+    #[test]
+    fn smoke_search() {
+        let mut x = [(1, 'a'), (3, 'b'), (3, 'c'), (1, 'd')];
+        let c = slice(&mut x);
+        assert!(c.contains_value(&(3, 'c')));
+        assert_eq!(c.position(&(1, 'd')), Some(3));
+        assert_eq!(c.position(&(2, 'a')), None);
+        assert_eq!(c.max_copy(), Some((3, 'c')));
+        assert_eq!(c.min_copy(), Some((1, 'a')));
+        assert_eq!(c.max_by_copy(|a, b| a.0.cmp(&b.0)), Some((3, 'c')));
+        assert_eq!(c.min_by_copy(|a, b| a.0.cmp(&b.0)), Some((1, 'a')));
+        assert_eq!(c[..0].max_copy(), None);
+        assert_eq!(c[..3].binary_search_by_copy(|v| v.cmp(&(3, 'b'))), Ok(1));
+        assert_eq!(c[..3].binary_search_by_copy(|v| v.cmp(&(4, 'a'))), Err(3));
+    }
+
+    #[test]
+    fn smoke_iter_copied() {
+        let mut x = [1, 2, 3, 4];
+        let c = slice(&mut x);
+        let mut it = c.iter_copied();
+        assert_eq!(it.len(), 4);
+        assert_eq!((it.next(), it.next_back()), (Some(1), Some(4)));
+        c[1].set(20);
+        assert_eq!(it.clone().collect::<Vec<_>>(), [20, 3]);
+        assert_eq!(it.len(), 2);
+        assert!(c[..0].iter_copied().next().is_none());
+    }
+
+    #[test]
+    fn smoke_reorder() {
+        let mut x = [0, 1, 2, 3, 4, 5, 6];
+        {
+            let c = slice(&mut x);
+            c.swap(0, 6);
+            c.swap(3, 3);
+            assert_eq!(c.to_plain_vec(), [6, 1, 2, 3, 4, 5, 0]);
+            c.reverse();
+            c[..0].reverse();
+            assert_eq!(c.to_plain_vec(), [0, 5, 4, 3, 2, 1, 6]);
+            c.rotate_left(3);
+            assert_eq!(c.to_plain_vec(), [3, 2, 1, 6, 0, 5, 4]);
+            c.rotate_right(2);
+            assert_eq!(c.to_plain_vec(), [5, 4, 3, 2, 1, 6, 0]);
+            c.rotate_left(7);
+            c.rotate_right(0);
+        }
+        assert_eq!(x, [5, 4, 3, 2, 1, 6, 0]);
+    }
+
+    #[test]
+    fn smoke_overlapping() {
+        let mut x: Vec<u32> = (0..8).collect();
+        {
+            let c = slice(&mut x);
+            c.copy_within(2..6, 3);
+            assert_eq!(c.to_plain_vec(), [0, 1, 2, 2, 3, 4, 5, 7]);
+            c.copy_within(3.., 0);
+            assert_eq!(c.to_plain_vec(), [2, 3, 4, 5, 7, 4, 5, 7]);
+            c.copy_within(8.., 8);
+            c.swap_ranges(0..=1, 6);
+            assert_eq!(c.to_plain_vec(), [5, 7, 4, 5, 7, 4, 2, 3]);
+            c.swap_ranges(2..6, 1);
+        }
+        assert_eq!(x, [5, 4, 5, 7, 4, 7, 2, 3]);
+    }
+
+    #[test]
+    fn smoke_move() {
+        let mut x = vec![vec![1], vec![2, 2], vec![3, 3, 3]];
+        {
+            let c = slice(&mut x);
+            assert_eq!(CellSliceMoveExt::take(c, 1), [2, 2]);
+            assert_eq!(CellSliceMoveExt::replace(c, 1, vec![4]), []);
+            assert_eq!(c.swap_remove_with_default(0), [1]);
+            assert_eq!(c.swap_remove_with_default(2), []);
+        }
+        assert_eq!(x, [vec![3, 3, 3], vec![4], vec![]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn swap_remove_empty() {
+        let c: &[Cell<String>] = &[];
+        c.swap_remove_with_default(0);
+    }
+
+    #[test]
+    fn smoke_apply() {
+        let mut x = [5, 1, 4, 2, 3];
+        {
+            let c = slice(&mut x);
+            let n = c.apply(1..4, |s| {
+                // overwritten when the buffer is copied back
+                c[2].set(100);
+                s.sort();
+                s.len()
+            });
+            assert_eq!((n, c.to_plain_vec()), (3, vec![5, 1, 2, 4, 3]));
+            let sum = unsafe { c.apply_unchecked(.., |s| { s.reverse(); s.iter().sum::<i32>() }) };
+            assert_eq!(sum, 15);
+        }
+        assert_eq!(x, [3, 4, 2, 1, 5]);
+    }
+
+    #[test]
+    #[should_panic(expected = "dest out of bounds")]
+    fn copy_within_past_end() {
+        let mut x = [0; 3];
+        slice(&mut x).copy_within(1.., 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rotate_past_end() {
+        let mut x = [0; 3];
+        slice(&mut x).rotate_right(4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn copy_from_mismatched() {
+        let mut x = [0; 3];
+        slice(&mut x).copy_from(&[1, 2]);
+    }
+}
@@ -0,0 +1,165 @@
+use std::cell::Cell;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr;
+
+/// A bump allocator over a borrowed byte buffer, handing out aliased
+/// values of any `Copy` type.
+///
+/// Allocations live as long as the shared borrow of the allocator
+/// they were made through. Resetting needs a unique borrow, so it can
+/// only happen once every allocation is gone.
+///
+/// The allocator only exists inside `CellBump::with`. Values may
+/// contain padding, which mustn't be visible as bytes once the buffer
+/// is returned, so the bytes used are zeroed by `reset` and when
+/// `with` returns (or unwinds), which can't be skipped by leaking the
+/// allocator.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::CellBump;
+///
+/// let mut scratch = [0u8; 64];
+/// CellBump::with(&mut scratch, |bump| {
+///     for _frame in 0..3 {
+///         let pos = bump.alloc([0.0f32; 2]).unwrap();
+///         let count = bump.alloc(0u32).unwrap();
+///
+///         pos.set([1.0, 2.0]);
+///         count.set(count.get() + 1);
+///         assert_eq!(count.get(), 1);
+///
+///         bump.reset();
+///     }
+/// });
+/// ```
+pub struct CellBump<'a> {
+    start: *mut u8,
+    len: usize,
+    used: Cell<usize>,
+    _marker: PhantomData<&'a mut [u8]>,
+}
+
+impl<'a> CellBump<'a> {
+    /// Call `f` with an allocator using `buffer` as its memory,
+    /// returning its result.
+    ///
+    /// The bytes of `buffer` that were used are zeroed before this
+    /// returns.
+    pub fn with<R, F>(buffer: &mut [u8], f: F) -> R
+        where F: for<'b> FnOnce(&mut CellBump<'b>) -> R
+    {
+        // dropping `bump` clears the buffer, even if `f` panics. `f`
+        // only has a `&mut`, and can't create another allocator to
+        // swap in, so it can't leak it.
+        let mut bump = CellBump {
+            start: buffer.as_mut_ptr(),
+            len: buffer.len(),
+            used: Cell::new(0),
+            _marker: PhantomData,
+        };
+        f(&mut bump)
+    }
+
+    /// The total size of the buffer, in bytes.
+    pub fn capacity(&self) -> usize {
+        self.len
+    }
+
+    /// The number of bytes allocated so far, including alignment
+    /// padding.
+    pub fn used(&self) -> usize {
+        self.used.get()
+    }
+
+    /// Allocate space for `value`, suitably aligned, returning it as an
+    /// aliased cell, or `None` if there isn't enough space left.
+    pub fn alloc<T: Copy>(&self, value: T) -> Option<&Cell<T>> {
+        let base = self.start as usize;
+        let used = self.used.get();
+        let addr = (base + used).checked_add(mem::align_of::<T>() - 1)?
+            & !(mem::align_of::<T>() - 1);
+        let offset = addr - base;
+        let end = offset.checked_add(mem::size_of::<T>())?;
+        if end > self.len {
+            return None
+        }
+        self.used.set(end);
+        unsafe {
+            let p = self.start.add(offset) as *mut T;
+            ptr::write(p, value);
+            Some(&*(p as *const Cell<T>))
+        }
+    }
+
+    /// Free every allocation at once.
+    pub fn reset(&mut self) {
+        self.clear_used();
+        self.used.set(0);
+    }
+
+    fn clear_used(&mut self) {
+        unsafe { ptr::write_bytes(self.start, 0, self.used.get()) }
+    }
+}
+
+impl<'a> Drop for CellBump<'a> {
+    fn drop(&mut self) {
+        self.clear_used()
+    }
+}
+
+impl<'a> fmt::Debug for CellBump<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CellBump")
+            .field("capacity", &self.len)
+            .field("used", &self.used.get())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_bump() {
+        let mut buf = [0xAAu8; 32];
+        CellBump::with(&mut buf, |b| {
+            {
+                let a = b.alloc(1u8).unwrap();
+                let c = b.alloc(2u64).unwrap();
+                let d = b.alloc((3u8, 4u32)).unwrap();
+                assert_eq!(c as *const _ as usize % mem::align_of::<u64>(), 0);
+                assert_eq!(d as *const _ as usize % mem::align_of::<u32>(), 0);
+                a.set(a.get() + 1);
+                c.set(c.get() * 10);
+                assert_eq!((a.get(), c.get(), d.get()), (2, 20, (3, 4)));
+                assert!(b.alloc([0u64; 4]).is_none());
+                assert!(b.used() <= 32);
+            }
+            b.reset();
+            assert_eq!(b.used(), 0);
+            assert!(b.alloc([7u64; 3]).is_some());
+        });
+        // only the used prefix is cleared
+        assert!(buf.contains(&0xAA));
+        assert!(buf[..24].iter().all(|&x| x == 0));
+    }
+
+    #[test]
+    fn cleared_on_panic() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut buf = [0xAAu8; 16];
+        let r = catch_unwind(AssertUnwindSafe(|| CellBump::with(&mut buf, |b| {
+            b.alloc((1u8, 2u32)).unwrap();
+            panic!("oops")
+        })));
+        assert!(r.is_err());
+        assert!(buf[..8].iter().all(|&x| x == 0));
+    }
+}
@@ -0,0 +1,116 @@
+//! Searching and rewriting aliased byte buffers.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use alias::bytes;
+//!
+//! let mut buf = *b"GET / HTTP/1.1\r\nHost: x\r\n";
+//! let cells = alias::slice(&mut buf);
+//!
+//! assert_eq!(bytes::find_subslice(cells, b"\r\n"), Some(14));
+//! assert_eq!(bytes::replace_byte_in_place(cells, b'\r', b' '), 2);
+//! assert_eq!(bytes::find_byte(cells, b'\r'), None);
+//! ```
+
+use std::cell::Cell;
+use std::mem;
+use std::ptr;
+
+const LO: u64 = 0x0101_0101_0101_0101;
+const HI: u64 = 0x8080_8080_8080_8080;
+
+/// The index of the first occurrence of `byte` in `data`, if any.
+///
+/// This checks eight bytes at a time.
+pub fn find_byte(data: &[Cell<u8>], byte: u8) -> Option<usize> {
+    const WORD: usize = mem::size_of::<u64>();
+    let splat = LO * byte as u64;
+    let p = data.as_ptr() as *const u8;
+    let mut i = 0;
+    while i + WORD <= data.len() {
+        // nothing else can write to `data` during this function, so a
+        // wide read is the same as reading each cell.
+        let x = unsafe { ptr::read_unaligned(p.add(i) as *const u64) } ^ splat;
+        if x.wrapping_sub(LO) & !x & HI != 0 {
+            break
+        }
+        i += WORD;
+    }
+    data[i..].iter().position(|c| c.get() == byte).map(|j| i + j)
+}
+
+/// The index of the first occurrence of `needle` in `haystack`, if
+/// any. An empty needle is found at index 0.
+pub fn find_subslice(haystack: &[Cell<u8>], needle: &[u8]) -> Option<usize> {
+    let (&first, rest) = match needle.split_first() {
+        Some(x) => x,
+        None => return Some(0),
+    };
+    let mut start = 0;
+    while haystack.len() - start >= needle.len() {
+        let i = start + find_byte(&haystack[start..haystack.len() - rest.len()], first)?;
+        let candidate = &haystack[i + 1..i + needle.len()];
+        if candidate.iter().zip(rest).all(|(c, &b)| c.get() == b) {
+            return Some(i)
+        }
+        start = i + 1;
+    }
+    None
+}
+
+/// Replace every occurrence of `old` in `data` with `new`, returning
+/// how many were replaced.
+pub fn replace_byte_in_place(data: &[Cell<u8>], old: u8, new: u8) -> usize {
+    let mut count = 0;
+    let mut start = 0;
+    while let Some(i) = find_byte(&data[start..], old) {
+        data[start + i].set(new);
+        count += 1;
+        start += i + 1;
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slice;
+
+    #[test]
+    fn smoke_find() {
+        let mut x: Vec<u8> = (0..=255).collect();
+        let c = slice(&mut x);
+        for b in 0..=255u8 {
+            assert_eq!(find_byte(c, b), Some(b as usize));
+            // at every offset, to hit both the wide and tail paths
+            assert_eq!(find_byte(&c[b as usize..], b), Some(0));
+            assert_eq!(find_byte(&c[b as usize + 1..], b), None);
+        }
+        // bytes adjacent to a match mustn't produce false positives
+        let mut y = [0x01u8, 0x00, 0x81, 0x80, 0x02, 0xFF, 0x7F, 0x01, 0x00];
+        assert_eq!(find_byte(slice(&mut y), 0x00), Some(1));
+        assert_eq!(find_byte(&slice(&mut y)[2..], 0x00), Some(6));
+
+        assert_eq!(find_subslice(c, &[10, 11, 12]), Some(10));
+        assert_eq!(find_subslice(c, &[10, 12]), None);
+        assert_eq!(find_subslice(c, &[254, 255]), Some(254));
+        assert_eq!(find_subslice(c, &[255, 0]), None);
+        assert_eq!(find_subslice(c, &[]), Some(0));
+        assert_eq!(find_subslice(&c[..1], &[0, 1]), None);
+    }
+
+    #[test]
+    fn smoke_replace() {
+        let mut x = *b"aXbXXcdefghijXX";
+        assert_eq!(replace_byte_in_place(slice(&mut x), b'X', b'-'), 5);
+        assert_eq!(&x, b"a-b--cdefghij--");
+        assert_eq!(replace_byte_in_place(slice(&mut x), b'X', b'-'), 0);
+    }
+
+    #[test]
+    fn overlapping_prefix() {
+        let mut x = *b"aaab";
+        assert_eq!(find_subslice(slice(&mut x), b"aab"), Some(1));
+    }
+}
@@ -0,0 +1,151 @@
+use std::cell::Cell;
+use std::fmt;
+
+type Callback<'a, Args> = Box<dyn FnMut(Args) + 'a>;
+
+/// A slot for an `FnMut` callback that can be replaced and invoked
+/// through shared references, without `RefCell`.
+///
+/// Calling the callback moves it out of the slot for the duration of
+/// the call, so a callback that (directly or indirectly) calls its
+/// own slot again doesn't panic: the inner call simply finds the slot
+/// empty and does nothing. If the slot is `set` or `clear`ed during
+/// the call, that takes priority over putting the running callback
+/// back.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::CallbackCell;
+///
+/// let mut clicks = 0;
+/// let count = alias::one(&mut clicks);
+///
+/// let on_click = CallbackCell::new();
+/// assert!(!on_click.call(1));
+///
+/// on_click.set(|n: u32| count.set(count.get() + n));
+/// assert!(on_click.call(1));
+/// assert!(on_click.call(2));
+///
+/// on_click.clear();
+/// assert!(!on_click.call(3));
+/// assert_eq!(count.get(), 3);
+/// ```
+pub struct CallbackCell<'a, Args> {
+    slot: Cell<Option<Callback<'a, Args>>>,
+    generation: Cell<u64>,
+}
+
+impl<'a, Args> CallbackCell<'a, Args> {
+    /// Create an empty slot.
+    pub fn new() -> CallbackCell<'a, Args> {
+        CallbackCell { slot: Cell::new(None), generation: Cell::new(0) }
+    }
+
+    /// Store `f` as the callback, replacing any existing one.
+    pub fn set<F: FnMut(Args) + 'a>(&self, f: F) {
+        self.replace(Some(Box::new(f)))
+    }
+
+    /// Remove the callback, if any.
+    pub fn clear(&self) {
+        self.replace(None)
+    }
+
+    /// Whether there is currently a callback stored.
+    ///
+    /// This is `false` while the callback is running.
+    pub fn is_set(&self) -> bool {
+        let f = self.slot.take();
+        let ret = f.is_some();
+        self.slot.set(f);
+        ret
+    }
+
+    fn replace(&self, f: Option<Callback<'a, Args>>) {
+        self.generation.set(self.generation.get().wrapping_add(1));
+        // drop the old one after updating the slot, in case its
+        // destructor touches this cell.
+        drop(self.slot.replace(f))
+    }
+
+    /// Invoke the callback with `args`, returning whether there was
+    /// one to invoke.
+    pub fn call(&self, args: Args) -> bool {
+        match self.slot.take() {
+            None => false,
+            Some(mut f) => {
+                let gen = self.generation.get();
+                f(args);
+                if self.generation.get() == gen {
+                    self.slot.set(Some(f));
+                }
+                true
+            }
+        }
+    }
+}
+
+impl<'a, Args> Default for CallbackCell<'a, Args> {
+    fn default() -> CallbackCell<'a, Args> {
+        CallbackCell::new()
+    }
+}
+
+impl<'a, Args> fmt::Debug for CallbackCell<'a, Args> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CallbackCell")
+            .field("is_set", &self.is_set())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn smoke_reentrant() {
+        let log = Rc::new(Cell::new(0));
+        let cb: Rc<CallbackCell<u32>> = Rc::new(CallbackCell::new());
+
+        let (l, c) = (log.clone(), Rc::downgrade(&cb));
+        cb.set(move |n| {
+            l.set(l.get() + n);
+            let c = c.upgrade().unwrap();
+            // re-entrant call is a no-op
+            assert!(!c.call(100));
+            assert!(!c.is_set());
+        });
+        assert!(cb.call(1));
+        assert!(cb.call(2));
+        assert_eq!(log.get(), 3);
+        assert!(cb.is_set());
+        assert_eq!(format!("{:?}", cb), "CallbackCell { is_set: true }");
+    }
+
+    #[test]
+    fn replace_during_call() {
+        let hits = Cell::new((0, 0));
+        let cb: Rc<CallbackCell<()>> = Rc::new(CallbackCell::new());
+
+        let c = Rc::downgrade(&cb);
+        let hits_ = &hits;
+        cb.set(move |()| {
+            hits_.set((hits_.get().0 + 1, hits_.get().1));
+            c.upgrade().unwrap().set(move |()| hits_.set((hits_.get().0, hits_.get().1 + 1)));
+        });
+        assert!(cb.call(()));
+        assert!(cb.call(()));
+        assert!(cb.call(()));
+        assert_eq!(hits.get(), (1, 2));
+
+        let c = Rc::downgrade(&cb);
+        cb.set(move |()| c.upgrade().unwrap().clear());
+        assert!(cb.call(()));
+        assert!(!cb.is_set());
+        assert!(!cb.call(()));
+    }
+}
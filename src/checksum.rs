@@ -0,0 +1,154 @@
+//! Checksums over live aliased byte buffers, without snapshotting.
+//!
+//! By default the CRC is computed a byte at a time with a 1 KiB
+//! table; the `fast-crc` feature processes eight bytes at a time,
+//! using 8 KiB of tables.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use alias::checksum::{crc32, Crc32};
+//!
+//! let mut packet = *b"123456789";
+//! let cells = alias::slice(&mut packet);
+//! assert_eq!(crc32(cells), 0xCBF4_3926);
+//!
+//! let mut h = Crc32::new();
+//! h.update(&cells[..4]);
+//! h.update(&cells[4..]);
+//! assert_eq!(h.finish(), 0xCBF4_3926);
+//! ```
+
+use std::cell::Cell;
+
+// the reflected IEEE 802.3 polynomial, as used by zlib, PNG, etc.
+const POLY: u32 = 0xEDB8_8320;
+
+const fn tables<const N: usize>() -> [[u32; 256]; N] {
+    let mut t = [[0; 256]; N];
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 { POLY ^ (c >> 1) } else { c >> 1 };
+            k += 1;
+        }
+        t[0][i] = c;
+        i += 1;
+    }
+    let mut j = 1;
+    while j < N {
+        let mut i = 0;
+        while i < 256 {
+            let prev = t[j - 1][i];
+            t[j][i] = (prev >> 8) ^ t[0][(prev & 0xFF) as usize];
+            i += 1;
+        }
+        j += 1;
+    }
+    t
+}
+
+#[cfg(not(feature = "fast-crc"))]
+static TABLES: [[u32; 256]; 1] = tables();
+#[cfg(feature = "fast-crc")]
+static TABLES: [[u32; 256]; 8] = tables();
+
+/// Process as much of `data` as possible eight bytes at a time,
+/// returning the new state and the bytes left over.
+#[cfg(feature = "fast-crc")]
+fn update_wide(mut c: u32, data: &[Cell<u8>]) -> (u32, &[Cell<u8>]) {
+    let t = &TABLES;
+    let mut chunks = data.chunks_exact(8);
+    for b in &mut chunks {
+        let lo = c ^ u32::from_le_bytes([b[0].get(), b[1].get(), b[2].get(), b[3].get()]);
+        c = t[7][(lo & 0xFF) as usize] ^ t[6][(lo >> 8 & 0xFF) as usize]
+            ^ t[5][(lo >> 16 & 0xFF) as usize] ^ t[4][(lo >> 24) as usize]
+            ^ t[3][b[4].get() as usize] ^ t[2][b[5].get() as usize]
+            ^ t[1][b[6].get() as usize] ^ t[0][b[7].get() as usize];
+    }
+    (c, chunks.remainder())
+}
+
+#[cfg(not(feature = "fast-crc"))]
+fn update_wide(c: u32, data: &[Cell<u8>]) -> (u32, &[Cell<u8>]) {
+    (c, data)
+}
+
+/// The CRC-32 (IEEE) of `data`.
+pub fn crc32(data: &[Cell<u8>]) -> u32 {
+    let mut h = Crc32::new();
+    h.update(data);
+    h.finish()
+}
+
+/// An incremental CRC-32 (IEEE), which can be fed a buffer in pieces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Crc32 {
+    state: u32,
+}
+
+impl Crc32 {
+    /// Start a new checksum.
+    pub fn new() -> Crc32 {
+        Crc32 { state: !0 }
+    }
+
+    /// Add `data` to the checksum.
+    pub fn update(&mut self, data: &[Cell<u8>]) {
+        let (mut c, rest) = update_wide(self.state, data);
+        for b in rest {
+            c = (c >> 8) ^ TABLES[0][((c ^ b.get() as u32) & 0xFF) as usize];
+        }
+        self.state = c;
+    }
+
+    /// The checksum of everything added so far.
+    ///
+    /// This doesn't reset the state, so more data can be added
+    /// afterwards.
+    pub fn finish(&self) -> u32 {
+        !self.state
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Crc32 {
+        Crc32::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slice;
+
+    #[test]
+    fn smoke_crc32() {
+        let mut x: Vec<u8> = (0..100).map(|i| (i * 7 + 3) as u8).collect();
+        let c = slice(&mut x);
+        assert_eq!(crc32(&[]), 0);
+        assert_eq!(crc32(&c[..1]), 0x4B0B_BE37);
+
+        // the same regardless of how the input is split
+        let whole = crc32(c);
+        for split in 0..c.len() {
+            let mut h = Crc32::default();
+            h.update(&c[..split]);
+            assert_eq!(h.finish(), crc32(&c[..split]));
+            h.update(&c[split..]);
+            assert_eq!(h.finish(), whole);
+        }
+
+        // bitwise reference implementation
+        let mut r = !0u32;
+        for b in c.iter() {
+            r ^= b.get() as u32;
+            for _ in 0..8 {
+                r = if r & 1 != 0 { POLY ^ (r >> 1) } else { r >> 1 };
+            }
+        }
+        assert_eq!(whole, !r);
+    }
+}
@@ -0,0 +1,255 @@
+//! Hex and base64 transcoding within aliased byte buffers.
+//!
+//! Each function works on a single buffer, reading from the `input`
+//! range and writing starting at the `output` index, and returns the
+//! number of bytes written. The regions may overlap as long as the
+//! writes never overtake the reads: encoding (which expands the data)
+//! needs `output >= input.start`, and decoding (which shrinks it)
+//! needs `output <= input.start`. In particular, data can always be
+//! transcoded in place, starting at the same index.
+//!
+//! Nothing is written if an error is returned.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use alias::codec;
+//!
+//! let mut frame = *b"LOG:48656c6c6f\0\0\0\0\0\0\0\0";
+//! let cells = alias::slice(&mut frame);
+//!
+//! let n = codec::hex_decode_into(cells, 4..14, 4).unwrap();
+//! assert_eq!(&frame[4..4 + n], b"Hello");
+//!
+//! let cells = alias::slice(&mut frame);
+//! let m = codec::base64_encode_into(cells, 4..4 + n, 4).unwrap();
+//! assert_eq!(&frame[..4 + m], b"LOG:SGVsbG8=");
+//! ```
+
+use std::cell::Cell;
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+
+/// The ways transcoding can fail.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CodecError {
+    /// The input range, or the output that would be written, extends
+    /// past the end of the buffer.
+    OutOfBounds,
+    /// The output would overwrite input before it is read.
+    Overlap,
+    /// The input has a length that can't be decoded.
+    InvalidLength,
+    /// The input has an invalid byte at this index of the buffer.
+    InvalidByte(usize),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CodecError::OutOfBounds => f.write_str("region extends past the end of the buffer"),
+            CodecError::Overlap => f.write_str("output would overwrite unread input"),
+            CodecError::InvalidLength => f.write_str("invalid input length"),
+            CodecError::InvalidByte(i) => write!(f, "invalid input byte at index {}", i),
+        }
+    }
+}
+
+impl Error for CodecError {}
+
+const HEX: &[u8; 16] = b"0123456789abcdef";
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Check that the regions are in bounds and ordered correctly.
+fn check(buf: &[Cell<u8>], input: &Range<usize>, output: usize, out_len: usize,
+         expanding: bool) -> Result<(), CodecError> {
+    if input.start > input.end || input.end > buf.len()
+        || output.checked_add(out_len).is_none_or(|end| end > buf.len()) {
+        return Err(CodecError::OutOfBounds)
+    }
+    let disjoint = output + out_len <= input.start || input.end <= output;
+    let ordered = if expanding { output >= input.start } else { output <= input.start };
+    if disjoint || ordered { Ok(()) } else { Err(CodecError::Overlap) }
+}
+
+/// Encode `buf[input]` as lowercase hex, writing it starting at
+/// `buf[output]`.
+pub fn hex_encode_into(buf: &[Cell<u8>], input: Range<usize>, output: usize)
+                       -> Result<usize, CodecError> {
+    let out_len = input.len().checked_mul(2).ok_or(CodecError::OutOfBounds)?;
+    check(buf, &input, output, out_len, true)?;
+    // back to front, so the writes trail the reads.
+    for i in (0..input.len()).rev() {
+        let b = buf[input.start + i].get();
+        buf[output + 2 * i].set(HEX[(b >> 4) as usize]);
+        buf[output + 2 * i + 1].set(HEX[(b & 0xF) as usize]);
+    }
+    Ok(out_len)
+}
+
+fn hex_digit(b: u8) -> Option<u8> {
+    match b {
+        b'0'..=b'9' => Some(b - b'0'),
+        b'a'..=b'f' => Some(b - b'a' + 10),
+        b'A'..=b'F' => Some(b - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// Decode the hex (of either case) in `buf[input]`, writing the bytes
+/// starting at `buf[output]`.
+pub fn hex_decode_into(buf: &[Cell<u8>], input: Range<usize>, output: usize)
+                       -> Result<usize, CodecError> {
+    if input.len() & 1 != 0 {
+        return Err(CodecError::InvalidLength)
+    }
+    let out_len = input.len() / 2;
+    check(buf, &input, output, out_len, false)?;
+    if let Some(i) = input.clone().find(|&i| hex_digit(buf[i].get()).is_none()) {
+        return Err(CodecError::InvalidByte(i))
+    }
+    for i in 0..out_len {
+        let hi = hex_digit(buf[input.start + 2 * i].get()).unwrap();
+        let lo = hex_digit(buf[input.start + 2 * i + 1].get()).unwrap();
+        buf[output + i].set(hi << 4 | lo);
+    }
+    Ok(out_len)
+}
+
+/// Decode the hex at the start of `buf` over itself.
+///
+/// This is `hex_decode_into(buf, 0..buf.len(), 0)`.
+pub fn hex_decode_in_place(buf: &[Cell<u8>]) -> Result<usize, CodecError> {
+    hex_decode_into(buf, 0..buf.len(), 0)
+}
+
+/// Encode `buf[input]` as padded standard base64, writing it starting
+/// at `buf[output]`.
+pub fn base64_encode_into(buf: &[Cell<u8>], input: Range<usize>, output: usize)
+                          -> Result<usize, CodecError> {
+    let groups = input.len().div_ceil(3);
+    let out_len = groups.checked_mul(4).ok_or(CodecError::OutOfBounds)?;
+    check(buf, &input, output, out_len, true)?;
+    for g in (0..groups).rev() {
+        let start = input.start + 3 * g;
+        let n = (input.end - start).min(3);
+        let mut group = [0u8; 3];
+        for (j, b) in group.iter_mut().enumerate().take(n) {
+            *b = buf[start + j].get();
+        }
+        let bits = (group[0] as u32) << 16 | (group[1] as u32) << 8 | group[2] as u32;
+        for j in 0..4 {
+            let c = if j <= n { BASE64[(bits >> (18 - 6 * j)) as usize & 0x3F] } else { b'=' };
+            buf[output + 4 * g + j].set(c);
+        }
+    }
+    Ok(out_len)
+}
+
+fn base64_digit(b: u8) -> Option<u32> {
+    BASE64.iter().position(|&c| c == b).map(|i| i as u32)
+}
+
+/// Decode the standard base64 in `buf[input]`, writing the bytes
+/// starting at `buf[output]`.
+///
+/// Trailing `=` padding is optional.
+pub fn base64_decode_into(buf: &[Cell<u8>], input: Range<usize>, output: usize)
+                          -> Result<usize, CodecError> {
+    if input.start > input.end || input.end > buf.len() {
+        return Err(CodecError::OutOfBounds)
+    }
+    let mut end = input.end;
+    while end > input.start && input.end - end < 2 && buf[end - 1].get() == b'=' {
+        end -= 1;
+    }
+    let digits = end - input.start;
+    if digits % 4 == 1 || (end != input.end && input.len() & 3 != 0) {
+        return Err(CodecError::InvalidLength)
+    }
+    let out_len = digits / 4 * 3 + (digits % 4).saturating_sub(1);
+    check(buf, &input, output, out_len, false)?;
+    if let Some(i) = (input.start..end).find(|&i| base64_digit(buf[i].get()).is_none()) {
+        return Err(CodecError::InvalidByte(i))
+    }
+    for g in 0..digits.div_ceil(4) {
+        let start = input.start + 4 * g;
+        let n = (end - start).min(4);
+        let mut bits = 0;
+        for j in 0..4 {
+            let d = if j < n { base64_digit(buf[start + j].get()).unwrap() } else { 0 };
+            bits = bits << 6 | d;
+        }
+        for j in 0..n - 1 {
+            buf[output + 3 * g + j].set((bits >> (16 - 8 * j)) as u8);
+        }
+    }
+    Ok(out_len)
+}
+
+/// Decode the base64 at the start of `buf` over itself.
+///
+/// This is `base64_decode_into(buf, 0..buf.len(), 0)`.
+pub fn base64_decode_in_place(buf: &[Cell<u8>]) -> Result<usize, CodecError> {
+    base64_decode_into(buf, 0..buf.len(), 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slice;
+
+    #[test]
+    fn smoke_hex() {
+        let mut x = [0u8; 8];
+        x[..3].copy_from_slice(&[0x00, 0xAB, 0x7F]);
+        {
+            let c = slice(&mut x);
+            assert_eq!(hex_encode_into(c, 0..3, 0), Ok(6));
+            assert_eq!(hex_encode_into(c, 0..3, 3), Err(CodecError::OutOfBounds));
+            assert_eq!(hex_encode_into(c, 2..3, 1), Err(CodecError::Overlap));
+        }
+        assert_eq!(&x[..6], b"00ab7f");
+        x[2] = b'A';
+        {
+            let c = slice(&mut x);
+            assert_eq!(hex_decode_into(c, 0..5, 0), Err(CodecError::InvalidLength));
+            assert_eq!(hex_decode_into(c, 0..6, 1), Err(CodecError::Overlap));
+            assert_eq!(hex_decode_in_place(&c[..6]), Ok(3));
+            assert_eq!(hex_decode_in_place(&c[..2]), Err(CodecError::InvalidByte(0)));
+        }
+        assert_eq!(x, [0x00, 0xAB, 0x7F, b'b', b'7', b'f', 0, 0]);
+    }
+
+    #[test]
+    fn smoke_base64() {
+        let cases: &[(&[u8], &[u8])] = &[
+            (b"", b""),
+            (b"f", b"Zg=="),
+            (b"fo", b"Zm8="),
+            (b"foo", b"Zm9v"),
+            (b"foob", b"Zm9vYg=="),
+            (b"\xFF\xFE\x00\x01\x80", b"//4AAYA="),
+        ];
+        for &(raw, encoded) in cases {
+            let mut x = [0u8; 12];
+            x[..raw.len()].copy_from_slice(raw);
+            let c = slice(&mut x);
+            assert_eq!(base64_encode_into(c, 0..raw.len(), 0), Ok(encoded.len()));
+            assert_eq!(c[..encoded.len()].iter().map(Cell::get).collect::<Vec<_>>(), encoded);
+            assert_eq!(base64_decode_in_place(&c[..encoded.len()]), Ok(raw.len()));
+            assert_eq!(c[..raw.len()].iter().map(Cell::get).collect::<Vec<_>>(), raw);
+        }
+
+        let mut y = *b"Zm9vYg==";
+        let c = slice(&mut y);
+        // unpadded
+        assert_eq!(base64_decode_into(c, 0..6, 0), Ok(4));
+        c[0].set(b'*');
+        assert_eq!(base64_decode_into(c, 0..8, 0), Err(CodecError::InvalidByte(0)));
+        assert_eq!(base64_decode_into(c, 0..5, 0), Err(CodecError::InvalidLength));
+        assert_eq!(base64_decode_into(c, 0..7, 0), Err(CodecError::InvalidLength));
+        assert_eq!(base64_decode_into(c, 0..9, 0), Err(CodecError::OutOfBounds));
+    }
+}
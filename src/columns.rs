@@ -0,0 +1,120 @@
+use std::cell::Cell;
+
+use Slices;
+
+/// An iterator over the rows of several aliased columns.
+///
+/// Created by `alias::columns`.
+#[derive(Clone, Debug)]
+pub struct Rows<C> {
+    cols: C,
+    idx: usize,
+    len: usize,
+}
+
+/// Alias several equal-length slices at once, and iterate over them
+/// row by row, yielding a tuple of cells for each index.
+///
+/// `cols` is a tuple of up to 8 slices, as for `alias::slices`.
+///
+/// # Panics
+///
+/// Panics if the slices do not all have the same length.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut pos = [0.0, 1.0, 2.0];
+/// let mut vel = [1.0, 1.0, -1.0];
+/// let mut hits = [0u32; 3];
+///
+/// for (p, v, h) in alias::columns((&mut pos[..], &mut vel[..], &mut hits[..])) {
+///     p.set(p.get() + v.get());
+///     if p.get() >= 2.0 {
+///         v.set(-v.get());
+///         h.set(h.get() + 1);
+///     }
+/// }
+///
+/// assert_eq!(pos, [1.0, 2.0, 1.0]);
+/// assert_eq!(vel, [1.0, -1.0, -1.0]);
+/// assert_eq!(hits, [0, 1, 0]);
+/// ```
+pub fn columns<'a, C: Slices<'a>>(cols: C) -> Rows<C::Cells> {
+    let lens = cols.lens();
+    let len = lens[0];
+    assert!(lens.iter().all(|&l| l == len), "alias::columns: mismatched column lengths {:?}", lens);
+    Rows { cols: cols.cells(), idx: 0, len }
+}
+
+macro_rules! rows_impl {
+    ($($T: ident, $x: ident);*) => {
+        impl<'a, $($T),*> Iterator for Rows<($(&'a [Cell<$T>],)*)> {
+            type Item = ($(&'a Cell<$T>,)*);
+
+            fn next(&mut self) -> Option<Self::Item> {
+                if self.idx == self.len {
+                    return None
+                }
+                let i = self.idx;
+                self.idx += 1;
+                let ($($x,)*) = self.cols;
+                Some(($(&$x[i],)*))
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                let n = self.len - self.idx;
+                (n, Some(n))
+            }
+        }
+
+        impl<'a, $($T),*> ExactSizeIterator for Rows<($(&'a [Cell<$T>],)*)> {}
+    }
+}
+
+rows_impl!(A, a);
+rows_impl!(A, a; B, b);
+rows_impl!(A, a; B, b; C, c);
+rows_impl!(A, a; B, b; C, c; D, d);
+rows_impl!(A, a; B, b; C, c; D, d; E, e);
+rows_impl!(A, a; B, b; C, c; D, d; E, e; F, f);
+rows_impl!(A, a; B, b; C, c; D, d; E, e; F, f; G, g);
+rows_impl!(A, a; B, b; C, c; D, d; E, e; F, f; G, g; H, h);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_columns() {
+        let mut a = [1, 2, 3];
+        let mut b = [10u8, 20, 30];
+
+        {
+            let rows = columns((&mut a[..], &mut b[..]));
+            assert_eq!(rows.len(), 3);
+            for (x, y) in rows {
+                y.set(y.get() + x.get() as u8);
+                x.set(0);
+            }
+        }
+        assert_eq!(a, [0; 3]);
+        assert_eq!(b, [11, 22, 33]);
+
+        // not `Copy`, and a single column
+        let mut names = [String::from("a"), String::from("b")];
+        for (n,) in columns((&mut names[..],)) {
+            n.set(n.take() + "!");
+        }
+        assert_eq!(names, ["a!", "b!"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "mismatched column lengths")]
+    fn columns_mismatch() {
+        let mut a = [1, 2, 3];
+        let mut b = [1, 2];
+        let mut c = [1, 2, 3];
+        columns((&mut a[..], &mut b[..], &mut c[..]));
+    }
+}
@@ -0,0 +1,225 @@
+use std::cell::Cell;
+use std::mem;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use slice;
+
+/// A set of counters stored in caller-owned memory, updatable through
+/// shared references.
+///
+/// Each counter is identified by its index in the backing slice;
+/// giving those indices names (e.g. as constants or a C-like enum) is
+/// left to the caller.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::Counters;
+///
+/// const HITS: usize = 0;
+/// const MISSES: usize = 1;
+///
+/// let mut slots = [0; 2];
+/// let c = Counters::new(&mut slots);
+/// let (cache, report) = (c, c);
+///
+/// cache.incr(HITS);
+/// cache.incr(HITS);
+/// cache.add(MISSES, 5);
+///
+/// assert_eq!(report.snapshot(), [2, 5]);
+/// report.reset();
+/// assert_eq!(cache.get(HITS), 0);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Counters<'a> {
+    slots: &'a [Cell<u64>],
+}
+
+impl<'a> Counters<'a> {
+    /// Use `slots` as the backing storage for the counters.
+    pub fn new(slots: &'a mut [u64]) -> Counters<'a> {
+        Counters { slots: slice(slots) }
+    }
+
+    /// The number of counters.
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Whether there are no counters.
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Add one to counter `id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is out of bounds.
+    pub fn incr(&self, id: usize) {
+        self.add(id, 1)
+    }
+
+    /// Add `n` to counter `id`, wrapping on overflow.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is out of bounds.
+    pub fn add(&self, id: usize, n: u64) {
+        let c = &self.slots[id];
+        c.set(c.get().wrapping_add(n))
+    }
+
+    /// The current value of counter `id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is out of bounds.
+    pub fn get(&self, id: usize) -> u64 {
+        self.slots[id].get()
+    }
+
+    /// The current values of every counter.
+    pub fn snapshot(&self) -> Vec<u64> {
+        self.slots.iter().map(Cell::get).collect()
+    }
+
+    /// Set every counter to zero.
+    pub fn reset(&self) {
+        for c in self.slots {
+            c.set(0)
+        }
+    }
+}
+
+/// A set of counters stored in caller-owned memory, updatable through
+/// shared references from any number of threads.
+///
+/// This is the thread-safe equivalent of `Counters`. All operations
+/// are relaxed: each counter is individually consistent, but a
+/// `snapshot` taken while other threads are counting isn't an atomic
+/// snapshot of all counters together.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::SyncCounters;
+///
+/// let mut slots = [0; 4];
+/// let c = SyncCounters::new(&mut slots);
+///
+/// std::thread::scope(|s| {
+///     for t in 0..4 {
+///         s.spawn(move || for _ in 0..100 { c.incr(t) });
+///     }
+/// });
+///
+/// assert_eq!(c.snapshot(), [100; 4]);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct SyncCounters<'a> {
+    slots: &'a [AtomicU64],
+}
+
+impl<'a> SyncCounters<'a> {
+    /// Use `slots` as the backing storage for the counters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `slots` isn't sufficiently aligned to be accessed
+    /// atomically: `AtomicU64` requires 8-byte alignment, which `u64`
+    /// doesn't have on some 32-bit platforms.
+    pub fn new(slots: &'a mut [u64]) -> SyncCounters<'a> {
+        assert_eq!(slots.as_ptr() as usize % mem::align_of::<AtomicU64>(), 0,
+                   "SyncCounters::new: insufficiently aligned slots");
+        // same size, and alignment checked above.
+        SyncCounters { slots: unsafe { &*(slots as *mut [u64] as *const [AtomicU64]) } }
+    }
+
+    /// The number of counters.
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Whether there are no counters.
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Add one to counter `id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is out of bounds.
+    pub fn incr(&self, id: usize) {
+        self.add(id, 1)
+    }
+
+    /// Add `n` to counter `id`, wrapping on overflow.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is out of bounds.
+    pub fn add(&self, id: usize, n: u64) {
+        self.slots[id].fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// The current value of counter `id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is out of bounds.
+    pub fn get(&self, id: usize) -> u64 {
+        self.slots[id].load(Ordering::Relaxed)
+    }
+
+    /// The current values of every counter.
+    pub fn snapshot(&self) -> Vec<u64> {
+        self.slots.iter().map(|c| c.load(Ordering::Relaxed)).collect()
+    }
+
+    /// Set every counter to zero.
+    pub fn reset(&self) {
+        for c in self.slots {
+            c.store(0, Ordering::Relaxed)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn smoke_counters() {
+        let mut x = [0; 3];
+        {
+            let c = Counters::new(&mut x);
+            c.incr(0);
+            c.add(2, u64::MAX);
+            c.incr(2);
+            assert_eq!(c.len(), 3);
+            assert_eq!(c.snapshot(), [1, 0, 0]);
+            c.add(1, 7);
+        }
+        assert_eq!(x, [1, 7, 0]);
+    }
+
+    #[test]
+    fn smoke_sync_counters() {
+        let mut x = [0; 2];
+        {
+            let c = SyncCounters::new(&mut x);
+            thread::scope(|s| {
+                for _ in 0..4 {
+                    s.spawn(|| for i in 0..1000 { c.incr(i % 2) });
+                }
+            });
+            assert_eq!(c.get(0), 2000);
+            c.add(1, 1);
+        }
+        assert_eq!(x, [2000, 2001]);
+    }
+}
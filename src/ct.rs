@@ -0,0 +1,80 @@
+//! Constant-time comparisons of aliased byte buffers.
+//!
+//! Comparing MACs, tags or other secrets with `==` leaks, through
+//! timing, how long the matching prefix is. The functions here always
+//! look at every byte.
+//!
+//! # Examples
+//!
+//! ```rust
+//! let mut tag = [0xAB; 16];
+//! let tag = alias::slice(&mut tag);
+//!
+//! assert!(bool::from(alias::ct::ct_eq(tag, &[0xAB; 16])));
+//!
+//! tag[15].set(0);
+//! assert!(!bool::from(alias::ct::ct_eq(tag, &[0xAB; 16])));
+//! ```
+
+use std::cell::Cell;
+use std::hint;
+
+/// The result of a constant-time comparison.
+///
+/// This deliberately doesn't implement `PartialEq` or provide
+/// short-circuiting operations, to keep branching on the result as
+/// late and as explicit as possible.
+#[derive(Copy, Clone, Debug)]
+pub struct Choice(u8);
+
+impl Choice {
+    /// Returns `1` for true and `0` for false.
+    pub fn unwrap_u8(self) -> u8 {
+        self.0
+    }
+}
+
+impl From<Choice> for bool {
+    fn from(c: Choice) -> bool {
+        c.0 != 0
+    }
+}
+
+/// Compare the contents of `a` with `b` without exiting early.
+///
+/// The running time depends only on the lengths of the two slices,
+/// which are not considered secret: slices of different lengths
+/// compare unequal immediately.
+pub fn ct_eq(a: &[Cell<u8>], b: &[u8]) -> Choice {
+    if a.len() != b.len() {
+        return Choice(0)
+    }
+    let mut diff = 0u8;
+    for (x, &y) in a.iter().zip(b) {
+        diff |= x.get() ^ y;
+    }
+    let diff = hint::black_box(diff);
+    // 1 if diff == 0, 0 otherwise, without a branch.
+    Choice((((diff as u16).wrapping_sub(1) >> 8) & 1) as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slice;
+
+    #[test]
+    fn smoke_ct_eq() {
+        let mut x = [1u8, 2, 3, 0, 0xFF];
+        let x = slice(&mut x);
+
+        assert_eq!(ct_eq(x, &[1, 2, 3, 0, 0xFF]).unwrap_u8(), 1);
+        assert_eq!(ct_eq(x, &[1, 2, 3, 0, 0xFE]).unwrap_u8(), 0);
+        assert_eq!(ct_eq(x, &[0, 2, 3, 0, 0xFF]).unwrap_u8(), 0);
+        assert_eq!(ct_eq(x, &[1, 2, 3, 0]).unwrap_u8(), 0);
+        assert_eq!(ct_eq(&[], &[]).unwrap_u8(), 1);
+
+        x[0].set(0);
+        assert!(bool::from(ct_eq(x, &[0, 2, 3, 0, 0xFF])));
+    }
+}
@@ -0,0 +1,272 @@
+use std::cell::Cell;
+use std::fmt;
+use std::mem;
+
+/// A sequential cursor over a slice of cells.
+///
+/// The position is itself stored in a cell, so several parties can
+/// share one cursor through `&` and each pick up where the last left
+/// off. Byte cursors can also read and write primitive numbers in
+/// little-endian order.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::CellCursor;
+///
+/// let mut buf = [0u8; 8];
+/// {
+///     let cursor = CellCursor::new(alias::slice(&mut buf));
+///     let (header, body) = (&cursor, &cursor);
+///
+///     header.write(0xABCDu16).unwrap();
+///     body.write(7u32).unwrap();
+///     assert_eq!(cursor.position(), 6);
+///
+///     cursor.set_position(0);
+///     assert_eq!(cursor.read::<u16>(), Some(0xABCD));
+///     assert!(cursor.skip(4));
+///     assert_eq!(cursor.read::<u32>(), None); // only 2 bytes left
+/// }
+/// assert_eq!(buf, [0xCD, 0xAB, 7, 0, 0, 0, 0, 0]);
+/// ```
+///
+/// Two cursors over one slice make in-place compaction
+/// straightforward:
+///
+/// ```rust
+/// use alias::CellCursor;
+///
+/// let mut v = [3, 0, 1, 0, 0, 4];
+/// let kept = {
+///     let cells = alias::slice(&mut v);
+///     let (read, write) = (CellCursor::new(cells), CellCursor::new(cells));
+///     while let Some(x) = read.advance() {
+///         if x != 0 {
+///             write.write_and_advance(x).unwrap();
+///         }
+///     }
+///     for c in write.split_rest().1 {
+///         c.set(0);
+///     }
+///     write.position()
+/// };
+/// assert_eq!((kept, v), (3, [3, 1, 4, 0, 0, 0]));
+/// ```
+pub struct CellCursor<'a, T: 'a> {
+    data: &'a [Cell<T>],
+    pos: Cell<usize>,
+}
+
+impl<'a, T: Copy> CellCursor<'a, T> {
+    /// Create a cursor at the start of `data`.
+    pub fn new(data: &'a [Cell<T>]) -> CellCursor<'a, T> {
+        CellCursor { data, pos: Cell::new(0) }
+    }
+
+    /// The whole underlying slice.
+    pub fn get_ref(&self) -> &'a [Cell<T>] {
+        self.data
+    }
+
+    /// The current position.
+    pub fn position(&self) -> usize {
+        self.pos.get()
+    }
+
+    /// Move to `pos`, which may be past the end (in which case reads
+    /// and writes fail).
+    pub fn set_position(&self, pos: usize) {
+        self.pos.set(pos)
+    }
+
+    /// The number of elements after the current position.
+    pub fn remaining(&self) -> usize {
+        self.data.len().saturating_sub(self.pos.get())
+    }
+
+    /// Move forward `n` elements, returning `false` (and not moving)
+    /// if there aren't that many left.
+    pub fn skip(&self, n: usize) -> bool {
+        if n <= self.remaining() {
+            self.pos.set(self.pos.get() + n);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Move by `offset` elements, forwards or backwards, returning
+    /// `false` (and not moving) if that would leave the slice.
+    pub fn seek(&self, offset: isize) -> bool {
+        match self.pos.get().checked_add_signed(offset) {
+            Some(pos) if pos <= self.data.len() => {
+                self.pos.set(pos);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The element at the current position, if any, without moving.
+    pub fn peek(&self) -> Option<T> {
+        self.data.get(self.pos.get()).map(Cell::get)
+    }
+
+    /// The element at the current position, if any, moving past it.
+    pub fn advance(&self) -> Option<T> {
+        self.take(1).map(|c| c[0].get())
+    }
+
+    /// Overwrite the element at the current position with `value` and
+    /// move past it, or return `value` (and not move) if at the end.
+    pub fn write_and_advance(&self, value: T) -> Result<(), T> {
+        match self.take(1) {
+            Some(c) => {
+                c[0].set(value);
+                Ok(())
+            }
+            None => Err(value),
+        }
+    }
+
+    /// The cells before and from the current position.
+    ///
+    /// These still alias the cursor's slice, so, for example, an
+    /// in-place algorithm can hand the unprocessed part to other code
+    /// while continuing to write through the cursor.
+    pub fn split_rest(&self) -> (&'a [Cell<T>], &'a [Cell<T>]) {
+        self.data.split_at(self.pos.get().min(self.data.len()))
+    }
+
+    /// The next `n` cells, advancing past them, or `None` if there
+    /// aren't that many left.
+    fn take(&self, n: usize) -> Option<&'a [Cell<T>]> {
+        let start = self.pos.get();
+        if n <= self.remaining() {
+            self.pos.set(start + n);
+            Some(&self.data[start..start + n])
+        } else {
+            None
+        }
+    }
+}
+
+/// Primitive numbers that `CellCursor` can read and write as
+/// little-endian bytes.
+///
+/// This is implemented for all the primitive integer and floating
+/// point types, and cannot be implemented outside this crate.
+pub trait LeBytes: Copy + private::Sealed {
+    #[doc(hidden)]
+    fn read_le(bytes: &[Cell<u8>]) -> Self;
+    #[doc(hidden)]
+    fn write_le(self, bytes: &[Cell<u8>]);
+}
+
+macro_rules! le_bytes {
+    ($($t: ty),*) => {
+        $(
+            impl LeBytes for $t {
+                fn read_le(bytes: &[Cell<u8>]) -> $t {
+                    let mut b = [0; mem::size_of::<$t>()];
+                    for (x, c) in b.iter_mut().zip(bytes) {
+                        *x = c.get();
+                    }
+                    <$t>::from_le_bytes(b)
+                }
+                fn write_le(self, bytes: &[Cell<u8>]) {
+                    for (x, c) in self.to_le_bytes().iter().zip(bytes) {
+                        c.set(*x);
+                    }
+                }
+            }
+            impl private::Sealed for $t {}
+        )*
+    }
+}
+le_bytes!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+mod private {
+    pub trait Sealed {}
+}
+
+impl<'a> CellCursor<'a, u8> {
+    /// Read a little-endian `N` and advance past it, or return `None`
+    /// (and not move) if there aren't enough bytes left.
+    pub fn read<N: LeBytes>(&self) -> Option<N> {
+        self.take(mem::size_of::<N>()).map(N::read_le)
+    }
+
+    /// Write `value` as little-endian bytes and advance past it, or
+    /// return it (and not move) if there isn't enough space left.
+    pub fn write<N: LeBytes>(&self, value: N) -> Result<(), N> {
+        match self.take(mem::size_of::<N>()) {
+            Some(bytes) => {
+                value.write_le(bytes);
+                Ok(())
+            }
+            None => Err(value),
+        }
+    }
+}
+
+impl<'a, T> fmt::Debug for CellCursor<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CellCursor")
+            .field("len", &self.data.len())
+            .field("position", &self.pos.get())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slice;
+
+    #[test]
+    fn smoke_cursor() {
+        let mut x = [0u8; 16];
+        {
+            let c = CellCursor::new(slice(&mut x));
+            assert_eq!(c.write(-2i16), Ok(()));
+            assert_eq!(c.write(1.5f64), Ok(()));
+            assert_eq!(c.write(0x0102_0304u32), Ok(()));
+            assert_eq!(c.remaining(), 2);
+            assert_eq!(c.write(0u32), Err(0));
+            assert_eq!(c.position(), 14);
+
+            c.set_position(0);
+            assert_eq!(c.read::<i16>(), Some(-2));
+            assert_eq!(c.read::<f64>(), Some(1.5));
+            assert!(!c.skip(7));
+            assert!(c.skip(1));
+            assert_eq!(c.read::<u8>(), Some(3));
+
+            c.set_position(100);
+            assert_eq!(c.remaining(), 0);
+            assert_eq!(c.read::<u8>(), None);
+            assert!(c.skip(0));
+        }
+        assert_eq!(x[10..14], [4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn cursor_moves() {
+        let mut x = ['a', 'b', 'c'];
+        {
+            let c = CellCursor::new(slice(&mut x));
+            assert_eq!((c.peek(), c.advance(), c.peek()), (Some('a'), Some('a'), Some('b')));
+            assert!(!c.seek(-2) && !c.seek(3));
+            assert!(c.seek(2) && c.seek(-1));
+            assert_eq!(c.write_and_advance('z'), Ok(()));
+            let (done, rest) = c.split_rest();
+            assert_eq!((done.len(), rest.len()), (3, 0));
+            assert_eq!((c.peek(), c.advance(), c.write_and_advance('y')), (None, None, Err('y')));
+            c.set_position(10);
+            assert_eq!(c.split_rest().0.len(), 3);
+        }
+        assert_eq!(x, ['a', 'b', 'z']);
+    }
+}
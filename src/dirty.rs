@@ -0,0 +1,204 @@
+use std::cell::Cell;
+use std::fmt;
+
+use grid::CellGrid;
+use paint::clip;
+
+/// A rectangle of grid elements.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct Rect {
+    /// The column of the left edge.
+    pub x: usize,
+    /// The row of the top edge.
+    pub y: usize,
+    /// The number of columns.
+    pub width: usize,
+    /// The number of rows.
+    pub height: usize,
+}
+
+impl Rect {
+    fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    fn touches(&self, other: &Rect) -> bool {
+        self.x <= other.x + other.width && other.x <= self.x + self.width
+            && self.y <= other.y + other.height && other.y <= self.y + self.height
+    }
+
+    fn union(&self, other: &Rect) -> Rect {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Rect {
+            x,
+            y,
+            width: (self.x + self.width).max(other.x + other.width) - x,
+            height: (self.y + self.height).max(other.y + other.height) - y,
+        }
+    }
+}
+
+/// A `CellGrid` that records which regions have been written.
+///
+/// Every write through this wrapper adds its bounding rectangle to a
+/// list of dirty rectangles, merging any that touch, so that a
+/// redraw or upload can be limited to the parts that changed.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::{DirtyRectGrid, Rect};
+///
+/// let mut px = [0u32; 64 * 64];
+/// let g = DirtyRectGrid::new(&mut px, 64, 64);
+/// let (ui, cursor) = (&g, &g);
+///
+/// ui.fill_rect(0, 0, 10, 4, 0xFFFFFF);
+/// cursor.set(30, 30, 0xFF0000);
+/// ui.set(10, 2, 0x00FF00); // touches the first rectangle
+///
+/// assert_eq!(g.take_dirty_rects(), [
+///     Rect { x: 0, y: 0, width: 11, height: 4 },
+///     Rect { x: 30, y: 30, width: 1, height: 1 },
+/// ]);
+/// assert!(g.take_dirty_rects().is_empty());
+/// ```
+pub struct DirtyRectGrid<'a, T: 'a> {
+    grid: CellGrid<'a, T>,
+    dirty: Cell<Vec<Rect>>,
+}
+
+impl<'a, T: Copy> DirtyRectGrid<'a, T> {
+    /// Track writes to the row-major `width` × `height` grid stored in
+    /// `data`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data.len() != width * height`.
+    pub fn new(data: &'a mut [T], width: usize, height: usize) -> DirtyRectGrid<'a, T> {
+        DirtyRectGrid { grid: CellGrid::new(data, width, height), dirty: Cell::new(Vec::new()) }
+    }
+
+    /// The number of columns.
+    pub fn width(&self) -> usize {
+        self.grid.width()
+    }
+
+    /// The number of rows.
+    pub fn height(&self) -> usize {
+        self.grid.height()
+    }
+
+    /// The element at column `x` and row `y`, or `None` if that is out
+    /// of bounds.
+    pub fn get(&self, x: usize, y: usize) -> Option<T> {
+        self.grid.cell(x, y).map(Cell::get)
+    }
+
+    /// Set the element at column `x` and row `y`, if it is in bounds.
+    pub fn set(&self, x: usize, y: usize, value: T) {
+        if let Some(c) = self.grid.cell(x, y) {
+            c.set(value);
+            self.mark_dirty(Rect { x, y, width: 1, height: 1 });
+        }
+    }
+
+    /// Like `CellGrid::fill_rect`.
+    pub fn fill_rect(&self, x: isize, y: isize, w: usize, h: usize, value: T) {
+        self.grid.fill_rect(x, y, w, h, value);
+        self.mark_clipped(x, y, w, h);
+    }
+
+    /// Like `CellGrid::blit`.
+    pub fn blit(&self, src: &CellGrid<T>, (x, y): (isize, isize)) {
+        self.grid.blit(src, (x, y));
+        self.mark_clipped(x, y, src.width(), src.height());
+    }
+
+    fn mark_clipped(&self, x: isize, y: isize, w: usize, h: usize) {
+        let xs = clip(x, w, self.width());
+        let ys = clip(y, h, self.height());
+        self.mark_dirty(Rect { x: xs.start, y: ys.start, width: xs.len(), height: ys.len() });
+    }
+
+    /// Record `rect` as dirty, such as after writing through some
+    /// other view of the same memory.
+    pub fn mark_dirty(&self, rect: Rect) {
+        if rect.is_empty() {
+            return
+        }
+        let mut dirty = self.dirty.take();
+        let mut merged = rect;
+        // merging can make the rectangle touch others it didn't
+        // before, so keep going until nothing changes.
+        loop {
+            let before = dirty.len();
+            dirty.retain(|r| {
+                if r.touches(&merged) {
+                    merged = merged.union(r);
+                    false
+                } else {
+                    true
+                }
+            });
+            if dirty.len() == before {
+                break
+            }
+        }
+        dirty.push(merged);
+        dirty.sort_by_key(|r| (r.y, r.x));
+        self.dirty.set(dirty);
+    }
+
+    /// Return the dirty rectangles, sorted top to bottom then left to
+    /// right, and reset the grid to clean.
+    pub fn take_dirty_rects(&self) -> Vec<Rect> {
+        self.dirty.take()
+    }
+}
+
+impl<'a, T: Copy + fmt::Debug> fmt::Debug for DirtyRectGrid<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let dirty = self.dirty.take();
+        let ret = f.debug_struct("DirtyRectGrid")
+            .field("grid", &self.grid)
+            .field("dirty", &dirty)
+            .finish();
+        self.dirty.set(dirty);
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_dirty() {
+        let mut x = [0u8; 100];
+        let mut s = [1u8; 4];
+        let sprite = CellGrid::new(&mut s, 2, 2);
+        let g = DirtyRectGrid::new(&mut x, 10, 10);
+
+        g.set(10, 0, 1);
+        g.fill_rect(-5, -5, 3, 3, 1);
+        assert!(g.take_dirty_rects().is_empty());
+
+        g.blit(&sprite, (9, 9));
+        g.set(0, 0, 2);
+        g.fill_rect(3, 0, 2, 2, 3);
+        assert_eq!(g.get(9, 9), Some(1));
+        assert_eq!(g.take_dirty_rects(), [
+            Rect { x: 0, y: 0, width: 1, height: 1 },
+            Rect { x: 3, y: 0, width: 2, height: 2 },
+            Rect { x: 9, y: 9, width: 1, height: 1 },
+        ]);
+
+        // bridging two rectangles merges all three
+        g.set(0, 0, 0);
+        g.set(2, 2, 0);
+        g.mark_dirty(Rect { x: 1, y: 1, width: 1, height: 1 });
+        assert_eq!(g.take_dirty_rects(), [Rect { x: 0, y: 0, width: 3, height: 3 }]);
+    }
+}
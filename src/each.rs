@@ -0,0 +1,92 @@
+use std::cell::Cell;
+use std::fmt;
+use std::iter::FusedIterator;
+
+use one;
+
+/// Allow each element yielded by `iter` to be mutated while aliased.
+///
+/// This converts any iterator of unique references into one of cells,
+/// so containers that aren't contiguous, like the values of a map or
+/// the nodes of a linked list, can be aliased too.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::collections::LinkedList;
+///
+/// let mut list: LinkedList<i32> = (1..=4).collect();
+/// {
+///     let cells: Vec<_> = alias::each(list.iter_mut()).collect();
+///     let sum = || cells.iter().map(|c| c.get()).sum::<i32>();
+///     cells[0].set(sum());
+///     cells[3].swap(cells[1]);
+/// }
+/// assert_eq!(list.into_iter().collect::<Vec<_>>(), [10, 4, 3, 2]);
+/// ```
+pub fn each<'a, T: 'a, I>(iter: I) -> Each<I::IntoIter>
+    where I: IntoIterator<Item = &'a mut T>
+{
+    Each { inner: iter.into_iter() }
+}
+
+/// The iterator returned by `each`.
+#[derive(Clone)]
+pub struct Each<I> {
+    inner: I,
+}
+
+impl<'a, T: 'a, I> Iterator for Each<I>
+    where I: Iterator<Item = &'a mut T>
+{
+    type Item = &'a Cell<T>;
+
+    fn next(&mut self) -> Option<&'a Cell<T>> {
+        self.inner.next().map(one)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, T: 'a, I> DoubleEndedIterator for Each<I>
+    where I: DoubleEndedIterator<Item = &'a mut T>
+{
+    fn next_back(&mut self) -> Option<&'a Cell<T>> {
+        self.inner.next_back().map(one)
+    }
+}
+
+impl<'a, T: 'a, I> ExactSizeIterator for Each<I>
+    where I: ExactSizeIterator<Item = &'a mut T> {}
+
+impl<'a, T: 'a, I> FusedIterator for Each<I>
+    where I: FusedIterator<Item = &'a mut T> {}
+
+impl<I: fmt::Debug> fmt::Debug for Each<I> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Each").field(&self.inner).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn smoke_each() {
+        let mut m = BTreeMap::from([(1, 'a'), (2, 'b'), (3, 'c')]);
+        {
+            let mut it = each(m.values_mut());
+            assert_eq!(it.len(), 3);
+            let last = it.next_back().unwrap();
+            let first = it.next().unwrap();
+            first.swap(last);
+            it.next().unwrap().set('z');
+            assert!(it.next().is_none());
+        }
+        assert_eq!(m.into_values().collect::<String>(), "cza");
+    }
+}
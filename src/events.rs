@@ -0,0 +1,159 @@
+use std::cell::Cell;
+use std::fmt;
+
+use slice;
+
+/// A fixed-capacity FIFO of events over borrowed storage, which
+/// anything holding a shared reference can push to.
+///
+/// Events are consumed with `drain`, typically once per frame. Events
+/// pushed while a drain is in progress (for example, by the handlers
+/// of earlier events) are yielded by that same drain.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::EventQueue;
+///
+/// #[derive(Copy, Clone, PartialEq, Debug)]
+/// enum Event { Hit(u32), Died(u32) }
+///
+/// let mut storage = [Event::Hit(0); 8];
+/// let queue = EventQueue::new(&mut storage);
+///
+/// queue.push(Event::Hit(1)).unwrap();
+/// queue.push(Event::Hit(2)).unwrap();
+///
+/// let mut seen = vec![];
+/// for e in queue.drain() {
+///     seen.push(e);
+///     if let Event::Hit(id) = e {
+///         if id == 2 {
+///             queue.push(Event::Died(id)).unwrap();
+///         }
+///     }
+/// }
+///
+/// assert_eq!(seen, [Event::Hit(1), Event::Hit(2), Event::Died(2)]);
+/// assert!(queue.is_empty());
+/// ```
+pub struct EventQueue<'a, E: 'a> {
+    slots: &'a [Cell<E>],
+    head: Cell<usize>,
+    tail: Cell<usize>,
+}
+
+impl<'a, E: Copy> EventQueue<'a, E> {
+    /// Create an empty queue using `storage` to hold events; its
+    /// length is the capacity of the queue.
+    pub fn new(storage: &'a mut [E]) -> EventQueue<'a, E> {
+        EventQueue { slots: slice(storage), head: Cell::new(0), tail: Cell::new(0) }
+    }
+
+    /// The maximum number of events that can be pushed between drains.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// The number of events waiting to be drained.
+    pub fn len(&self) -> usize {
+        self.tail.get() - self.head.get()
+    }
+
+    /// Whether there are no events waiting.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Add `event` to the back of the queue, or return it if the queue
+    /// is full.
+    ///
+    /// Space is only reclaimed once every pending event has been
+    /// drained.
+    pub fn push(&self, event: E) -> Result<(), E> {
+        let tail = self.tail.get();
+        match self.slots.get(tail) {
+            Some(c) => {
+                c.set(event);
+                self.tail.set(tail + 1);
+                Ok(())
+            }
+            None => Err(event),
+        }
+    }
+
+    /// Remove and iterate over events in the order they were pushed,
+    /// including any pushed during iteration.
+    ///
+    /// Events not reached (if the iterator is dropped early) stay in
+    /// the queue.
+    pub fn drain(&self) -> EventDrain<'_, 'a, E> {
+        EventDrain { queue: self }
+    }
+}
+
+impl<'a, E: Copy + fmt::Debug> fmt::Debug for EventQueue<'a, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list()
+            .entries(self.slots[self.head.get()..self.tail.get()].iter().map(Cell::get))
+            .finish()
+    }
+}
+
+/// An iterator removing events from an `EventQueue`.
+///
+/// Created by `EventQueue::drain`.
+pub struct EventDrain<'q, 'a: 'q, E: 'a> {
+    queue: &'q EventQueue<'a, E>,
+}
+
+impl<'q, 'a, E: Copy> Iterator for EventDrain<'q, 'a, E> {
+    type Item = E;
+
+    fn next(&mut self) -> Option<E> {
+        let q = self.queue;
+        let head = q.head.get();
+        if head < q.tail.get() {
+            q.head.set(head + 1);
+            Some(q.slots[head].get())
+        } else {
+            // everything has been consumed, so reclaim the space.
+            q.head.set(0);
+            q.tail.set(0);
+            None
+        }
+    }
+}
+
+impl<'q, 'a, E: Copy + fmt::Debug> fmt::Debug for EventDrain<'q, 'a, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("EventDrain").field(self.queue).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_events() {
+        let mut x = [0u8; 3];
+        let q = EventQueue::new(&mut x);
+        assert_eq!(q.capacity(), 3);
+        assert_eq!(q.push(1), Ok(()));
+        assert_eq!(q.push(2), Ok(()));
+        assert_eq!(q.push(3), Ok(()));
+        assert_eq!(q.push(4), Err(4));
+        assert_eq!(format!("{:?}", q), "[1, 2, 3]");
+
+        assert_eq!(q.drain().next(), Some(1));
+        assert_eq!(q.len(), 2);
+        // still full until fully drained
+        assert_eq!(q.push(4), Err(4));
+
+        assert_eq!(q.drain().collect::<Vec<_>>(), [2, 3]);
+        assert!(q.is_empty());
+        assert_eq!(q.push(5), Ok(()));
+        assert_eq!(q.drain().collect::<Vec<_>>(), [5]);
+    }
+}
@@ -0,0 +1,583 @@
+use std::cell::Cell;
+
+/// Read-modify-write for any cell.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::CellExt;
+///
+/// let mut log = vec!["start"];
+/// let mut n = 1;
+/// {
+///     let (log, n) = (alias::one(&mut log), alias::one(&mut n));
+///     let old = n.update_with(|&x| x * 10);
+///     log.update_with(|l| {
+///         let mut l = l.clone();
+///         l.push(if old == 1 { "one" } else { "other" });
+///         l
+///     });
+/// }
+/// assert_eq!((log, n), (vec!["start", "one"], 10));
+/// ```
+pub trait CellExt<T> {
+    /// Replace the value in the cell with `f` applied to it, returning
+    /// the old value.
+    ///
+    /// While `f` runs, the cell holds `T::default()`, so reads of it
+    /// through other references (such as by `f` itself) see that
+    /// instead of the old value, and any writes to it are lost when
+    /// the new value is stored.
+    fn update_with<F>(&self, f: F) -> T
+        where T: Default, F: FnOnce(&T) -> T;
+
+    /// Replace the value in the cell with `f` applied to it, moving
+    /// the old value into `f`.
+    ///
+    /// This updates non-`Copy` values, such as a `String` or a `Vec`,
+    /// without cloning them. While `f` runs, the cell holds
+    /// `T::default()`, with the same consequences as for
+    /// `update_with`. If `f` panics, the old value is dropped and the
+    /// cell is left holding `T::default()`.
+    ///
+    /// (Leaving the cell's old bytes in place while `f` runs, and
+    /// aborting on panic, would not need `Default`, but isn't sound
+    /// here: `f` could reach the cell through another reference and
+    /// drop the value it has been given.)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use alias::CellExt;
+    ///
+    /// let mut path = String::from("/usr");
+    /// {
+    ///     let p = alias::one(&mut path);
+    ///     let push = |part: &str| p.replace_with(|mut s| {
+    ///         s.push('/');
+    ///         s.push_str(part);
+    ///         s
+    ///     });
+    ///     push("local");
+    ///     push("bin");
+    /// }
+    /// assert_eq!(path, "/usr/local/bin");
+    /// ```
+    fn replace_with<F>(&self, f: F)
+        where T: Default, F: FnOnce(T) -> T;
+
+    /// Set the value to `new` if `pred` returns true for the current
+    /// value, returning whether it did.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use alias::CellExt;
+    ///
+    /// let mut level = 3;
+    /// let l = alias::one(&mut level);
+    /// let lower = |to| l.set_if(|old| to < old, to);
+    ///
+    /// assert!(!lower(5));
+    /// assert!(lower(1));
+    /// assert_eq!(l.get(), 1);
+    /// ```
+    fn set_if<F>(&self, pred: F, new: T) -> bool
+        where T: Copy, F: FnOnce(T) -> bool;
+
+    /// Set the value to `new` if it is currently `expected`.
+    ///
+    /// This returns `Ok` with the old value (equal to `expected`) if
+    /// the value was replaced, or `Err` with the current value if not,
+    /// like `compare_exchange` on the standard atomics (which are the
+    /// equivalent for `alias::atomic`).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use alias::CellExt;
+    ///
+    /// #[derive(Clone, Copy, Debug, PartialEq)]
+    /// enum State { Idle, Running, Done }
+    ///
+    /// let mut state = State::Idle;
+    /// {
+    ///     let s = alias::one(&mut state);
+    ///     let start = || s.compare_and_set(State::Idle, State::Running);
+    ///     let finish = || s.compare_and_set(State::Running, State::Done);
+    ///
+    ///     assert_eq!(finish(), Err(State::Idle));
+    ///     assert_eq!(start(), Ok(State::Idle));
+    ///     assert_eq!(start(), Err(State::Running));
+    ///     assert!(finish().is_ok());
+    /// }
+    /// assert_eq!(state, State::Done);
+    /// ```
+    fn compare_and_set(&self, expected: T, new: T) -> Result<T, T>
+        where T: Copy + PartialEq;
+}
+
+impl<T> CellExt<T> for Cell<T> {
+    fn update_with<F>(&self, f: F) -> T
+        where T: Default, F: FnOnce(&T) -> T
+    {
+        let old = self.take();
+        self.set(f(&old));
+        old
+    }
+
+    fn replace_with<F>(&self, f: F)
+        where T: Default, F: FnOnce(T) -> T
+    {
+        self.set(f(self.take()))
+    }
+
+    fn set_if<F>(&self, pred: F, new: T) -> bool
+        where T: Copy, F: FnOnce(T) -> bool
+    {
+        let ok = pred(self.get());
+        if ok {
+            self.set(new)
+        }
+        ok
+    }
+
+    fn compare_and_set(&self, expected: T, new: T) -> Result<T, T>
+        where T: Copy + PartialEq
+    {
+        let old = self.get();
+        if old == expected {
+            self.set(new);
+            Ok(old)
+        } else {
+            Err(old)
+        }
+    }
+}
+
+/// Arithmetic on integer cells, to avoid writing out
+/// `c.set(c.get() + n)`.
+///
+/// This is implemented for `Cell`s of each primitive integer type,
+/// and cannot be implemented outside this crate.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::CellNumExt;
+///
+/// let mut hits = 0u32;
+/// let mut peak = 0u8;
+/// {
+///     let (hits, peak) = (alias::one(&mut hits), alias::one(&mut peak));
+///     let record = |level: u8| {
+///         hits.add(1);
+///         peak.max_assign(level);
+///     };
+///     record(3);
+///     record(7);
+///     record(5);
+///     assert_eq!(hits.fetch_add(10), 3);
+///     peak.saturating_add(250);
+/// }
+/// assert_eq!((hits, peak), (13, 255));
+/// ```
+pub trait CellNumExt: private::Sealed {
+    /// The integer type in the cell.
+    type Num;
+
+    /// Add `n` to the contents, as with `+`.
+    fn add(&self, n: Self::Num);
+
+    /// Subtract `n` from the contents, as with `-`.
+    fn sub(&self, n: Self::Num);
+
+    /// Add `n` to the contents, as with `+`, returning the previous
+    /// value.
+    fn fetch_add(&self, n: Self::Num) -> Self::Num;
+
+    /// Add `n` to the contents, wrapping on overflow.
+    fn wrapping_add(&self, n: Self::Num);
+
+    /// Add `n` to the contents, saturating at the bounds of the type.
+    fn saturating_add(&self, n: Self::Num);
+
+    /// Replace the contents with `n`, if `n` is smaller.
+    fn min_assign(&self, n: Self::Num);
+
+    /// Replace the contents with `n`, if `n` is larger.
+    fn max_assign(&self, n: Self::Num);
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+macro_rules! num_ext {
+    ($($t: ty),*) => {
+        $(
+            impl private::Sealed for Cell<$t> {}
+
+            impl CellNumExt for Cell<$t> {
+                type Num = $t;
+
+                fn add(&self, n: $t) {
+                    self.set(self.get() + n)
+                }
+                fn sub(&self, n: $t) {
+                    self.set(self.get() - n)
+                }
+                fn fetch_add(&self, n: $t) -> $t {
+                    self.replace(self.get() + n)
+                }
+                fn wrapping_add(&self, n: $t) {
+                    self.set(self.get().wrapping_add(n))
+                }
+                fn saturating_add(&self, n: $t) {
+                    self.set(self.get().saturating_add(n))
+                }
+                fn min_assign(&self, n: $t) {
+                    self.set(self.get().min(n))
+                }
+                fn max_assign(&self, n: $t) {
+                    self.set(self.get().max(n))
+                }
+            }
+        )*
+    }
+}
+num_ext!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// Arithmetic on floating point cells, to avoid writing out
+/// `c.set(c.get() * x)`.
+///
+/// This is implemented for `Cell<f32>` and `Cell<f64>`, and cannot be
+/// implemented outside this crate.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::CellFloatExt;
+///
+/// let mut volume = 0.5f32;
+/// let mut speed = 0.0f64;
+/// {
+///     let (volume, speed) = (alias::one(&mut volume), alias::one(&mut speed));
+///     let louder = || { volume.mul(1.5); volume.clamp_assign(0.0, 1.0); };
+///     let accelerate = |target: f64| speed.lerp_toward(target, 0.5);
+///
+///     louder();
+///     louder();
+///     accelerate(8.0);
+///     accelerate(8.0);
+///     speed.min_assign(5.0);
+/// }
+/// assert_eq!((volume, speed), (1.0, 5.0));
+/// ```
+pub trait CellFloatExt: private::Sealed {
+    /// The float type in the cell.
+    type Float;
+
+    /// Add `x` to the contents.
+    fn add(&self, x: Self::Float);
+
+    /// Subtract `x` from the contents.
+    fn sub(&self, x: Self::Float);
+
+    /// Multiply the contents by `x`.
+    fn mul(&self, x: Self::Float);
+
+    /// Replace the contents with `x`, if `x` is smaller, as with
+    /// `min` (so a NaN is replaced).
+    fn min_assign(&self, x: Self::Float);
+
+    /// Replace the contents with `x`, if `x` is larger, as with `max`
+    /// (so a NaN is replaced).
+    fn max_assign(&self, x: Self::Float);
+
+    /// Restrict the contents to the interval `[min, max]`, as with
+    /// `clamp`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min > max`, or either is NaN.
+    fn clamp_assign(&self, min: Self::Float, max: Self::Float);
+
+    /// Move the contents the fraction `t` of the way to `target`,
+    /// where `t = 0` leaves it unchanged and `t = 1` sets it to
+    /// `target`.
+    fn lerp_toward(&self, target: Self::Float, t: Self::Float);
+}
+
+macro_rules! float_ext {
+    ($($t: ty),*) => {
+        $(
+            impl private::Sealed for Cell<$t> {}
+
+            impl CellFloatExt for Cell<$t> {
+                type Float = $t;
+
+                fn add(&self, x: $t) {
+                    self.set(self.get() + x)
+                }
+                fn sub(&self, x: $t) {
+                    self.set(self.get() - x)
+                }
+                fn mul(&self, x: $t) {
+                    self.set(self.get() * x)
+                }
+                fn min_assign(&self, x: $t) {
+                    self.set(self.get().min(x))
+                }
+                fn max_assign(&self, x: $t) {
+                    self.set(self.get().max(x))
+                }
+                fn clamp_assign(&self, min: $t, max: $t) {
+                    self.set(self.get().clamp(min, max))
+                }
+                fn lerp_toward(&self, target: $t, t: $t) {
+                    let x = self.get();
+                    self.set(x + (target - x) * t)
+                }
+            }
+        )*
+    }
+}
+float_ext!(f32, f64);
+
+/// Helpers for cells holding an `Option`.
+///
+/// `Cell::take` already works for these, leaving `None` behind.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::CellOptionExt;
+///
+/// let mut best: Option<(u32, &str)> = None;
+/// {
+///     let best = alias::one(&mut best);
+///     assert!(best.set_if_none((3, "c")));
+///     assert!(!best.set_if_none((1, "a")));
+///     assert_eq!(best.map_copied(|(score, _)| score), Some(3));
+///     assert_eq!(best.take(), Some((3, "c")));
+///     assert_eq!(best.get_or_set_with(|| (0, "default")), (0, "default"));
+/// }
+/// assert_eq!(best, Some((0, "default")));
+/// ```
+pub trait CellOptionExt<T> {
+    /// Whether the cell holds `Some`.
+    fn is_some(&self) -> bool;
+
+    /// Whether the cell holds `None`.
+    fn is_none(&self) -> bool {
+        !self.is_some()
+    }
+
+    /// The value in the cell, first setting it to `f()` if the cell
+    /// holds `None`.
+    ///
+    /// If `f` itself sets the cell (through another reference), that
+    /// value is overwritten.
+    fn get_or_set_with<F>(&self, f: F) -> T
+        where T: Copy, F: FnOnce() -> T;
+
+    /// Set the cell to `Some(value)` if it holds `None`, returning
+    /// whether it did.
+    fn set_if_none(&self, value: T) -> bool;
+
+    /// Apply `f` to a copy of the value in the cell, if any.
+    fn map_copied<U, F>(&self, f: F) -> Option<U>
+        where T: Copy, F: FnOnce(T) -> U;
+
+    /// Call `f` with a mutable reference to the value in the cell, if
+    /// any, returning its result.
+    ///
+    /// The value is moved out for the duration of the call, so it
+    /// needn't be `Copy` or `Clone`, and a large value is moved twice
+    /// rather than copied at every access. While `f` runs, the cell
+    /// holds `None`; if `f` sets the cell (through another reference),
+    /// that value is overwritten, and if `f` panics, the cell is left
+    /// holding `None`.
+    ///
+    /// There is no way to get a `&Cell<T>` to the payload instead:
+    /// another reference could set the `Option` to `None` while it is
+    /// in use.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use alias::CellOptionExt;
+    ///
+    /// let mut pending: Option<Vec<u32>> = Some(vec![]);
+    /// {
+    ///     let p = alias::one(&mut pending);
+    ///     let submit = |job| p.with_some(|jobs| jobs.push(job)).is_some();
+    ///     assert!(submit(1));
+    ///     assert!(submit(2));
+    ///     let done = p.take();
+    ///     assert!(!submit(3));
+    ///     assert_eq!(done, Some(vec![1, 2]));
+    /// }
+    /// ```
+    fn with_some<R, F>(&self, f: F) -> Option<R>
+        where F: FnOnce(&mut T) -> R;
+}
+
+impl<T> CellOptionExt<T> for Cell<Option<T>> {
+    fn is_some(&self) -> bool {
+        // `Option<T>` isn't necessarily `Copy`, so look by moving the
+        // value out and back.
+        let v = self.take();
+        let ret = v.is_some();
+        self.set(v);
+        ret
+    }
+
+    fn get_or_set_with<F>(&self, f: F) -> T
+        where T: Copy, F: FnOnce() -> T
+    {
+        match self.get() {
+            Some(v) => v,
+            None => {
+                let v = f();
+                self.set(Some(v));
+                v
+            }
+        }
+    }
+
+    fn set_if_none(&self, value: T) -> bool {
+        let v = self.take();
+        let ret = v.is_none();
+        self.set(Some(v.unwrap_or(value)));
+        ret
+    }
+
+    fn map_copied<U, F>(&self, f: F) -> Option<U>
+        where T: Copy, F: FnOnce(T) -> U
+    {
+        self.get().map(f)
+    }
+
+    fn with_some<R, F>(&self, f: F) -> Option<R>
+        where F: FnOnce(&mut T) -> R
+    {
+        let mut v = self.take()?;
+        let ret = f(&mut v);
+        self.set(Some(v));
+        Some(ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_num() {
+        let c = Cell::new(10i8);
+        c.add(5);
+        c.sub(20);
+        assert_eq!(c.fetch_add(1), -5);
+        c.set(100);
+        c.wrapping_add(100);
+        assert_eq!(c.get(), -56);
+        c.saturating_add(-100);
+        assert_eq!(c.get(), -128);
+        c.max_assign(-4);
+        c.min_assign(-3);
+        assert_eq!(c.get(), -4);
+    }
+
+    #[test]
+    fn smoke_float() {
+        let c = Cell::new(2.0f64);
+        c.add(1.0);
+        c.mul(4.0);
+        c.sub(2.0);
+        c.max_assign(f64::NAN);
+        assert_eq!(c.get(), 10.0);
+        c.lerp_toward(20.0, 0.25);
+        c.clamp_assign(-1.0, 12.0);
+        assert_eq!(c.get(), 12.0);
+        c.set(f64::NAN);
+        c.min_assign(3.0);
+        assert_eq!(c.get(), 3.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn clamp_inverted() {
+        Cell::new(0.0f32).clamp_assign(1.0, 0.0);
+    }
+
+    #[test]
+    fn smoke_update_with() {
+        let c = Cell::new(String::from("a"));
+        let old = c.update_with(|s| {
+            // reentrant accesses see the default
+            assert_eq!(c.take(), "");
+            c.set(String::from("lost"));
+            s.clone() + "b"
+        });
+        assert_eq!((old, c.take()), (String::from("a"), String::from("ab")));
+    }
+
+    #[test]
+    fn smoke_conditional_set() {
+        let c = Cell::new(1.5f32);
+        assert!(!c.set_if(f32::is_nan, 0.0));
+        assert_eq!(c.compare_and_set(1.5, 2.0), Ok(1.5));
+        assert_eq!(c.compare_and_set(1.5, 3.0), Err(2.0));
+        assert!(c.set_if(|x| x > 1.0, f32::NAN));
+        // NaN never compares equal
+        assert!(c.compare_and_set(f32::NAN, 0.0).unwrap_err().is_nan());
+    }
+
+    #[test]
+    fn replace_with_panic_leaves_default() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let c = Cell::new(vec![1, 2]);
+        c.replace_with(|mut v| { v.push(3); v });
+        assert_eq!(c.take(), [1, 2, 3]);
+        c.set(vec![4]);
+        let r = catch_unwind(AssertUnwindSafe(|| c.replace_with(|_| panic!("oops"))));
+        assert!(r.is_err());
+        assert_eq!(c.take(), []);
+    }
+
+    #[test]
+    fn smoke_option() {
+        let c = Cell::new(None);
+        assert!(c.is_none());
+        assert!(c.set_if_none(String::from("a")));
+        assert!(!c.set_if_none(String::from("b")));
+        assert!(c.is_some());
+        assert_eq!(c.take().as_deref(), Some("a"));
+
+        let d = Cell::new(None);
+        let mut calls = 0;
+        assert_eq!(d.get_or_set_with(|| { calls += 1; 5 }), 5);
+        assert_eq!(d.get_or_set_with(|| { calls += 1; 6 }), 5);
+        assert_eq!((calls, d.map_copied(|x| x * 2)), (1, Some(10)));
+
+        let e = Cell::new(Some(String::from("a")));
+        let len = e.with_some(|s| {
+            assert!(e.is_none());
+            e.set(Some(String::from("lost")));
+            s.push('b');
+            s.len()
+        });
+        assert_eq!((len, e.take().as_deref()), (Some(2), Some("ab")));
+        assert_eq!(e.with_some(|s| s.len()), None);
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(debug_assertions)]
+    fn add_overflow() {
+        Cell::new(255u8).add(1);
+    }
+}
@@ -0,0 +1,79 @@
+use std::cell::Cell;
+use std::mem;
+
+/// Floating point types with an unsigned integer type of exactly the
+/// same size and alignment, that holds their bit pattern.
+///
+/// This is implemented for `f32` (bits `u32`) and `f64` (bits `u64`),
+/// and cannot be implemented outside this crate.
+pub trait FloatBits: Copy + private::Sealed {
+    /// The integer type holding the bit pattern.
+    type Bits: Copy;
+}
+
+impl FloatBits for f32 {
+    type Bits = u32;
+}
+impl FloatBits for f64 {
+    type Bits = u64;
+}
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for f32 {}
+    impl Sealed for f64 {}
+}
+
+/// Allow the bit patterns of the floats in `data` to be mutated while
+/// aliased.
+///
+/// Every bit pattern is a valid float (and vice versa), so this is
+/// the aliased equivalent of `to_bits`/`from_bits`, useful for
+/// NaN-boxing, sign manipulation and total-order comparisons.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut x = [1.5f32, -2.0, 0.25];
+///
+/// {
+///     let bits = alias::float_bits(&mut x);
+///     // flip the sign of everything
+///     for b in bits {
+///         b.set(b.get() ^ 0x8000_0000);
+///     }
+///     assert_eq!(bits[0].get(), (-1.5f32).to_bits());
+/// }
+///
+/// assert_eq!(x, [-1.5, 2.0, -0.25]);
+/// ```
+pub fn float_bits<F: FloatBits>(data: &mut [F]) -> &[Cell<F::Bits>] {
+    debug_assert_eq!(mem::size_of::<F>(), mem::size_of::<F::Bits>());
+    debug_assert_eq!(mem::align_of::<F>(), mem::align_of::<F::Bits>());
+    unsafe { &*(data as *mut [F] as *const [Cell<F::Bits>]) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_float_bits() {
+        let mut x = [0.0f64, 1.0, f64::NAN];
+        {
+            let y = float_bits(&mut x);
+            let z = y;
+            assert_eq!(y[1].get(), 1.0f64.to_bits());
+            // NaN-box a payload
+            z[2].set(0x7FF8_0000_0000_0000 | 0xBEEF);
+            y[0].set(y[0].get() | (1 << 63));
+        }
+        assert!(x[0] == 0.0 && x[0].is_sign_negative());
+        assert!(x[2].is_nan());
+        assert_eq!(x[2].to_bits() & 0xFFFF, 0xBEEF);
+
+        let mut a = [1.0f32];
+        float_bits(&mut a)[0].set(2.0f32.to_bits());
+        assert_eq!(a, [2.0]);
+    }
+}
@@ -0,0 +1,168 @@
+use std::cell::Cell;
+use std::fmt;
+use std::ptr;
+
+use slice;
+
+const USED: usize = usize::MAX;
+
+/// A pool allocator over borrowed storage, where every operation works
+/// through shared references.
+///
+/// Each slot of the storage is a `(link, value)` pair. The links of
+/// free slots thread a singly-linked free list through the storage, so
+/// allocating and freeing are both `O(1)` and need no memory beyond
+/// the storage itself. Values are left untouched when freed, so a
+/// stale handle to a freed slot can still be used safely, it just sees
+/// whatever the slot is later reused for.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::CellFreeList;
+///
+/// let mut storage = [(0, [0.0; 2]); 3];
+/// let pool = CellFreeList::new(&mut storage);
+///
+/// let (i, a) = pool.alloc().unwrap();
+/// let (j, b) = pool.alloc().unwrap();
+/// a.set([1.0, 2.0]);
+/// b.set(a.get());
+///
+/// pool.free(i);
+/// let (k, _) = pool.alloc().unwrap();
+/// assert_eq!(k, i);
+/// assert_eq!(pool.get(j).unwrap().get(), [1.0, 2.0]);
+/// ```
+pub struct CellFreeList<'a, T: 'a> {
+    slots: &'a [Cell<(usize, T)>],
+    head: Cell<usize>,
+    len: Cell<usize>,
+}
+
+fn link_of<T>(slot: &Cell<(usize, T)>) -> &Cell<usize> {
+    unsafe { &*(ptr::addr_of!((*slot.as_ptr()).0) as *const Cell<usize>) }
+}
+fn value_of<T>(slot: &Cell<(usize, T)>) -> &Cell<T> {
+    unsafe { &*(ptr::addr_of!((*slot.as_ptr()).1) as *const Cell<T>) }
+}
+
+impl<'a, T: Copy> CellFreeList<'a, T> {
+    /// Create a pool using `storage` for its slots, all initially
+    /// free.
+    ///
+    /// The values in `storage` are left as they are; the links are
+    /// overwritten.
+    pub fn new(storage: &'a mut [(usize, T)]) -> CellFreeList<'a, T> {
+        for (i, slot) in storage.iter_mut().enumerate() {
+            slot.0 = i + 1;
+        }
+        CellFreeList { slots: slice(storage), head: Cell::new(0), len: Cell::new(0) }
+    }
+
+    /// The number of slots.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// The number of allocated slots.
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+
+    /// Whether no slots are allocated.
+    pub fn is_empty(&self) -> bool {
+        self.len.get() == 0
+    }
+
+    /// Allocate a free slot, returning its index and value, or `None`
+    /// if every slot is in use.
+    ///
+    /// The value is whatever was last stored in the slot.
+    pub fn alloc(&self) -> Option<(usize, &'a Cell<T>)> {
+        let i = self.head.get();
+        let slot = self.slots.get(i)?;
+        let link = link_of(slot);
+        self.head.set(link.get());
+        link.set(USED);
+        self.len.set(self.len.get() + 1);
+        Some((i, value_of(slot)))
+    }
+
+    /// The value of the allocated slot `index`, or `None` if it is free
+    /// or out of bounds.
+    pub fn get(&self, index: usize) -> Option<&'a Cell<T>> {
+        let slot = self.slots.get(index)?;
+        if link_of(slot).get() == USED {
+            Some(value_of(slot))
+        } else {
+            None
+        }
+    }
+
+    /// Return the slot `index` to the pool.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds or not currently allocated.
+    pub fn free(&self, index: usize) {
+        let link = link_of(&self.slots[index]);
+        assert!(link.get() == USED, "CellFreeList::free: slot {} is not allocated", index);
+        link.set(self.head.get());
+        self.head.set(index);
+        self.len.set(self.len.get() - 1);
+    }
+}
+
+impl<'a, T: Copy + fmt::Debug> fmt::Debug for CellFreeList<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let used = self.slots.iter().enumerate()
+            .filter(|&(_, s)| link_of(s).get() == USED)
+            .map(|(i, s)| (i, value_of(s).get()));
+        f.debug_map().entries(used).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_freelist() {
+        let mut x = [(0, 0u8); 3];
+        {
+            let p = CellFreeList::new(&mut x);
+            let (a, ca) = p.alloc().unwrap();
+            let (b, _) = p.alloc().unwrap();
+            let (c, cc) = p.alloc().unwrap();
+            assert_eq!((a, b, c), (0, 1, 2));
+            assert!(p.alloc().is_none());
+            assert_eq!(p.len(), 3);
+
+            ca.set(10);
+            cc.set(30);
+            p.free(b);
+            p.free(a);
+            assert!(p.get(a).is_none());
+            assert_eq!(p.get(c).unwrap().get(), 30);
+            assert_eq!(format!("{:?}", p), "{2: 30}");
+
+            // LIFO reuse
+            assert_eq!(p.alloc().unwrap().0, a);
+            assert_eq!(p.alloc().unwrap().0, b);
+            assert!(p.alloc().is_none());
+            assert_eq!(p.get(a).unwrap().get(), 10);
+        }
+        assert_eq!(x.iter().map(|s| s.1).collect::<Vec<_>>(), [10, 0, 30]);
+    }
+
+    #[test]
+    #[should_panic(expected = "not allocated")]
+    fn double_free() {
+        let mut x = [(0, ()); 2];
+        let p = CellFreeList::new(&mut x);
+        let (i, _) = p.alloc().unwrap();
+        p.free(i);
+        p.free(i);
+    }
+}
@@ -0,0 +1,140 @@
+use std::cell::Cell;
+use std::error::Error;
+use std::fmt;
+
+use one;
+
+/// A rule for which state transitions are allowed.
+///
+/// This is implemented for closures `Fn(from, to) -> bool` and for
+/// tables of allowed `(from, to)` pairs, `&[(S, S)]`.
+pub trait Transitions<S> {
+    /// Whether moving from `from` to `to` is allowed.
+    fn allowed(&self, from: S, to: S) -> bool;
+}
+
+impl<S, F: Fn(S, S) -> bool> Transitions<S> for F {
+    fn allowed(&self, from: S, to: S) -> bool {
+        self(from, to)
+    }
+}
+
+impl<S: PartialEq> Transitions<S> for &[(S, S)] {
+    fn allowed(&self, from: S, to: S) -> bool {
+        self.iter().any(|(f, t)| *f == from && *t == to)
+    }
+}
+
+/// An attempted state transition that was rejected.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct InvalidTransition<S> {
+    /// The state at the time of the attempt, which is unchanged.
+    pub from: S,
+    /// The state that was requested.
+    pub to: S,
+}
+
+impl<S: fmt::Debug> fmt::Display for InvalidTransition<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid transition from {:?} to {:?}", self.from, self.to)
+    }
+}
+
+impl<S: fmt::Debug> Error for InvalidTransition<S> {}
+
+/// An aliased state machine, which only allows the transitions
+/// permitted by a fixed rule.
+///
+/// The state can be read through any number of shared references,
+/// and changed with `transition`.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::{FsmCell, InvalidTransition};
+///
+/// #[derive(Copy, Clone, PartialEq, Debug)]
+/// enum Conn { Closed, Opening, Open }
+///
+/// let mut state = Conn::Closed;
+/// let table: &[_] = &[(Conn::Closed, Conn::Opening),
+///                     (Conn::Opening, Conn::Open),
+///                     (Conn::Opening, Conn::Closed),
+///                     (Conn::Open, Conn::Closed)];
+///
+/// let fsm = FsmCell::new(&mut state, table);
+/// let (reader, writer) = (&fsm, &fsm);
+///
+/// assert_eq!(writer.transition(Conn::Opening), Ok(Conn::Closed));
+/// assert_eq!(writer.transition(Conn::Opening),
+///            Err(InvalidTransition { from: Conn::Opening, to: Conn::Opening }));
+/// assert_eq!(reader.get(), Conn::Opening);
+/// ```
+pub struct FsmCell<'a, S: 'a, R> {
+    state: &'a Cell<S>,
+    rule: R,
+}
+
+impl<'a, S: Copy, R: Transitions<S>> FsmCell<'a, S, R> {
+    /// Allow the state `state` to be read while aliased, and changed
+    /// according to `rule`.
+    pub fn new(state: &'a mut S, rule: R) -> FsmCell<'a, S, R> {
+        FsmCell { state: one(state), rule }
+    }
+
+    /// The current state.
+    pub fn get(&self) -> S {
+        self.state.get()
+    }
+
+    /// Whether moving to the state `to` is currently allowed.
+    pub fn can_transition(&self, to: S) -> bool {
+        self.rule.allowed(self.state.get(), to)
+    }
+
+    /// Move to the state `to`, returning the previous state, or fail
+    /// without changing anything if that transition isn't allowed.
+    pub fn transition(&self, to: S) -> Result<S, InvalidTransition<S>> {
+        let from = self.state.get();
+        if self.rule.allowed(from, to) {
+            self.state.set(to);
+            Ok(from)
+        } else {
+            Err(InvalidTransition { from, to })
+        }
+    }
+}
+
+impl<'a, S: Copy + fmt::Debug, R> fmt::Debug for FsmCell<'a, S, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FsmCell")
+            .field("state", &self.state.get())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_fsm() {
+        let mut x = 0u32;
+        {
+            // only ever step forward by one, up to 3
+            let fsm = FsmCell::new(&mut x, |from, to| to == from + 1 && to <= 3);
+            let a = &fsm;
+            let b = &fsm;
+            assert!(a.can_transition(1));
+            assert!(!b.can_transition(2));
+            assert_eq!(a.transition(1), Ok(0));
+            assert_eq!(b.transition(3), Err(InvalidTransition { from: 1, to: 3 }));
+            assert_eq!(b.transition(2), Ok(1));
+            assert_eq!(a.transition(3), Ok(2));
+            assert_eq!(a.transition(4).unwrap_err().to_string(),
+                       "invalid transition from 3 to 4");
+            assert_eq!(format!("{:?}", b), "FsmCell { state: 3 }");
+        }
+        assert_eq!(x, 3);
+    }
+}
@@ -0,0 +1,521 @@
+use std::cell::Cell;
+use std::fmt;
+
+use slice;
+
+/// A two-dimensional aliased view of a row-major buffer.
+///
+/// Created by `CellGrid::new`.
+pub struct CellGrid<'a, T: 'a> {
+    cells: &'a [Cell<T>],
+    width: usize,
+    height: usize,
+}
+
+impl<'a, T> Clone for CellGrid<'a, T> {
+    fn clone(&self) -> CellGrid<'a, T> { *self }
+}
+impl<'a, T> Copy for CellGrid<'a, T> {}
+
+impl<'a, T: Copy + fmt::Debug> fmt::Debug for CellGrid<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CellGrid")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("cells", &self.cells)
+            .finish()
+    }
+}
+
+impl<'a, T: Copy> CellGrid<'a, T> {
+    /// The element at column `x` and row `y`, or `None` if that is out
+    /// of bounds.
+    pub fn get(&self, x: usize, y: usize) -> Option<T> {
+        self.cell(x, y).map(Cell::get)
+    }
+
+    /// Set the element at column `x` and row `y`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `(x, y)` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// // one step of the game of life
+    /// let mut cur = [0, 1, 0, 0, 0,
+    ///                0, 1, 0, 0, 0,
+    ///                0, 1, 0, 0, 0,
+    ///                0, 0, 0, 0, 0];
+    /// let mut next = [0; 20];
+    ///
+    /// let g = alias::CellGrid::new(&mut cur, 5, 4);
+    /// let n = alias::CellGrid::new(&mut next, 5, 4);
+    /// for y in 0..g.height() {
+    ///     for x in 0..g.width() {
+    ///         let live = g.neighbors(x, y).filter(|c| c.get() == 1).count();
+    ///         let alive = g.get(x, y) == Some(1);
+    ///         n.set(x, y, (live == 3 || alive && live == 2) as u8);
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(next, [0, 0, 0, 0, 0,
+    ///                   1, 1, 1, 0, 0,
+    ///                   0, 0, 0, 0, 0,
+    ///                   0, 0, 0, 0, 0]);
+    /// ```
+    pub fn set(&self, x: usize, y: usize, value: T) {
+        match self.cell(x, y) {
+            Some(c) => c.set(value),
+            None => panic!("CellGrid::set: ({}, {}) out of bounds for a {}x{} grid",
+                           x, y, self.width, self.height),
+        }
+    }
+}
+
+impl<'a, T> CellGrid<'a, T> {
+    /// Allow the row-major `width` × `height` grid stored in `data` to
+    /// be mutated while aliased.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data.len() != width * height`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let mut x = [0; 6];
+    ///
+    /// {
+    ///     let g = alias::CellGrid::new(&mut x, 3, 2);
+    ///     let h = g;
+    ///     g.cell(2, 1).unwrap().set(5);
+    ///     h.cell(0, 0).unwrap().set(h.cell(2, 1).unwrap().get() + 1);
+    ///     assert!(g.cell(3, 0).is_none());
+    /// }
+    ///
+    /// assert_eq!(x, [6, 0, 0,
+    ///                0, 0, 5]);
+    /// ```
+    pub fn new(data: &'a mut [T], width: usize, height: usize) -> CellGrid<'a, T> {
+        assert_eq!(Some(data.len()), width.checked_mul(height),
+                   "CellGrid::new: buffer length doesn't match dimensions");
+        CellGrid { cells: slice(data), width, height }
+    }
+
+    /// The number of columns.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The number of rows.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The whole grid as a flat row-major slice.
+    pub fn as_slice(&self) -> &'a [Cell<T>] {
+        self.cells
+    }
+
+    /// The cell at column `x` and row `y`, or `None` if that is out of
+    /// bounds.
+    pub fn cell(&self, x: usize, y: usize) -> Option<&'a Cell<T>> {
+        if x < self.width && y < self.height {
+            Some(&self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    /// The row `y`, or `None` if that is out of bounds.
+    pub fn row(&self, y: usize) -> Option<&'a [Cell<T>]> {
+        if y < self.height {
+            Some(&self.cells[y * self.width..(y + 1) * self.width])
+        } else {
+            None
+        }
+    }
+
+    /// Iterate over the rows, from top to bottom.
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &'a [Cell<T>]> + ExactSizeIterator {
+        let grid = *self;
+        (0..self.height).map(move |y| &grid.cells[y * grid.width..(y + 1) * grid.width])
+    }
+
+    /// Iterate over the (up to eight) in-bounds elements adjacent to
+    /// column `x` and row `y`, including diagonally, in row-major
+    /// order.
+    ///
+    /// This is empty if `(x, y)` is out of bounds.
+    pub fn neighbors(&self, x: usize, y: usize) -> impl Iterator<Item = &'a Cell<T>> {
+        let grid = *self;
+        let valid = x < self.width && y < self.height;
+        (0..3).flat_map(move |dy| (0..3).filter_map(move |dx| {
+            if !valid || (dx, dy) == (1, 1) {
+                return None
+            }
+            // offsets of -1 wrap to usize::MAX, which is out of bounds.
+            grid.cell((x + dx).wrapping_sub(1), (y + dy).wrapping_sub(1))
+        }))
+    }
+
+    /// Iterate over the `(2 * radius + 1)`-square neighbourhoods of
+    /// every element that is at least `radius` away from the edges.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let mut x = [1, 2, 3,
+    ///              4, 5, 6,
+    ///              7, 8, 9];
+    ///
+    /// let g = alias::CellGrid::new(&mut x, 3, 3);
+    /// for n in g.neighborhoods(1) {
+    ///     let sum: i32 = n.cells().map(|c| c.get()).sum();
+    ///     n.center().set(sum);
+    /// }
+    ///
+    /// assert_eq!(g.cell(1, 1).unwrap().get(), 45);
+    /// ```
+    pub fn neighborhoods(&self, radius: usize) -> Neighborhoods<'a, T> {
+        Neighborhoods {
+            grid: *self,
+            radius,
+            wrap: false,
+            x: radius,
+            y: radius,
+        }
+    }
+
+    /// View the grid as a torus, where indexing wraps around the edges.
+    pub fn wrapping(&self) -> WrappingGrid<'a, T> {
+        WrappingGrid { grid: *self }
+    }
+}
+
+/// A two-dimensional aliased view of a row-major buffer, where
+/// indexing wraps around the edges.
+///
+/// Created by `WrappingGrid::new` or `CellGrid::wrapping`.
+pub struct WrappingGrid<'a, T: 'a> {
+    grid: CellGrid<'a, T>,
+}
+
+impl<'a, T> Clone for WrappingGrid<'a, T> {
+    fn clone(&self) -> WrappingGrid<'a, T> { *self }
+}
+impl<'a, T> Copy for WrappingGrid<'a, T> {}
+
+impl<'a, T: Copy + fmt::Debug> fmt::Debug for WrappingGrid<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WrappingGrid")
+            .field("width", &self.grid.width)
+            .field("height", &self.grid.height)
+            .field("cells", &self.grid.cells)
+            .finish()
+    }
+}
+
+impl<'a, T> WrappingGrid<'a, T> {
+    /// Allow the row-major `width` × `height` torus stored in `data`
+    /// to be mutated while aliased.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data.len() != width * height`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let mut x = [0; 6];
+    ///
+    /// {
+    ///     let g = alias::WrappingGrid::new(&mut x, 3, 2);
+    ///     g.cell(-1, -1).set(5);
+    ///     g.cell(3, 0).set(g.cell(2, 1).get() + 1);
+    /// }
+    ///
+    /// assert_eq!(x, [6, 0, 0,
+    ///                0, 0, 5]);
+    /// ```
+    pub fn new(data: &'a mut [T], width: usize, height: usize) -> WrappingGrid<'a, T> {
+        CellGrid::new(data, width, height).wrapping()
+    }
+
+    /// The number of columns.
+    pub fn width(&self) -> usize {
+        self.grid.width
+    }
+
+    /// The number of rows.
+    pub fn height(&self) -> usize {
+        self.grid.height
+    }
+
+    /// The grid with non-wrapping indexing.
+    pub fn unwrapped(&self) -> CellGrid<'a, T> {
+        self.grid
+    }
+
+    /// The cell at column `x` and row `y`, taken modulo the width and
+    /// height respectively.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grid is empty.
+    pub fn cell(&self, x: isize, y: isize) -> &'a Cell<T> {
+        self.grid.wrapped(x, y)
+    }
+
+    /// Iterate over the `(2 * radius + 1)`-square neighbourhoods of
+    /// every element, wrapping around the edges.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let mut x = [0, 1, 0,
+    ///              0, 0, 0];
+    ///
+    /// let g = alias::WrappingGrid::new(&mut x, 3, 2);
+    /// let counts: Vec<i32> = g.neighborhoods(1)
+    ///     .map(|n| n.cells().map(|c| c.get()).sum())
+    ///     .collect();
+    ///
+    /// // with only two rows, the neighbourhoods of the bottom row
+    /// // wrap around to see the top row twice.
+    /// assert_eq!(counts, [1, 1, 1, 2, 2, 2]);
+    /// ```
+    pub fn neighborhoods(&self, radius: usize) -> Neighborhoods<'a, T> {
+        Neighborhoods {
+            grid: self.grid,
+            radius,
+            wrap: true,
+            x: 0,
+            y: 0,
+        }
+    }
+}
+
+impl<'a, T> CellGrid<'a, T> {
+    fn wrapped(&self, x: isize, y: isize) -> &'a Cell<T> {
+        let x = x.rem_euclid(self.width as isize) as usize;
+        let y = y.rem_euclid(self.height as isize) as usize;
+        &self.cells[y * self.width + x]
+    }
+}
+
+/// The square neighbourhood around one element of a `CellGrid` or
+/// `WrappingGrid`.
+pub struct Neighborhood<'a, T: 'a> {
+    grid: CellGrid<'a, T>,
+    radius: usize,
+    wrap: bool,
+    x: usize,
+    y: usize,
+}
+
+impl<'a, T> Clone for Neighborhood<'a, T> {
+    fn clone(&self) -> Neighborhood<'a, T> { *self }
+}
+impl<'a, T> Copy for Neighborhood<'a, T> {}
+
+impl<'a, T> fmt::Debug for Neighborhood<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Neighborhood")
+            .field("position", &(self.x, self.y))
+            .field("radius", &self.radius)
+            .field("wrap", &self.wrap)
+            .finish()
+    }
+}
+
+impl<'a, T> Neighborhood<'a, T> {
+    /// The coordinates `(x, y)` of the centre in the grid.
+    pub fn position(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+
+    /// The radius of the neighbourhood.
+    pub fn radius(&self) -> usize {
+        self.radius
+    }
+
+    /// The centre element.
+    pub fn center(&self) -> &'a Cell<T> {
+        &self.grid.cells[self.y * self.grid.width + self.x]
+    }
+
+    /// The element offset from the centre by `(dx, dy)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either offset is further than `radius` from zero.
+    pub fn at(&self, dx: isize, dy: isize) -> &'a Cell<T> {
+        let r = self.radius as isize;
+        assert!(-r <= dx && dx <= r && -r <= dy && dy <= r,
+                "Neighborhood::at: offset ({}, {}) outside radius {}", dx, dy, r);
+        let x = self.x as isize + dx;
+        let y = self.y as isize + dy;
+        if self.wrap {
+            self.grid.wrapped(x, y)
+        } else {
+            &self.grid.cells[y as usize * self.grid.width + x as usize]
+        }
+    }
+
+    /// Iterate over every element in the neighbourhood, including the
+    /// centre, in row-major order.
+    pub fn cells(&self) -> impl Iterator<Item = &'a Cell<T>> {
+        let n = *self;
+        let r = self.radius as isize;
+        (-r..r + 1).flat_map(move |dy| (-r..r + 1).map(move |dx| n.at(dx, dy)))
+    }
+}
+
+/// An iterator over the neighbourhoods of a `CellGrid`.
+///
+/// Created by `CellGrid::neighborhoods` or `WrappingGrid::neighborhoods`.
+pub struct Neighborhoods<'a, T: 'a> {
+    grid: CellGrid<'a, T>,
+    radius: usize,
+    wrap: bool,
+    x: usize,
+    y: usize,
+}
+
+impl<'a, T> fmt::Debug for Neighborhoods<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Neighborhoods")
+            .field("position", &(self.x, self.y))
+            .field("radius", &self.radius)
+            .field("wrap", &self.wrap)
+            .finish()
+    }
+}
+
+impl<'a, T> Iterator for Neighborhoods<'a, T> {
+    type Item = Neighborhood<'a, T>;
+
+    fn next(&mut self) -> Option<Neighborhood<'a, T>> {
+        // wrapping neighbourhoods exist for every element, otherwise
+        // only for those `radius` away from the edges.
+        let margin = if self.wrap { 0 } else { self.radius };
+        // a radius too large to add up can't fit either.
+        let span = margin.checked_mul(2).and_then(|m| m.checked_add(1));
+        let bottom = self.y.checked_add(margin);
+        if span.is_none_or(|s| s > self.grid.width) || bottom.is_none_or(|b| b >= self.grid.height) {
+            return None
+        }
+        let n = Neighborhood {
+            grid: self.grid,
+            radius: self.radius,
+            wrap: self.wrap,
+            x: self.x,
+            y: self.y,
+        };
+        self.x += 1;
+        if self.x + margin >= self.grid.width {
+            self.x = margin;
+            self.y += 1;
+        }
+        Some(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_neighborhoods() {
+        let mut x = [0u32; 20];
+        for (i, v) in x.iter_mut().enumerate() {
+            *v = i as u32;
+        }
+        {
+            let g = CellGrid::new(&mut x, 5, 4);
+            let ns: Vec<_> = g.neighborhoods(1).collect();
+            assert_eq!(ns.iter().map(|n| n.position()).collect::<Vec<_>>(),
+                       [(1, 1), (2, 1), (3, 1), (1, 2), (2, 2), (3, 2)]);
+
+            let n = ns[4];
+            assert_eq!(n.center().get(), 12);
+            assert_eq!(n.at(-1, -1).get(), 6);
+            assert_eq!(n.at(1, 1).get(), 18);
+            assert_eq!(n.cells().map(|c| c.get()).collect::<Vec<_>>(),
+                       [6, 7, 8, 11, 12, 13, 16, 17, 18]);
+            n.at(0, 1).set(100);
+
+            assert_eq!(g.neighborhoods(2).count(), 0);
+            assert_eq!(g.neighborhoods(usize::MAX / 2).count(), 0);
+            assert_eq!(g.neighborhoods(usize::MAX).count(), 0);
+            assert_eq!(g.neighborhoods(0).count(), 20);
+        }
+        assert_eq!(x[17], 100);
+    }
+
+    #[test]
+    fn smoke_access() {
+        let mut x = [1, 2, 3,
+                     4, 5, 6];
+        {
+            let g = CellGrid::new(&mut x, 3, 2);
+            assert_eq!((g.get(2, 1), g.get(3, 0), g.get(0, 2)), (Some(6), None, None));
+            g.set(1, 0, 20);
+            g.row(1).unwrap()[0].set(40);
+            assert!(g.row(2).is_none());
+            assert_eq!(g.rows().map(|r| r[0].get()).collect::<Vec<_>>(), [1, 40]);
+            assert_eq!(g.rows().rev().len(), 2);
+
+            let ns = |x, y| g.neighbors(x, y).map(|c| c.get()).collect::<Vec<_>>();
+            assert_eq!(ns(0, 0), [20, 40, 5]);
+            assert_eq!(ns(1, 1), [1, 20, 3, 40, 6]);
+            assert_eq!(ns(3, 1), []);
+
+            let mut e: [u8; 0] = [];
+            let empty = CellGrid::new(&mut e, 0, 3);
+            assert_eq!(empty.rows().filter(|r| r.is_empty()).count(), 3);
+
+            let mut names = [String::from("a"), String::new()];
+            let w = WrappingGrid::new(&mut names, 2, 1);
+            assert_eq!(w.cell(-1, 0).take(), "");
+            assert_eq!(w.unwrapped().cell(0, 0).unwrap().take(), "a");
+        }
+        assert_eq!(x, [1, 20, 3, 40, 5, 6]);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn set_out_of_bounds() {
+        let mut x = [0; 6];
+        CellGrid::new(&mut x, 3, 2).set(3, 0, 1);
+    }
+
+    #[test]
+    fn smoke_wrapping() {
+        let mut x = [0u32; 12];
+        for (i, v) in x.iter_mut().enumerate() {
+            *v = i as u32;
+        }
+        {
+            let g = WrappingGrid::new(&mut x, 4, 3);
+            assert_eq!(g.cell(-1, 0).get(), 3);
+            assert_eq!(g.cell(4, 3).get(), 0);
+            assert_eq!(g.cell(-5, -4).get(), 11);
+
+            let ns: Vec<_> = g.neighborhoods(1).collect();
+            assert_eq!(ns.len(), 12);
+            assert_eq!(ns[0].cells().map(|c| c.get()).collect::<Vec<_>>(),
+                       [11, 8, 9, 3, 0, 1, 7, 4, 5]);
+
+            // larger than the grid itself
+            assert_eq!(g.neighborhoods(3).nth(5).unwrap().at(-2, 2).get(), 3);
+
+            g.unwrapped().cell(3, 2).unwrap().set(100);
+            assert_eq!(ns[0].at(-1, -1).get(), 100);
+        }
+        assert_eq!(x[11], 100);
+    }
+}
@@ -0,0 +1,153 @@
+use std::cell::Cell;
+
+fn overlaps<T>(a: &[Cell<T>], b: &[Cell<T>]) -> bool {
+    let (a, b) = (a.as_ptr_range(), b.as_ptr_range());
+    a.start < b.end && b.start < a.end
+}
+
+fn check_lengths<T>(channels: &[&[Cell<T>]], interleaved: &[Cell<T>], name: &str) -> usize {
+    let frames = channels.first().map_or(0, |c| c.len());
+    assert!(channels.iter().all(|c| c.len() == frames),
+            "alias::{}: channels have mismatched lengths", name);
+    assert_eq!(Some(interleaved.len()), frames.checked_mul(channels.len()),
+               "alias::{}: interleaved length doesn't match channels", name);
+    frames
+}
+
+/// Write the `channels` into `dst` interleaved, so that element `i`
+/// of channel `c` ends up at `dst[i * channels.len() + c]`.
+///
+/// The channels may alias `dst`, such as when converting a planar
+/// buffer to interleaved in place; in that case they are copied out
+/// first. A single channel that already *is* `dst` is left alone.
+///
+/// # Panics
+///
+/// Panics if the channels have different lengths, or `dst` isn't
+/// exactly long enough for all of them.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut audio = [1, 2, 3, 10, 20, 30];
+/// let cells = alias::slice(&mut audio);
+///
+/// // planar left/right in the same buffer, interleaved in place
+/// let (left, right) = cells.split_at(3);
+/// alias::interleave(&[left, right], cells);
+///
+/// assert_eq!(audio, [1, 10, 2, 20, 3, 30]);
+/// ```
+pub fn interleave<T: Copy>(channels: &[&[Cell<T>]], dst: &[Cell<T>]) {
+    check_lengths(channels, dst, "interleave");
+    let n = channels.len();
+    if n == 1 && channels[0].as_ptr() == dst.as_ptr() {
+        return
+    }
+    if channels.iter().any(|c| overlaps(c, dst)) {
+        let copies: Vec<Vec<T>> = channels.iter().map(|c| c.iter().map(Cell::get).collect()).collect();
+        for (c, channel) in copies.iter().enumerate() {
+            for (i, &v) in channel.iter().enumerate() {
+                dst[i * n + c].set(v);
+            }
+        }
+    } else {
+        for (c, channel) in channels.iter().enumerate() {
+            for (i, v) in channel.iter().enumerate() {
+                dst[i * n + c].set(v.get());
+            }
+        }
+    }
+}
+
+/// Split the interleaved `src` into `channels`, so that
+/// `src[i * channels.len() + c]` ends up at element `i` of channel `c`.
+///
+/// This is the inverse of `interleave`, and the same aliasing is
+/// allowed.
+///
+/// # Panics
+///
+/// Panics if the channels have different lengths, or `src` isn't
+/// exactly as long as all of them.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut stereo = [1, 10, 2, 20, 3, 30];
+/// let mut left = [0; 3];
+/// let mut right = [0; 3];
+///
+/// alias::deinterleave(alias::slice(&mut stereo),
+///                     &[alias::slice(&mut left), alias::slice(&mut right)]);
+///
+/// assert_eq!((left, right), ([1, 2, 3], [10, 20, 30]));
+/// ```
+pub fn deinterleave<T: Copy>(src: &[Cell<T>], channels: &[&[Cell<T>]]) {
+    check_lengths(channels, src, "deinterleave");
+    let n = channels.len();
+    if n == 1 && channels[0].as_ptr() == src.as_ptr() {
+        return
+    }
+    if channels.iter().any(|c| overlaps(c, src)) {
+        let copy: Vec<T> = src.iter().map(Cell::get).collect();
+        for (i, &v) in copy.iter().enumerate() {
+            channels[i % n][i / n].set(v);
+        }
+    } else {
+        for (i, v) in src.iter().enumerate() {
+            channels[i % n][i / n].set(v.get());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slice;
+
+    #[test]
+    fn smoke_interleave() {
+        let mut a = [1, 2];
+        let mut b = [3, 4];
+        let mut c = [5, 6];
+        let mut out = [0; 6];
+        {
+            let chans = [slice(&mut a), slice(&mut b), slice(&mut c)];
+            let out = slice(&mut out);
+            interleave(&chans, out);
+            assert_eq!(out.iter().map(Cell::get).collect::<Vec<_>>(), [1, 3, 5, 2, 4, 6]);
+            out[0].set(0);
+            deinterleave(out, &chans);
+            // a single channel is a no-op
+            interleave(&[out], out);
+            interleave::<u8>(&[], &[]);
+        }
+        assert_eq!((a, b, c), ([0, 2], [3, 4], [5, 6]));
+
+        // and back to planar in place
+        let mut x = [1, 10, 2, 20, 3, 30];
+        {
+            let cells = slice(&mut x);
+            let (l, r) = cells.split_at(3);
+            deinterleave(cells, &[l, r]);
+        }
+        assert_eq!(x, [1, 2, 3, 10, 20, 30]);
+    }
+
+    #[test]
+    #[should_panic(expected = "mismatched lengths")]
+    fn mismatched_channels() {
+        let mut x = [0; 3];
+        let x = slice(&mut x);
+        interleave(&[&x[..1], &x[1..]], x);
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't match channels")]
+    fn mismatched_dst() {
+        let mut x = [0; 3];
+        let x = slice(&mut x);
+        interleave(&[&x[..1], &x[1..2]], x);
+    }
+}
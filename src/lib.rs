@@ -52,9 +52,10 @@
 //! occur by default. However, it can occur via specialised types that
 //! control what mutation can happen, such as
 //! `std::cell::Cell<T>`. That type is a plain wrapper around `T` that
-//! only works with a subset of possible `T`s (`T: Copy`). These types
-//! all assume they have full control over access to their internal
-//! data: they mediate every interaction.
+//! never hands out references to its contents, only moving values in
+//! and out (`set`, `replace`, `take`, `swap`, and `get` for `T:
+//! Copy`). These types all assume they have full control over access
+//! to their internal data: they mediate every interaction.
 //!
 //! If one has unique access to some piece of data (`&mut T`), it is
 //! definitely safe to treat it as aliased (`&T`), but it is also safe
@@ -72,9 +73,206 @@
 //! optimisations possible due to the way it restricts access to its
 //! internals.)
 
+#[cfg(feature = "derive")]
+extern crate alias_derive;
+
 use std::mem;
 use std::cell::Cell;
 
+/// Derive per-field aliasing for a struct with named fields.
+///
+/// For a struct `Foo`, this generates a `FooCells<'a>` struct with
+/// the same fields, each holding a `&'a Cell` of the original
+/// field's type, and a `Foo::alias(&mut self) -> FooCells` method.
+/// Each field can then be handed out and mutated independently while
+/// aliased. Generic structs aren't supported.
+///
+/// This needs the `derive` feature.
+///
+/// # Examples
+///
+/// ```rust
+/// #[derive(alias::Alias)]
+/// struct Player {
+///     name: String,
+///     health: u32,
+///     pos: (f32, f32),
+/// }
+///
+/// let mut p = Player { name: "Ada".to_string(), health: 10, pos: (0.0, 0.0) };
+/// {
+///     let cells = p.alias();
+///     let (damage, movement) = (cells, cells);
+///     damage.health.set(damage.health.get() - 3);
+///     movement.pos.set((1.0, 2.0));
+///     cells.name.set(cells.name.take() + " (hurt)");
+/// }
+/// assert_eq!((p.name.as_str(), p.health, p.pos), ("Ada (hurt)", 7, (1.0, 2.0)));
+/// # #[derive(alias::Alias)]
+/// # pub struct Tricky {
+/// #     #[allow(dead_code)] pub(crate) f: fn(u8) -> u8,
+/// #     m: std::collections::HashMap<u8, Vec<u8>>,
+/// # }
+/// # let mut t = Tricky { f: |x| x, m: Default::default() };
+/// # t.alias().m.take();
+/// ```
+///
+/// ```rust,compile_fail
+/// #[derive(alias::Alias)]
+/// struct Wrapper<T> {
+///     inner: T,
+/// }
+/// ```
+#[cfg(feature = "derive")]
+pub use alias_derive::Alias;
+
+/// Derive column-wise views of aliased slices of a struct with named
+/// fields.
+///
+/// For a struct `Foo`, this generates a `FooColumns<'a>` struct and a
+/// `Foo::columns(&'a [Cell<Foo>]) -> FooColumns<'a>` function. The
+/// view has a method for each field, iterating over that field of
+/// every element as `&'a Cell`s, projected as by `cell_project!`.
+/// This gives struct-of-arrays style access without changing the
+/// layout. Generic structs aren't supported.
+///
+/// This needs the `derive` feature.
+///
+/// # Examples
+///
+/// ```rust
+/// #[derive(Clone, Copy, alias::AliasColumns)]
+/// struct Body {
+///     pos: f32,
+///     vel: f32,
+///     mass: f32,
+/// }
+///
+/// let mut bodies = [Body { pos: 0.0, vel: 1.0, mass: 2.0 }; 3];
+/// bodies[2].vel = -2.0;
+/// {
+///     let cols = Body::columns(alias::slice(&mut bodies));
+///     let integrate = |dt: f32| for (p, v) in cols.pos().zip(cols.vel()) {
+///         p.set(p.get() + v.get() * dt);
+///     };
+///     let momentum = || cols.vel().zip(cols.mass()).map(|(v, m)| v.get() * m.get()).sum::<f32>();
+///
+///     integrate(0.5);
+///     assert_eq!(momentum(), 0.0);
+/// }
+/// assert_eq!(bodies[2].pos, -1.0);
+/// ```
+#[cfg(feature = "derive")]
+pub use alias_derive::AliasColumns;
+
+pub use aliasable::{of, AliasExt, Aliasable};
+pub use bits::{bits, bitset, BitCells, BitReader, BitWord, BitWriter, CellBitSet};
+pub use borrow::{dyn_borrow, AliasRefCell, AliasRef, AliasRefMut, AlreadyBorrowed};
+pub use bulk::{set_from_iter, set_from_iter_exact, CellSliceExt, CellSliceMoveExt, IterCopied, IterLengthError};
+pub use bump::CellBump;
+pub use callback::CallbackCell;
+pub use columns::{columns, Rows};
+pub use counters::{Counters, SyncCounters};
+pub use cursor::{CellCursor, LeBytes};
+pub use dirty::{DirtyRectGrid, Rect};
+pub use each::{each, Each};
+pub use events::{EventQueue, EventDrain};
+pub use ext::{CellExt, CellFloatExt, CellNumExt, CellOptionExt};
+pub use float::{float_bits, FloatBits};
+pub use freelist::CellFreeList;
+pub use fsm::{FsmCell, InvalidTransition, Transitions};
+pub use grid::{CellGrid, WrappingGrid, Neighborhood, Neighborhoods};
+pub use interleave::{interleave, deinterleave};
+pub use map::{map_values, btree_values, CellMapView, CellMapIter, CellBTreeView, CellBTreeIter};
+pub use memo::MemoCell;
+#[cfg(unix)]
+pub use os::os_bytes;
+pub use once::{once, AliasOnce};
+pub use owned::{boxed, rc, arc};
+pub use region::{RegionLocker, RegionGuard, SharedRegion, Busy};
+pub use paint::WorklistFull;
+pub use ring::CellRing;
+pub use rng::CellRng;
+pub use scratch::scratch;
+pub use slotmap::{CellSlotMap, SlotKey};
+pub use stack::CellStack;
+pub use statics::StaticCell;
+pub use text::{cell_str, str_ascii, AsciiCell, CellStr, CellString};
+#[cfg(feature = "trace")]
+pub use trace::{traced, Access, TracedCell};
+pub use tuple::{tuple, slices, Slices, Tuple};
+pub use uninit::{uninit_slice, UninitTracker};
+pub use unique::{unique, UniqueSlice, DisjointError};
+pub use varint::{read_varint, write_varint, varint_len, VarintError};
+pub use vec::{vec_parts, vec_append, deque, CellDeque, CellVec};
+pub use view::{slice_view, debug, AliasedSlice};
+pub use volatile::{volatile, volatile_slice, VolatileCell};
+pub use whole::{slice_cell, unsized_one, split_at, as_cells, from_cells};
+pub use windows::{array_windows, as_array_chunks, as_chunk_cells, ArrayWindows};
+pub use zip::{zip, Zip};
+
+mod aliasable;
+pub mod atomic;
+mod bits;
+pub mod blas;
+mod borrow;
+pub mod branded;
+mod bulk;
+mod bump;
+pub mod bytes;
+mod callback;
+pub mod checksum;
+#[cfg(feature = "codec")]
+pub mod codec;
+mod columns;
+mod counters;
+pub mod ct;
+mod cursor;
+mod dirty;
+mod each;
+mod events;
+mod ext;
+mod float;
+mod freelist;
+mod fsm;
+mod grid;
+mod interleave;
+mod map;
+mod memo;
+mod once;
+mod owned;
+#[cfg(unix)]
+mod os;
+mod paint;
+pub mod permute;
+pub mod project;
+pub mod racy;
+mod region;
+mod ring;
+mod rng;
+mod scratch;
+#[cfg(feature = "zeroize")]
+pub mod secret;
+mod slotmap;
+pub mod sort;
+mod stack;
+mod statics;
+pub mod stats;
+mod text;
+#[cfg(feature = "trace")]
+mod trace;
+mod tuple;
+pub mod unalias;
+mod uninit;
+mod unique;
+mod varint;
+mod vec;
+mod view;
+mod volatile;
+mod whole;
+mod windows;
+mod zip;
+
 /// Allow the mutable reference `data` to be mutated while aliased.
 ///
 /// # Examples
@@ -90,7 +288,19 @@ use std::cell::Cell;
 /// y.set(y.get() + 2);
 /// assert_eq!(z.get(), 12);
 /// ```
-pub fn one<'a, T: Copy>(data: &'a mut T) -> &'a Cell<T> {
+///
+/// This is a `const fn`, so can also be used in constant
+/// initialisers:
+///
+/// ```rust
+/// const NEXT: u32 = {
+///     let mut counter = 41;
+///     let c = alias::one(&mut counter);
+///     c.replace(c.get() + 1)
+/// };
+/// assert_eq!(NEXT, 41);
+/// ```
+pub const fn one<T>(data: &mut T) -> &Cell<T> {
     unsafe { mem::transmute(data) }
 }
 
@@ -116,10 +326,243 @@ pub fn one<'a, T: Copy>(data: &'a mut T) -> &'a Cell<T> {
 /// assert_eq!(z[2].get(), 12);
 /// assert_eq!(z[3].get(), 13);
 /// ```
-pub fn slice<'a, T: Copy>(data: &'a mut [T]) -> &'a [Cell<T>] {
+///
+/// The result is an ordinary slice, so the `[T]` methods that take
+/// `&self`, like `windows`, `chunks`, `split_at` and `iter`, all work
+/// on it, giving sub-slices of cells that still alias the original:
+///
+/// ```rust
+/// // a 3-tap FIR filter, in place
+/// let mut samples = [0.0, 3.0, 6.0, 3.0, 0.0, 0.0];
+/// let taps = [0.25, 0.5, 0.25];
+///
+/// let cells = alias::slice(&mut samples);
+/// for w in cells.windows(3).rev() {
+///     let y = w.iter().zip(&taps).map(|(x, t)| x.get() * t).sum();
+///     w[2].set(y);
+/// }
+/// for chunk in cells.chunks(4) {
+///     chunk[0].set(-1.0);
+/// }
+///
+/// assert_eq!(samples, [-1.0, 3.0, 3.0, 4.5, -1.0, 0.75]);
+/// ```
+pub const fn slice<T>(data: &mut [T]) -> &[Cell<T>] {
     unsafe { mem::transmute(data) }
 }
 
+/// Allow the contents of the mutable array `data` to be mutated while
+/// aliased, keeping its length in the type.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut x = [1, 2, 3];
+///
+/// {
+///     let [a, b, c] = alias::array(&mut x);
+///     a.swap(c);
+///     b.set(a.get() + c.get());
+/// }
+///
+/// assert_eq!(x, [3, 4, 1]);
+/// ```
+pub const fn array<const N: usize, T>(data: &mut [T; N]) -> &[Cell<T>; N] {
+    unsafe { &*(data as *mut [T; N] as *const [Cell<T>; N]) }
+}
+
+/// Allow the contents of the rows `data` to be mutated while aliased,
+/// as one flat slice.
+///
+/// This also applies to nested arrays like `[[T; N]; M]`, which
+/// coerce to `[[T; N]]`.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut grid = [[0; 3]; 2];
+///
+/// {
+///     let flat = alias::flatten(&mut grid);
+///     // index across the row boundary
+///     for w in flat.windows(2) {
+///         w[1].set(w[0].get() + 1);
+///     }
+/// }
+///
+/// assert_eq!(grid, [[0, 1, 2], [3, 4, 5]]);
+/// ```
+pub const fn flatten<const N: usize, T>(data: &mut [[T; N]]) -> &[Cell<T>] {
+    slice(data.as_flattened_mut())
+}
+
+/// Allow the value pointed to by `ptr` to be mutated while aliased.
+///
+/// This is `alias::one` for data that is only available as a raw
+/// pointer, such as a buffer passed in from C.
+///
+/// # Safety
+///
+/// For all of `'a`:
+///
+/// - `ptr` must be non-null, properly aligned, and point to an
+///   initialised `T`,
+/// - the `T` must not be read or written other than through the
+///   returned reference (or references derived from it), and
+/// - the `T` must not be deallocated.
+///
+/// # Examples
+///
+/// ```rust
+/// extern "C" fn on_event(count: *mut u32) {
+///     let c = unsafe { alias::from_raw(count) };
+///     let bump = || c.set(c.get() + 1);
+///     bump();
+///     bump();
+/// }
+///
+/// let mut n = 1;
+/// on_event(&mut n);
+/// assert_eq!(n, 3);
+/// ```
+pub const unsafe fn from_raw<'a, T>(ptr: *mut T) -> &'a Cell<T> {
+    &*(ptr as *const Cell<T>)
+}
+
+/// Allow the `len` elements starting at `ptr` to be mutated while
+/// aliased.
+///
+/// This is `alias::slice` for data that is only available as a raw
+/// pointer and a length, such as a buffer passed in from C.
+///
+/// # Safety
+///
+/// For all of `'a`:
+///
+/// - `ptr` must be non-null and properly aligned, even if `len` is
+///   zero,
+/// - `ptr` must point to `len` consecutive initialised values of type
+///   `T` within a single allocation, with the total size at most
+///   `isize::MAX` bytes,
+/// - the elements must not be read or written other than through the
+///   returned slice (or references derived from it), and
+/// - the elements must not be deallocated.
+///
+/// # Examples
+///
+/// ```rust
+/// extern "C" fn fill(buf: *mut i32, len: usize) {
+///     let cells = unsafe { alias::from_raw_parts(buf, len) };
+///     let (evens, odds) = (cells, cells);
+///     for c in evens.iter().step_by(2) { c.set(2); }
+///     for c in odds.iter().skip(1).step_by(2) { c.set(-1); }
+/// }
+///
+/// let mut v = [0; 5];
+/// fill(v.as_mut_ptr(), v.len());
+/// assert_eq!(v, [2, -1, 2, -1, 2]);
+/// ```
+pub const unsafe fn from_raw_parts<'a, T>(ptr: *mut T, len: usize) -> &'a [Cell<T>] {
+    std::slice::from_raw_parts(ptr as *const Cell<T>, len)
+}
+
+/// Allow `data` to be mutated while aliased within the closure `f`
+/// only.
+///
+/// This is `f(alias::one(data))`, but the aliased view can't escape
+/// `f`, so it is clear where it ends and `data` is usable as normal
+/// as soon as this returns.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut total = 0;
+///
+/// let count = alias::scope(&mut total, |t| {
+///     let add = |n| t.set(t.get() + n);
+///     let double = || t.set(t.get() * 2);
+///     add(3);
+///     double();
+///     add(1);
+///     3
+/// });
+///
+/// total += 1;
+/// assert_eq!((count, total), (3, 8));
+/// ```
+///
+/// The cell cannot be returned from the closure:
+///
+/// ```rust,compile_fail
+/// let mut x = 0;
+/// let c = alias::scope(&mut x, |c| c);
+/// ```
+pub fn scope<T, R, F>(data: &mut T, f: F) -> R
+    where F: FnOnce(&Cell<T>) -> R
+{
+    f(one(data))
+}
+
+/// Allow the contents of `data` to be mutated while aliased within
+/// the closure `f` only.
+///
+/// This is the slice version of `scope`.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut x = [1, 2, 3, 4];
+///
+/// alias::scope_slice(&mut x, |cells| {
+///     for w in cells.windows(2) {
+///         w[1].set(w[0].get() + w[1].get());
+///     }
+/// });
+///
+/// x.reverse();
+/// assert_eq!(x, [10, 6, 3, 1]);
+/// ```
+pub fn scope_slice<T, R, F>(data: &mut [T], f: F) -> R
+    where F: FnOnce(&[Cell<T>]) -> R
+{
+    f(slice(data))
+}
+
+/// Alias `data` once and bind the resulting `&Cell` under each of the
+/// given names.
+///
+/// `share!(x => a, b, c)` is `let a = alias::one(&mut x);` followed
+/// by `let b = a; let c = a;`, which is handy when each name is about
+/// to be captured by a different closure.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[macro_use] extern crate alias;
+/// # fn main() {
+/// let mut clicks = 0;
+/// {
+///     share!(clicks => on_click, on_double_click, on_reset);
+///     let handlers: Vec<Box<dyn Fn()>> = vec![
+///         Box::new(move || on_click.set(on_click.get() + 1)),
+///         Box::new(move || on_double_click.set(on_double_click.get() + 2)),
+///         Box::new(move || on_reset.set(0)),
+///     ];
+///     handlers[2]();
+///     handlers[0]();
+///     handlers[1]();
+/// }
+/// assert_eq!(clicks, 3);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! share {
+    ($data: expr => $($name: ident),+ $(,)*) => {
+        let cell: &::std::cell::Cell<_> = $crate::one(&mut $data);
+        $(let $name = cell;)+
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -197,4 +640,106 @@ mod tests {
         }
         assert_eq!(x, [Some(val), None, Some(val2)]);
     }
+    #[test]
+    fn smoke_array() {
+        let a = 1;
+        let val = X { x: 0xAA, y: !0, z: 0x77, w: &a };
+
+        let mut x = [None, Some(val)];
+
+        {
+            let y = array(&mut x);
+            let z: &[Cell<_>; 2] = y;
+            y[0].set(z[1].get());
+            z[1].set(None);
+            assert_eq!(y[0].get(), Some(val));
+            assert_eq!(array::<0, u8>(&mut []).len(), 0);
+        }
+        assert_eq!(x, [Some(val), None]);
+    }
+    #[test]
+    fn non_copy() {
+        let mut x = String::from("a");
+        let mut v = vec![String::from("b"), String::new()];
+        let mut arr = [vec![1], vec![2, 3]];
+        {
+            let y = one(&mut x);
+            let z = y;
+            let old = y.replace(String::from("c"));
+            assert_eq!(old, "a");
+            assert_eq!(z.take(), "c");
+
+            let s = slice(&mut v);
+            s[1].set(s[0].take() + "!");
+            s[0].swap(y);
+
+            let a = array(&mut arr);
+            a[0].swap(&a[1]);
+            let mut inner = a[1].take();
+            inner.push(4);
+            a[1].set(inner);
+        }
+        assert_eq!(x, "");
+        assert_eq!(v, ["", "b!"]);
+        assert_eq!(arr, [vec![2, 3], vec![1, 4]]);
+    }
+    #[test]
+    fn smoke_flatten() {
+        let mut x = vec![[1, 2], [3, 4], [5, 6]];
+        {
+            let f = flatten(&mut x);
+            assert_eq!(f.len(), 6);
+            f[1].swap(&f[4]);
+            assert!(flatten::<0, u8>(&mut [[], []]).is_empty());
+        }
+        assert_eq!(x, [[1, 5], [3, 4], [2, 6]]);
+    }
+    #[test]
+    fn smoke_raw() {
+        let mut v = vec![String::from("a"), String::new()];
+        unsafe {
+            let s = from_raw_parts(v.as_mut_ptr(), 2);
+            s[0].swap(from_raw(v.as_mut_ptr().add(1)));
+            assert!(from_raw_parts::<u8>(std::ptr::NonNull::dangling().as_ptr(), 0).is_empty());
+        }
+        assert_eq!(v, ["", "a"]);
+    }
+    #[test]
+    fn smoke_share() {
+        let mut x = vec![1];
+        let cell = 5;
+        {
+            share!(x => a, b,);
+            a.set(b.take().into_iter().chain(Some(cell)).collect());
+        }
+        assert_eq!(x, [1, 5]);
+    }
+    #[test]
+    fn const_conversions() {
+        const FIRST: [u8; 3] = {
+            let (mut x, mut y, mut z) = ([[1, 2], [3, 4]], [5, 6], [7]);
+            let f = flatten(&mut x);
+            let s = slice(&mut y);
+            let a = array(&mut z);
+            [f[3].replace(0) + f[3].get(), s[1].get(), a[0].get()]
+        };
+        assert_eq!(FIRST, [4, 6, 7]);
+    }
+    #[test]
+    fn smoke_scope() {
+        let mut x = String::from("a");
+        let len = scope(&mut x, |c| {
+            let s = c.take() + "b";
+            let len = s.len();
+            c.set(s);
+            len
+        });
+        x.push('c');
+        assert_eq!((len, &*x), (2, "abc"));
+
+        let mut v = [1, 2, 3];
+        scope_slice(&mut v, |s| s[0].swap(&s[2]));
+        v[1] = 0;
+        assert_eq!(v, [3, 0, 1]);
+    }
 }
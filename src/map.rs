@@ -0,0 +1,301 @@
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap};
+use std::collections::{btree_map, hash_map};
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+
+use one;
+
+/// An aliased view of the values of a `HashMap`, allowing repeated
+/// keyed lookups.
+///
+/// The keys can't be changed (so the map's structure is fixed while
+/// the view exists), but every value can be read and written through
+/// shared references.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use alias::CellMapView;
+///
+/// let mut scores = HashMap::new();
+/// scores.insert("alice", 10);
+/// scores.insert("bob", 20);
+///
+/// {
+///     let view = CellMapView::new(&mut scores);
+///     let alice = view.get(&"alice").unwrap();
+///     let bob = view.get(&"bob").unwrap();
+///
+///     bob.set(bob.get() + alice.get());
+///     view.get(&"alice").unwrap().set(0);
+///     assert!(view.get(&"carol").is_none());
+/// }
+///
+/// assert_eq!(scores["alice"], 0);
+/// assert_eq!(scores["bob"], 30);
+/// ```
+pub struct CellMapView<'a, K: 'a, V: 'a> {
+    index: HashMap<&'a K, &'a Cell<V>>,
+}
+
+/// Allow the values of `map` to be mutated while aliased, looked up
+/// by key.
+///
+/// This is `CellMapView::new`.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::collections::HashMap;
+///
+/// let mut names: HashMap<u32, String> = HashMap::new();
+/// names.insert(1, "ada".to_string());
+/// names.insert(2, "bob".to_string());
+///
+/// {
+///     let view = alias::map_values(&mut names);
+///     let shout = |id| view.get(&id).map(|c| c.set(c.take().to_uppercase()));
+///     let swap = |a, b| view.get(&a).unwrap().swap(view.get(&b).unwrap());
+///     shout(1);
+///     swap(1, 2);
+/// }
+///
+/// assert_eq!((&*names[&1], &*names[&2]), ("bob", "ADA"));
+/// ```
+pub fn map_values<K: Hash + Eq, V, S: BuildHasher>(map: &mut HashMap<K, V, S>)
+                                                   -> CellMapView<'_, K, V> {
+    CellMapView::new(map)
+}
+
+/// Allow the values of `map` to be mutated while aliased, looked up
+/// by key.
+///
+/// This is `CellBTreeView::new`.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+///
+/// let mut stock = BTreeMap::from([("apple", 3), ("pear", 0), ("plum", 5)]);
+///
+/// {
+///     let view = alias::btree_values(&mut stock);
+///     let sell = |k| view.get(&k).unwrap().set(view.get(&k).unwrap().get() - 1);
+///     sell("apple");
+///     sell("plum");
+///     let empty: Vec<_> = view.iter().filter(|(_, c)| c.get() == 0).map(|(k, _)| *k).collect();
+///     assert_eq!(empty, ["pear"]);
+/// }
+///
+/// assert_eq!(stock["apple"], 2);
+/// ```
+pub fn btree_values<K: Ord, V>(map: &mut BTreeMap<K, V>) -> CellBTreeView<'_, K, V> {
+    CellBTreeView::new(map)
+}
+
+impl<'a, K: Hash + Eq, V> CellMapView<'a, K, V> {
+    /// Allow the values of `map` to be mutated while aliased.
+    ///
+    /// This indexes every value up front, taking time and memory
+    /// proportional to the size of the map.
+    pub fn new<S: BuildHasher>(map: &'a mut HashMap<K, V, S>) -> CellMapView<'a, K, V> {
+        CellMapView { index: map.iter_mut().map(|(k, v)| (k, one(v))).collect() }
+    }
+
+    /// The value corresponding to `key`, if there is one.
+    pub fn get(&self, key: &K) -> Option<&'a Cell<V>> {
+        self.index.get(key).cloned()
+    }
+
+    /// Whether there is a value for `key`.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.index.contains_key(key)
+    }
+
+    /// The number of entries.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Whether the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Iterate over every key and value cell, in arbitrary order.
+    pub fn iter(&self) -> CellMapIter<'_, 'a, K, V> {
+        CellMapIter { inner: self.index.iter() }
+    }
+}
+
+impl<'a, K: fmt::Debug, V: Copy + fmt::Debug> fmt::Debug for CellMapView<'a, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.index.iter().map(|(k, v)| (k, v.get()))).finish()
+    }
+}
+
+/// An iterator over the entries of a `CellMapView`.
+///
+/// Created by `CellMapView::iter`.
+pub struct CellMapIter<'v, 'a: 'v, K: 'a, V: 'a> {
+    inner: hash_map::Iter<'v, &'a K, &'a Cell<V>>,
+}
+
+impl<'v, 'a, K, V> Iterator for CellMapIter<'v, 'a, K, V> {
+    type Item = (&'a K, &'a Cell<V>);
+
+    fn next(&mut self) -> Option<(&'a K, &'a Cell<V>)> {
+        self.inner.next().map(|(&k, &v)| (k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'v, 'a, K, V> ExactSizeIterator for CellMapIter<'v, 'a, K, V> {}
+
+impl<'v, 'a, K: fmt::Debug, V: Copy + fmt::Debug> fmt::Debug for CellMapIter<'v, 'a, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.inner.clone().map(|(k, v)| (k, v.get()))).finish()
+    }
+}
+
+/// An aliased view of the values of a `BTreeMap`, allowing repeated
+/// keyed lookups.
+///
+/// This is the `BTreeMap` version of `CellMapView`, and iterates in
+/// key order.
+pub struct CellBTreeView<'a, K: 'a, V: 'a> {
+    index: BTreeMap<&'a K, &'a Cell<V>>,
+}
+
+impl<'a, K: Ord, V> CellBTreeView<'a, K, V> {
+    /// Allow the values of `map` to be mutated while aliased.
+    ///
+    /// This indexes every value up front, taking time and memory
+    /// proportional to the size of the map.
+    pub fn new(map: &'a mut BTreeMap<K, V>) -> CellBTreeView<'a, K, V> {
+        CellBTreeView { index: map.iter_mut().map(|(k, v)| (k, one(v))).collect() }
+    }
+
+    /// The value corresponding to `key`, if there is one.
+    pub fn get(&self, key: &K) -> Option<&'a Cell<V>> {
+        self.index.get(key).cloned()
+    }
+
+    /// Whether there is a value for `key`.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.index.contains_key(key)
+    }
+
+    /// The number of entries.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Whether the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Iterate over every key and value cell, in key order.
+    pub fn iter(&self) -> CellBTreeIter<'_, 'a, K, V> {
+        CellBTreeIter { inner: self.index.iter() }
+    }
+}
+
+impl<'a, K: fmt::Debug, V: Copy + fmt::Debug> fmt::Debug for CellBTreeView<'a, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.index.iter().map(|(k, v)| (k, v.get()))).finish()
+    }
+}
+
+/// An iterator over the entries of a `CellBTreeView`.
+///
+/// Created by `CellBTreeView::iter`.
+pub struct CellBTreeIter<'v, 'a: 'v, K: 'a, V: 'a> {
+    inner: btree_map::Iter<'v, &'a K, &'a Cell<V>>,
+}
+
+impl<'v, 'a, K, V> Iterator for CellBTreeIter<'v, 'a, K, V> {
+    type Item = (&'a K, &'a Cell<V>);
+
+    fn next(&mut self) -> Option<(&'a K, &'a Cell<V>)> {
+        self.inner.next().map(|(&k, &v)| (k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'v, 'a, K, V> DoubleEndedIterator for CellBTreeIter<'v, 'a, K, V> {
+    fn next_back(&mut self) -> Option<(&'a K, &'a Cell<V>)> {
+        self.inner.next_back().map(|(&k, &v)| (k, v))
+    }
+}
+
+impl<'v, 'a, K, V> ExactSizeIterator for CellBTreeIter<'v, 'a, K, V> {}
+
+impl<'v, 'a, K: fmt::Debug, V: Copy + fmt::Debug> fmt::Debug for CellBTreeIter<'v, 'a, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.inner.clone().map(|(k, v)| (k, v.get()))).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_map_view() {
+        let mut m: HashMap<String, u32> = (0..10).map(|i| (i.to_string(), i)).collect();
+        {
+            let v = CellMapView::new(&mut m);
+            assert_eq!(v.len(), 10);
+            let a = v.get(&"3".to_string()).unwrap();
+            let b = v.get(&"3".to_string()).unwrap();
+            a.set(b.get() * 100);
+            assert!(v.contains_key(&"9".to_string()));
+            assert!(!v.contains_key(&"10".to_string()));
+
+            for (k, c) in v.iter() {
+                if k.as_str() < "2" {
+                    c.set(c.get() + 1);
+                }
+            }
+            assert_eq!(v.iter().len(), 10);
+        }
+        assert_eq!(m["0"], 1);
+        assert_eq!(m["1"], 2);
+        assert_eq!(m["3"], 300);
+        assert_eq!(m["9"], 9);
+
+        let mut one_entry = HashMap::new();
+        one_entry.insert(1, 'a');
+        assert_eq!(format!("{:?}", CellMapView::new(&mut one_entry)), "{1: 'a'}");
+    }
+
+    #[test]
+    fn smoke_btree_view() {
+        let mut m: BTreeMap<u8, String> = (0..4).map(|i| (i, i.to_string())).collect();
+        {
+            let v = btree_values(&mut m);
+            assert_eq!((v.len(), v.contains_key(&4)), (4, false));
+            let mut it = v.iter();
+            let (_, last) = it.next_back().unwrap();
+            let (_, first) = it.next().unwrap();
+            first.swap(last);
+            v.get(&1).unwrap().set("x".to_string());
+            assert_eq!(it.len(), 2);
+        }
+        assert_eq!(m.into_values().collect::<String>(), "3x20");
+
+        let mut one_entry = BTreeMap::from([(1, 'a')]);
+        assert_eq!(format!("{:?}", CellBTreeView::new(&mut one_entry)), "{1: 'a'}");
+    }
+}
@@ -0,0 +1,114 @@
+use std::cell::Cell;
+use std::fmt;
+
+/// A cached value derived from aliased inputs, which is recomputed on
+/// demand after being invalidated.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::MemoCell;
+///
+/// let mut widths = [10, 20, 30];
+/// let widths = alias::slice(&mut widths);
+///
+/// let total = MemoCell::new();
+/// let compute = || widths.iter().map(|w| w.get()).sum::<i32>();
+///
+/// assert_eq!(total.get_or_compute(compute), 60);
+///
+/// widths[0].set(40);
+/// // still cached
+/// assert_eq!(total.get_or_compute(compute), 60);
+///
+/// total.invalidate();
+/// assert_eq!(total.get_or_compute(compute), 90);
+/// ```
+pub struct MemoCell<T> {
+    value: Cell<Option<T>>,
+    generation: Cell<u64>,
+}
+
+impl<T: Copy> MemoCell<T> {
+    /// Create an empty (dirty) cache.
+    pub fn new() -> MemoCell<T> {
+        MemoCell { value: Cell::new(None), generation: Cell::new(0) }
+    }
+
+    /// Return the cached value, or compute, cache and return it with
+    /// `f` if there isn't one.
+    ///
+    /// If `f` itself invalidates this cache, the value it computes is
+    /// returned but not cached, since it may have been computed from
+    /// stale inputs.
+    pub fn get_or_compute<F: FnOnce() -> T>(&self, f: F) -> T {
+        if let Some(v) = self.value.get() {
+            return v
+        }
+        let gen = self.generation.get();
+        let v = f();
+        if self.generation.get() == gen {
+            self.value.set(Some(v));
+        }
+        v
+    }
+
+    /// The cached value, if there is one.
+    pub fn get(&self) -> Option<T> {
+        self.value.get()
+    }
+
+    /// Whether the value needs to be recomputed.
+    pub fn is_dirty(&self) -> bool {
+        self.value.get().is_none()
+    }
+
+    /// Discard the cached value, so that the next `get_or_compute`
+    /// recomputes it.
+    pub fn invalidate(&self) {
+        self.generation.set(self.generation.get().wrapping_add(1));
+        self.value.set(None)
+    }
+}
+
+impl<T: Copy> Default for MemoCell<T> {
+    fn default() -> MemoCell<T> {
+        MemoCell::new()
+    }
+}
+
+impl<T: Copy + fmt::Debug> fmt::Debug for MemoCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MemoCell")
+            .field("value", &self.value.get())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_memo() {
+        let calls = Cell::new(0);
+        let m = MemoCell::new();
+        assert!(m.is_dirty());
+        let f = || { calls.set(calls.get() + 1); calls.get() * 10 };
+
+        assert_eq!(m.get_or_compute(f), 10);
+        assert_eq!(m.get_or_compute(f), 10);
+        assert_eq!(m.get(), Some(10));
+        assert_eq!(format!("{:?}", m), "MemoCell { value: Some(10) }");
+
+        m.invalidate();
+        assert!(m.is_dirty());
+        assert_eq!(m.get_or_compute(f), 20);
+
+        // invalidated while computing: not cached
+        m.invalidate();
+        assert_eq!(m.get_or_compute(|| { m.invalidate(); 5 }), 5);
+        assert!(m.is_dirty());
+        assert_eq!(calls.get(), 2);
+    }
+}
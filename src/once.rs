@@ -0,0 +1,113 @@
+use std::cell::UnsafeCell;
+use std::fmt;
+
+/// A lazily-initialised value that many aliases can read, and which
+/// is set at most once.
+///
+/// This is like `std::cell::OnceCell`, but over an existing
+/// `Option<T>`: once the value is set it is never changed or moved
+/// while aliased, so plain `&T` references to it can be handed out.
+///
+/// Created by `alias::once`.
+#[repr(transparent)]
+pub struct AliasOnce<T> {
+    value: UnsafeCell<Option<T>>,
+}
+
+/// Allow `data` to be initialised (if it is `None`) while aliased,
+/// and then read through plain references.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut table: Option<Vec<u32>> = None;
+/// let mut builds = 0;
+/// {
+///     let t = alias::once(&mut table);
+///     let mut lookup = |i: usize| t.get_or_init(|| {
+///         builds += 1;
+///         (0..8).map(|x| x * x).collect()
+///     })[i];
+///
+///     assert_eq!(lookup(3), 9);
+///     assert_eq!(lookup(7), 49);
+///     assert_eq!(t.get().map(Vec::len), Some(8));
+/// }
+/// assert_eq!((builds, table.unwrap()[2]), (1, 4));
+/// ```
+pub fn once<T>(data: &mut Option<T>) -> &AliasOnce<T> {
+    // `UnsafeCell<X>` has the same layout as `X`.
+    unsafe { &*(data as *mut Option<T> as *const AliasOnce<T>) }
+}
+
+impl<T> AliasOnce<T> {
+    /// The value, if it has been set.
+    pub fn get(&self) -> Option<&T> {
+        // once `Some`, the value is never written again while `self`
+        // is borrowed.
+        unsafe { (*self.value.get()).as_ref() }
+    }
+
+    /// Set the value, if it isn't already, or return `value`.
+    pub fn set(&self, value: T) -> Result<(), T> {
+        if self.get().is_some() {
+            return Err(value)
+        }
+        // it's `None`, so there are no references into it.
+        unsafe { *self.value.get() = Some(value) }
+        Ok(())
+    }
+
+    /// The value, first setting it to `f()` if it isn't already.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `f` sets the value itself (through another
+    /// reference), as that would leave two candidates.
+    pub fn get_or_init<F: FnOnce() -> T>(&self, f: F) -> &T {
+        if let Some(v) = self.get() {
+            return v
+        }
+        if self.set(f()).is_err() {
+            panic!("AliasOnce::get_or_init: reentrant initialisation")
+        }
+        self.get().unwrap()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for AliasOnce<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("AliasOnce").field(&self.get()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_once() {
+        let mut x = None;
+        {
+            let a = once(&mut x);
+            let b = a;
+            assert!(a.get().is_none());
+            let r = b.get_or_init(|| String::from("a"));
+            assert_eq!(a.set(String::from("b")), Err(String::from("b")));
+            assert_eq!(a.get_or_init(|| unreachable!()), "a");
+            assert_eq!((&**r, &*format!("{:?}", a)), ("a", r#"AliasOnce(Some("a"))"#));
+        }
+        assert_eq!(x.as_deref(), Some("a"));
+
+        let mut y = Some(1);
+        assert_eq!(once(&mut y).set(2), Err(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "reentrant")]
+    fn reentrant_init() {
+        let mut x = None;
+        let a = once(&mut x);
+        a.get_or_init(|| { a.set(1).unwrap(); 2 });
+    }
+}
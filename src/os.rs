@@ -0,0 +1,64 @@
+use std::cell::Cell;
+use std::ffi::OsStr;
+use std::slice::from_raw_parts_mut;
+
+use slice;
+
+/// Allow the bytes of the OS string `data` to be mutated while
+/// aliased.
+///
+/// This is only available on Unix, where OS strings are arbitrary
+/// sequences of bytes (with no interior NULs when passed to the OS,
+/// which remains the caller's responsibility), and so any
+/// modification of those bytes leaves a valid `OsStr`. Elsewhere,
+/// the in-memory encoding of an `OsStr` has internal invariants (for
+/// example WTF-8 on Windows) that arbitrary byte writes could break.
+///
+/// `&mut OsString` coerces to `&mut OsStr`, so this can be called
+/// directly on an `OsString`.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::ffi::OsString;
+///
+/// let mut path = OsString::from("/home/USER/.config");
+///
+/// {
+///     let b = alias::os_bytes(&mut path);
+///     for (c, &r) in b[6..10].iter().zip(b"huon") {
+///         c.set(r);
+///     }
+/// }
+///
+/// assert_eq!(path, "/home/huon/.config");
+/// ```
+pub fn os_bytes(data: &mut OsStr) -> &[Cell<u8>] {
+    let len = data.len();
+    // on Unix, an `OsStr` is stored exactly as its bytes, i.e. what
+    // `as_encoded_bytes` returns. This is the `&mut` equivalent of
+    // that, the same as `OsString::as_mut_vec` would be.
+    let ptr = data as *mut OsStr as *mut u8;
+    slice(unsafe { from_raw_parts_mut(ptr, len) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+
+    #[test]
+    fn smoke_os_bytes() {
+        let mut x = OsString::from_vec(vec![0xFF, b'a', 0x80, b'b']);
+        assert_eq!(x.as_encoded_bytes().as_ptr(), &*x as *const OsStr as *const u8);
+        {
+            let y = os_bytes(&mut x);
+            let z = y;
+            assert_eq!(y.len(), 4);
+            y[0].set(z[1].get());
+            z[3].set(0xFE);
+        }
+        assert_eq!(x.into_vec(), [b'a', b'a', 0x80, 0xFE]);
+    }
+}
@@ -0,0 +1,108 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Allow the contents of the box `data` to be mutated while aliased.
+///
+/// A `Box` uniquely owns its contents, so this always succeeds. Like
+/// `alias::unsized_one`, `T` may be unsized, e.g. a boxed slice to
+/// view with `alias::as_cells`.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut scores: Box<[u32]> = vec![0; 3].into_boxed_slice();
+/// {
+///     let s = alias::as_cells(alias::boxed(&mut scores));
+///     let (add, reset) = (|i: usize| s[i].set(s[i].get() + 10), |i: usize| s[i].set(0));
+///     add(0);
+///     add(2);
+///     reset(0);
+/// }
+/// assert_eq!(*scores, [0, 0, 10]);
+/// ```
+pub fn boxed<T: ?Sized>(data: &mut Box<T>) -> &Cell<T> {
+    Cell::from_mut(&mut **data)
+}
+
+/// Allow the contents of `data` to be mutated while aliased, if it is
+/// the only `Rc` pointing to them.
+///
+/// This returns `None` if there are other `Rc` or `Weak` pointers to
+/// the same allocation, since they could otherwise observe the
+/// mutations, as for `Rc::get_mut`.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::rc::Rc;
+///
+/// let mut config = Rc::new((8, false));
+/// {
+///     let c = alias::rc(&mut config).unwrap();
+///     let enable = || c.set((c.get().0, true));
+///     enable();
+/// }
+/// assert_eq!(*config, (8, true));
+///
+/// let other = config.clone();
+/// assert!(alias::rc(&mut config).is_none());
+/// # drop(other);
+/// ```
+pub fn rc<T: ?Sized>(data: &mut Rc<T>) -> Option<&Cell<T>> {
+    Rc::get_mut(data).map(Cell::from_mut)
+}
+
+/// Allow the contents of `data` to be mutated while aliased, if it is
+/// the only `Arc` pointing to them.
+///
+/// This returns `None` if there are other `Arc` or `Weak` pointers to
+/// the same allocation, as for `Arc::get_mut`. The result is an
+/// ordinary `&Cell`, so it can't be shared with other threads.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::sync::Arc;
+///
+/// let mut totals = Arc::new(vec![1, 2, 3]);
+/// {
+///     let t = alias::arc(&mut totals).unwrap();
+///     let push = |x| {
+///         let mut v = t.take();
+///         v.push(x);
+///         t.set(v);
+///     };
+///     push(4);
+/// }
+/// assert_eq!(*totals, [1, 2, 3, 4]);
+/// ```
+pub fn arc<T: ?Sized>(data: &mut Arc<T>) -> Option<&Cell<T>> {
+    Arc::get_mut(data).map(Cell::from_mut)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_owned() {
+        let mut b: Box<[u8]> = vec![1, 2].into_boxed_slice();
+        ::as_cells(boxed(&mut b))[0].set(3);
+        assert_eq!(*b, [3, 2]);
+
+        let mut r = Rc::new(1);
+        let w = Rc::downgrade(&r);
+        assert!(rc(&mut r).is_none());
+        drop(w);
+        rc(&mut r).unwrap().set(2);
+        assert_eq!(*r, 2);
+
+        let mut a = Arc::new(1);
+        let a2 = a.clone();
+        assert!(arc(&mut a).is_none());
+        drop(a2);
+        arc(&mut a).unwrap().set(5);
+        assert_eq!(*a, 5);
+    }
+}
@@ -0,0 +1,431 @@
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+use std::ptr;
+
+use grid::CellGrid;
+
+/// The error returned when a fill runs out of worklist space.
+///
+/// The region will have been partially filled.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct WorklistFull;
+
+impl fmt::Display for WorklistFull {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("flood fill worklist is full")
+    }
+}
+
+impl Error for WorklistFull {}
+
+impl<'a, T: Copy> CellGrid<'a, T> {
+    /// Replace the 4-connected region of elements equal to the one at
+    /// `start` with `value`, returning how many elements changed.
+    ///
+    /// This doesn't allocate: `worklist` holds the pending spans. A
+    /// worklist as long as the grid is wide and high is enough for all
+    /// but very convoluted regions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let mut map = [0, 0, 1, 0,
+    ///                1, 0, 1, 0,
+    ///                0, 1, 1, 0];
+    ///
+    /// let g = alias::CellGrid::new(&mut map, 4, 3);
+    /// let mut worklist = [(0, 0); 8];
+    /// assert_eq!(g.flood_fill((0, 0), 7, &mut worklist), Ok(3));
+    ///
+    /// assert_eq!(map, [7, 7, 1, 0,
+    ///                  1, 7, 1, 0,
+    ///                  0, 1, 1, 0]);
+    /// ```
+    pub fn flood_fill(&self, start: (usize, usize), value: T, worklist: &mut [(usize, usize)])
+                      -> Result<usize, WorklistFull>
+        where T: PartialEq
+    {
+        let old = self.cell(start.0, start.1).expect("CellGrid::flood_fill: start out of bounds").get();
+        if old == value {
+            return Ok(0)
+        }
+        self.fill_where(start, value, worklist, |v| v == old)
+    }
+
+    /// Set every element of the 4-connected region around `start`
+    /// where `matches` holds to `value`, returning how many elements
+    /// changed.
+    ///
+    /// `worklist` is used as for `flood_fill`. Elements that have been
+    /// filled are recognised by no longer matching, so `value` itself
+    /// must not match.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start` is out of bounds, or `matches(value)` is true.
+    pub fn fill_where<F>(&self, start: (usize, usize), value: T, worklist: &mut [(usize, usize)],
+                         mut matches: F) -> Result<usize, WorklistFull>
+        where F: FnMut(T) -> bool
+    {
+        assert!(start.0 < self.width() && start.1 < self.height(),
+                "CellGrid::fill_where: start out of bounds");
+        assert!(!matches(value), "CellGrid::fill_where: the fill value matches the region");
+        let (w, h) = (self.width(), self.height());
+        let at = |x: usize, y: usize| &self.as_slice()[y * w + x];
+
+        let mut len = 0;
+        let mut filled = 0;
+        push(worklist, &mut len, start)?;
+        while len > 0 {
+            len -= 1;
+            let (x, y) = worklist[len];
+            if !matches(at(x, y).get()) {
+                continue
+            }
+            let mut lo = x;
+            while lo > 0 && matches(at(lo - 1, y).get()) {
+                lo -= 1;
+            }
+            let mut hi = x;
+            while hi + 1 < w && matches(at(hi + 1, y).get()) {
+                hi += 1;
+            }
+            for x in lo..=hi {
+                at(x, y).set(value);
+            }
+            filled += hi - lo + 1;
+
+            // seed each run of matching elements above and below.
+            for ny in [y.wrapping_sub(1), y + 1] {
+                if ny >= h {
+                    continue
+                }
+                let mut in_run = false;
+                for x in lo..=hi {
+                    let m = matches(at(x, ny).get());
+                    if m && !in_run {
+                        push(worklist, &mut len, (x, ny))?;
+                    }
+                    in_run = m;
+                }
+            }
+        }
+        Ok(filled)
+    }
+}
+
+/// The part of `start..start + len` within `0..limit`.
+pub fn clip(start: isize, len: usize, limit: usize) -> Range<usize> {
+    let end = start.saturating_add(len.min(isize::MAX as usize) as isize);
+    let clamp = |v: isize| v.clamp(0, limit as isize) as usize;
+    clamp(start)..clamp(end)
+}
+
+impl<'a, T: Copy> CellGrid<'a, T> {
+    /// Set every element of the `w` × `h` rectangle with top-left
+    /// corner at column `x` and row `y` to `value`, ignoring the parts
+    /// outside the grid.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let mut px = [0; 12];
+    /// alias::CellGrid::new(&mut px, 4, 3).fill_rect(-1, 1, 3, 5, 9);
+    /// assert_eq!(px, [0, 0, 0, 0,
+    ///                 9, 9, 0, 0,
+    ///                 9, 9, 0, 0]);
+    /// ```
+    pub fn fill_rect(&self, x: isize, y: isize, w: usize, h: usize, value: T) {
+        let xs = clip(x, w, self.width());
+        for y in clip(y, h, self.height()) {
+            for x in xs.clone() {
+                self.as_slice()[y * self.width() + x].set(value);
+            }
+        }
+    }
+
+    /// Set the elements along the straight line from `from` to `to`
+    /// (both inclusive, as `(x, y)`) to `value`, ignoring the parts
+    /// outside the grid.
+    ///
+    /// This uses Bresenham's algorithm, so the line is one element
+    /// thick, without gaps.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let mut px = [0; 12];
+    /// alias::CellGrid::new(&mut px, 4, 3).draw_line((-1, 0), (4, 2), 1);
+    /// assert_eq!(px, [1, 0, 0, 0,
+    ///                 0, 1, 1, 0,
+    ///                 0, 0, 0, 1]);
+    /// ```
+    pub fn draw_line(&self, from: (isize, isize), to: (isize, isize), value: T) {
+        // widen, so that neither the differences nor the products
+        // below can overflow.
+        let (dx, dy) = (to.0 as i128 - from.0 as i128, to.1 as i128 - from.1 as i128);
+        let x_major = dx.abs() >= dy.abs();
+        let (major, minor) = if x_major { (dx.abs(), dy.abs()) } else { (dy.abs(), dx.abs()) };
+        let (major, minor) = (major as u128, minor as u128);
+        if major == 0 {
+            if from.0 >= 0 && from.1 >= 0 {
+                if let Some(c) = self.cell(from.0 as usize, from.1 as usize) {
+                    c.set(value);
+                }
+            }
+            return
+        }
+        // the `k`th element takes `k` steps along the major axis, and
+        // `k * minor / major` rounded half up along the other, exactly
+        // as Bresenham's algorithm would.
+        let point = |k: u128| {
+            let (q, r) = (k * minor / major, k * minor % major);
+            let m = q + (2 * r >= major) as u128;
+            let (i, j) = if x_major { (k, m) } else { (m, k) };
+            (from.0 as i128 + dx.signum() * i as i128, from.1 as i128 + dy.signum() * j as i128)
+        };
+
+        // both coordinates are monotonic in `k`, so the steps inside
+        // the grid are a range, found by bisection rather than by
+        // stepping over the parts outside.
+        let (mut start, mut end) = (0, major + 1);
+        for &(axis, delta, limit) in &[(0, dx, self.width()), (1, dy, self.height())] {
+            let coord = |k| if axis == 0 { point(k).0 } else { point(k).1 };
+            let limit = limit as i128;
+            let (enter, leave) = if delta >= 0 {
+                (first(major + 1, |k| coord(k) >= 0), first(major + 1, |k| coord(k) >= limit))
+            } else {
+                (first(major + 1, |k| coord(k) < limit), first(major + 1, |k| coord(k) < 0))
+            };
+            start = start.max(enter);
+            end = end.min(leave);
+        }
+        for k in start..end {
+            let (x, y) = point(k);
+            self.as_slice()[y as usize * self.width() + x as usize].set(value);
+        }
+    }
+
+    /// Copy all of `src` into this grid, with its top-left corner at
+    /// `(x, y)`, ignoring the parts outside this grid.
+    ///
+    /// `src` may view the same memory as this grid: if it has the same
+    /// width (such as when it *is* this grid), overlapping copies work
+    /// like `memmove`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let mut sprite = [1, 2,
+    ///                   3, 4];
+    /// let mut px = [0; 9];
+    ///
+    /// let s = alias::CellGrid::new(&mut sprite, 2, 2);
+    /// alias::CellGrid::new(&mut px, 3, 3).blit(&s, (2, -1));
+    /// assert_eq!(px, [0, 0, 3,
+    ///                 0, 0, 0,
+    ///                 0, 0, 0]);
+    /// ```
+    pub fn blit(&self, src: &CellGrid<T>, (x, y): (isize, isize)) {
+        let cols = clip(x, src.width(), self.width());
+        let rows = clip(y, src.height(), self.height());
+        if cols.is_empty() || rows.is_empty() {
+            return
+        }
+        let row = |dy: usize| {
+            let sy = (dy as isize - y) as usize;
+            let sx = (cols.start as isize - x) as usize;
+            let from = &src.as_slice()[sy * src.width() + sx..][..cols.len()];
+            let to = &self.as_slice()[dy * self.width() + cols.start..][..cols.len()];
+            (from, to)
+        };
+        let copy_row = |dy: usize| {
+            let (from, to) = row(dy);
+            // nothing else can access the cells during the copy.
+            unsafe { ptr::copy(from.as_ptr(), to.as_ptr() as *mut _, cols.len()) }
+        };
+        // go backwards if the destination is later in memory, so that
+        // overlapping rows aren't overwritten before they're read.
+        let (from, to) = row(rows.start);
+        if to.as_ptr() > from.as_ptr() {
+            rows.rev().for_each(copy_row)
+        } else {
+            rows.for_each(copy_row)
+        }
+    }
+}
+
+/// The first `k` in `0..n` for which `pred` holds, or `n` if there is
+/// none, assuming it never goes from true back to false.
+fn first<F: Fn(u128) -> bool>(n: u128, pred: F) -> u128 {
+    let (mut lo, mut hi) = (0, n);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if pred(mid) {
+            hi = mid
+        } else {
+            lo = mid + 1
+        }
+    }
+    lo
+}
+
+fn push(worklist: &mut [(usize, usize)], len: &mut usize, p: (usize, usize))
+        -> Result<(), WorklistFull> {
+    *worklist.get_mut(*len).ok_or(WorklistFull)? = p;
+    *len += 1;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_fill() {
+        let mut x = *b"\
+.#....\
+.#.##.\
+...###\
+####.#";
+        {
+            let g = CellGrid::new(&mut x, 6, 4);
+            let mut wl = [(0, 0); 16];
+            assert_eq!(g.flood_fill((0, 0), b'o', &mut wl), Ok(11));
+            assert_eq!(g.flood_fill((0, 0), b'o', &mut wl), Ok(0));
+            // the isolated corner
+            assert_eq!(g.fill_where((4, 3), b'x', &mut wl, |c| c == b'.'), Ok(1));
+        }
+        assert_eq!(&x, b"\
+o#oooo\
+o#o##o\
+ooo###\
+####x#");
+
+        // a comb needs a seed per tooth
+        let comb = || {
+            let mut y = [0u8; 9 * 3];
+            for i in (1..9).step_by(2) {
+                y[9 + i] = 1;
+                y[18 + i] = 1;
+            }
+            y
+        };
+        let mut y = comb();
+        let g = CellGrid::new(&mut y, 9, 3);
+        assert_eq!(g.flood_fill((0, 0), 2, &mut [(0, 0); 2]), Err(WorklistFull));
+        let mut y = comb();
+        let g = CellGrid::new(&mut y, 9, 3);
+        assert_eq!(g.flood_fill((4, 2), 2, &mut [(0, 0); 5]), Ok(19));
+    }
+
+    #[test]
+    fn smoke_draw() {
+        let mut x = [0u8; 25];
+        {
+            let g = CellGrid::new(&mut x, 5, 5);
+            g.fill_rect(3, 3, 10, 10, 1);
+            g.fill_rect(-10, 0, 5, 5, 2);
+            g.fill_rect(0, 0, usize::MAX, 0, 3);
+            g.draw_line((0, 4), (4, 0), 4);
+            g.draw_line((2, 2), (2, 2), 5);
+            g.draw_line((-3, 1), (-1, 4), 6);
+        }
+        assert_eq!(x, [0, 0, 0, 0, 4,
+                       0, 0, 0, 4, 0,
+                       0, 0, 5, 0, 0,
+                       0, 4, 0, 1, 1,
+                       4, 0, 0, 1, 1]);
+
+        // steep lines in both directions visit every row
+        let mut y = [0u8; 15];
+        {
+            let g = CellGrid::new(&mut y, 3, 5);
+            g.draw_line((2, 4), (0, 0), 1);
+        }
+        assert_eq!(y, [1, 0, 0,
+                       1, 0, 0,
+                       0, 1, 0,
+                       0, 1, 0,
+                       0, 0, 1]);
+    }
+
+    #[test]
+    fn draw_far_endpoints() {
+        let mut x = [0u8; 25];
+        {
+            let g = CellGrid::new(&mut x, 5, 5);
+            g.draw_line((isize::MIN, isize::MIN), (isize::MAX, isize::MAX), 1);
+            g.draw_line((isize::MAX, 0), (isize::MIN, 1), 2);
+            g.draw_line((isize::MIN, 4), (isize::MIN + 1, 4), 3);
+            g.draw_line((4, isize::MAX), (4, 3), 4);
+        }
+        assert_eq!(x, [2, 2, 2, 2, 2,
+                       0, 1, 0, 0, 0,
+                       0, 0, 1, 0, 0,
+                       0, 0, 0, 1, 4,
+                       0, 0, 0, 0, 4]);
+    }
+
+    #[test]
+    fn blit_overlapping() {
+        let mut x: Vec<u8> = (0..16).collect();
+        {
+            let g = CellGrid::new(&mut x, 4, 4);
+            // shift down-right by one, then up-left by two
+            g.blit(&g, (1, 1));
+            assert_eq!(g.as_slice().iter().map(|c| c.get()).collect::<Vec<_>>(),
+                       [0, 1, 2, 3,
+                        4, 0, 1, 2,
+                        8, 4, 5, 6,
+                        12, 8, 9, 10]);
+            g.blit(&g, (-2, -2));
+        }
+        assert_eq!(x, [5, 6, 2, 3,
+                       9, 10, 1, 2,
+                       8, 4, 5, 6,
+                       12, 8, 9, 10]);
+    }
+
+    #[test]
+    fn blit_clipped() {
+        let mut s = [1u8; 4];
+        let mut x = [0u8; 9];
+        {
+            let sprite = CellGrid::new(&mut s, 2, 2);
+            let g = CellGrid::new(&mut x, 3, 3);
+            // entirely off each side
+            for &at in &[(-5, 0), (3, 0), (0, -5), (0, 3), (-2, -2), (isize::MIN, isize::MAX)] {
+                g.blit(&sprite, at);
+            }
+            // no columns or no rows
+            for &(w, h) in &[(0, 2), (2, 0), (0, 0)] {
+                let empty = CellGrid::new(&mut [], w, h);
+                for &at in &[(-1, 0), (0, -1), (1, 1), (3, 3)] {
+                    g.blit(&empty, at);
+                }
+            }
+        }
+        assert_eq!(x, [0; 9]);
+
+        let mut y = [0u8; 4];
+        let sprite = CellGrid::new(&mut s, 2, 2);
+        let d = ::DirtyRectGrid::new(&mut y, 2, 2);
+        d.blit(&sprite, (-2, 0));
+        d.blit(&CellGrid::new(&mut [], 0, 1), (-1, 0));
+        assert!(d.take_dirty_rects().is_empty());
+        assert_eq!(d.get(0, 0), Some(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "matches the region")]
+    fn fill_value_matches() {
+        let mut x = [0; 4];
+        let _ = CellGrid::new(&mut x, 2, 2).fill_where((0, 0), 1, &mut [], |v| v < 5);
+    }
+}
@@ -0,0 +1,149 @@
+//! In-place permutations of aliased slices, without scratch space.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use alias::permute;
+//!
+//! let mut x = [0, 1, 2, 3, 4, 5, 6, 7];
+//! permute::bit_reverse_permute(alias::slice(&mut x));
+//! assert_eq!(x, [0, 4, 2, 6, 1, 5, 3, 7]);
+//!
+//! let mut y = ['a', 'b', 'c', 'd'];
+//! permute::permute_in_place(alias::slice(&mut y), &[2, 0, 3, 1]);
+//! assert_eq!(y, ['c', 'a', 'd', 'b']);
+//! ```
+
+use std::cell::Cell;
+
+/// Reorder `data` so that the element at index `i` moves to the index
+/// whose binary representation is `i`'s reversed, as used by in-place
+/// radix-2 FFTs.
+///
+/// # Panics
+///
+/// Panics if the length of `data` is not a power of two.
+pub fn bit_reverse_permute<T>(data: &[Cell<T>]) {
+    let n = data.len();
+    assert!(n.is_power_of_two(),
+            "permute::bit_reverse_permute: length {} is not a power of two", n);
+    let shift = usize::BITS - n.trailing_zeros();
+    for i in 0..n {
+        // shifting by `usize::BITS` isn't allowed, so length 1 is special.
+        let j = if n == 1 { 0 } else { i.reverse_bits() >> shift };
+        if i < j {
+            data[i].swap(&data[j]);
+        }
+    }
+}
+
+/// Reorder `data` so that the element at index `i` becomes the
+/// element that was at index `perm[i]`.
+///
+/// This follows each cycle of the permutation, moving elements one at
+/// a time, and so needs no scratch space. To avoid allocating a
+/// "visited" set, each cycle is only processed starting from its
+/// smallest index, which takes `O(n × longest cycle)` time in the
+/// worst case.
+///
+/// # Panics
+///
+/// Panics if `perm` is not the same length as `data`, or is not a
+/// permutation of `0..data.len()`. In the latter case `data` may have
+/// been partially reordered.
+pub fn permute_in_place<T: Copy>(data: &[Cell<T>], perm: &[usize]) {
+    let n = data.len();
+    assert_eq!(perm.len(), n, "permute::permute_in_place: mismatched lengths");
+    let mut covered = 0;
+    'cycles: for start in 0..n {
+        // check that `start` is the smallest index in its cycle.
+        let mut j = perm[start];
+        let mut steps = 0;
+        while j != start {
+            assert!(j < n && steps < n, "permute::permute_in_place: not a permutation");
+            if j < start {
+                continue 'cycles
+            }
+            j = perm[j];
+            steps += 1;
+        }
+
+        covered += rotate_cycle(data, start, |j| perm[j]);
+    }
+    // the cycles of a permutation cover every index, but invalid
+    // input can have indices that are only reached from a smaller
+    // one, so none of the above cycles start from them.
+    assert_eq!(covered, n, "permute::permute_in_place: not a permutation");
+}
+
+/// Move each element of the cycle through `start` to the previous
+/// index in it, so index `j` gets the element at `next(j)`, returning
+/// the length of the cycle.
+///
+/// `next` is called exactly once for each index in the cycle, in
+/// order, and must lead back to `start`.
+pub(crate) fn rotate_cycle<T, F>(data: &[Cell<T>], start: usize, mut next: F) -> usize
+    where T: Copy, F: FnMut(usize) -> usize
+{
+    let tmp = data[start].get();
+    let mut j = start;
+    let mut len = 1;
+    loop {
+        let k = next(j);
+        if k == start {
+            data[j].set(tmp);
+            return len
+        }
+        data[j].set(data[k].get());
+        j = k;
+        len += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slice;
+
+    #[test]
+    fn smoke_bit_reverse() {
+        for &n in &[1usize, 2, 4, 16, 64] {
+            let mut x: Vec<usize> = (0..n).collect();
+            bit_reverse_permute(slice(&mut x));
+            let bits = n.trailing_zeros();
+            for (i, &v) in x.iter().enumerate() {
+                let rev = if bits == 0 { 0 } else { i.reverse_bits() >> (usize::BITS - bits) };
+                assert_eq!(v, rev);
+            }
+            // it's an involution
+            bit_reverse_permute(slice(&mut x));
+            assert_eq!(x, (0..n).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn smoke_permute() {
+        let perm = [3, 5, 0, 1, 2, 4, 6];
+        let mut x = [10, 11, 12, 13, 14, 15, 16];
+        permute_in_place(slice(&mut x), &perm);
+        assert_eq!(x, [13, 15, 10, 11, 12, 14, 16]);
+    }
+
+    #[test]
+    fn permute_invalid() {
+        use std::panic::catch_unwind;
+
+        // a repeated index, and an index (2) that is only reached
+        // from a smaller one, so never starts a cycle.
+        for perm in [[1, 1, 2], [1, 0, 0]] {
+            let err = catch_unwind(|| {
+                let mut x = [10, 11, 12];
+                permute_in_place(slice(&mut x), &perm);
+            }).unwrap_err();
+            let msg = err.downcast_ref::<String>().map(|s| &**s)
+                .or_else(|| err.downcast_ref::<&str>().cloned())
+                .unwrap();
+            assert!(msg.contains("not a permutation"), "{:?}: {}", perm, msg);
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! Projecting aliased references to structs onto their fields.
+//!
+//! A `&Cell<S>` for a struct `S` covers each of its fields, so it can
+//! safely be narrowed to a `&Cell<F>` for any one field `f: F`: every
+//! write through either goes through a `Cell`. The
+//! `cell_project!(cell, S, f)` macro does this, checking at compile
+//! time that `f` really is a field of `S` (rather than something
+//! reached through `Deref`) and that `S` isn't `#[repr(packed)]`.
+//!
+//! This allows working with individual fields of large structs
+//! without copying the whole thing in and out of the cell.
+//!
+//! # Examples
+//!
+//! ```rust
+//! #[macro_use]
+//! extern crate alias;
+//!
+//! #[derive(Clone, Copy)]
+//! struct Particle {
+//!     pos: [f32; 3],
+//!     vel: [f32; 3],
+//!     age: u32,
+//! }
+//!
+//! # fn main() {
+//! let mut ps = [Particle { pos: [0.0; 3], vel: [1.0, 0.0, 0.0], age: 0 }; 2];
+//! let cells = alias::slice(&mut ps);
+//!
+//! for p in cells {
+//!     let pos = cell_project!(p, Particle, pos);
+//!     let vel = cell_project!(p, Particle, vel);
+//!     let mut new = pos.get();
+//!     for (x, v) in new.iter_mut().zip(vel.get()) {
+//!         *x += v;
+//!     }
+//!     pos.set(new);
+//!     let age = cell_project!(p, Particle, age);
+//!     age.set(age.get() + 1);
+//! }
+//!
+//! assert_eq!(ps[1].pos, [1.0, 0.0, 0.0]);
+//! assert_eq!(ps[1].age, 1);
+//! # }
+//! ```
+//!
+//! Fields reached through `Deref`, and fields of packed structs,
+//! are rejected:
+//!
+//! ```rust,compile_fail
+//! # #[macro_use] extern crate alias;
+//! # fn main() {
+//! let mut b = Box::new((1, 2));
+//! let c = alias::one(&mut b);
+//! let _ = cell_project!(c, Box<(i32, i32)>, 0);
+//! # }
+//! ```
+//!
+//! ```rust,compile_fail
+//! # #[macro_use] extern crate alias;
+//! #[derive(Clone, Copy)]
+//! #[repr(packed)]
+//! struct Packed { a: u8, b: u32 }
+//! # fn main() {
+//! let mut p = Packed { a: 0, b: 0 };
+//! let _ = cell_project!(alias::one(&mut p), Packed, b);
+//! # }
+//! ```
+
+use std::cell::Cell;
+
+/// Project `cell: &Cell<S>` onto the field `field` of the struct
+/// `S`, giving a `&Cell` of the field's type with the same lifetime.
+///
+/// `field` can be a name, or an index for tuple structs. See the
+/// `project` module for details.
+#[macro_export]
+macro_rules! cell_project {
+    ($cell: expr, $ty: path, $field: tt) => {{
+        let cell: &::std::cell::Cell<$ty> = $cell;
+        if false {
+            // `field` must be a field of `ty` itself (patterns don't
+            // look through `Deref`), and referenceable (so not in a
+            // packed struct). This is never run.
+            let _ = |v: &$ty| {
+                let $ty { $field: _, .. } = *v;
+                let _ = &v.$field;
+            };
+        }
+        unsafe {
+            $crate::project::__project(cell, |p| ::std::ptr::addr_of_mut!((*p).$field))
+        }
+    }};
+}
+
+/// Implementation detail of `cell_project!`.
+///
+/// # Safety
+///
+/// `f` must return a pointer to a field of the struct it is given,
+/// which is suitably aligned.
+#[doc(hidden)]
+pub unsafe fn __project<T, F, P>(cell: &Cell<T>, f: P) -> &Cell<F>
+    where P: FnOnce(*mut T) -> *mut F
+{
+    &*(f(cell.as_ptr()) as *const Cell<F>)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Inner(u8, u64);
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Outer {
+        a: u16,
+        inner: Inner,
+    }
+
+    #[test]
+    fn smoke_project() {
+        let mut x = Outer { a: 1, inner: Inner(2, 3) };
+        {
+            let c = ::one(&mut x);
+            let a = cell_project!(c, Outer, a);
+            let inner = cell_project!(c, Outer, inner);
+            let i0 = cell_project!(inner, Inner, 0);
+            let i1: &Cell<u64> = cell_project!(inner, Inner, 1);
+
+            a.set(10);
+            i0.set(20);
+            i1.set(i1.get() + a.get() as u64);
+            assert_eq!(inner.get(), Inner(20, 13));
+            c.set(Outer { a: 0, inner: Inner(0, 0) });
+            assert_eq!((a.get(), i0.get(), i1.get()), (0, 0, 0));
+            i1.set(5);
+        }
+        assert_eq!(x, Outer { a: 0, inner: Inner(0, 5) });
+    }
+}
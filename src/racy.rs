@@ -0,0 +1,199 @@
+//! Sharing aliased cells between threads under external
+//! synchronisation.
+//!
+//! A `&Cell<T>` can't be sent to another thread, because nothing
+//! stops two threads from using it at once. Sometimes something else
+//! does: the threads work in phases separated by a `Barrier`, or each
+//! hands over to the next through a channel. `assert_sync` lets the
+//! caller vouch for that, giving a `RacyCell` that is `Sync`.
+//!
+//! In debug builds, each access to a `RacyCell` checks that no other
+//! thread is accessing the same cell at that moment, and panics if
+//! one is. This catches some, not all, violations of the contract.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use std::sync::Barrier;
+//! use std::thread;
+//!
+//! let mut data = [0u32; 4];
+//! {
+//!     let cells = unsafe { alias::racy::assert_sync_slice(alias::slice(&mut data)) };
+//!     let barrier = Barrier::new(2);
+//!     thread::scope(|s| {
+//!         // phase 1: the writer fills the buffer, phase 2: the
+//!         // reader doubles it. Each phase has one thread.
+//!         s.spawn(|| {
+//!             for (i, c) in cells.iter().enumerate() {
+//!                 c.set(i as u32);
+//!             }
+//!             barrier.wait();
+//!         });
+//!         s.spawn(|| {
+//!             barrier.wait();
+//!             for c in cells {
+//!                 c.set(c.get() * 2);
+//!             }
+//!         });
+//!     });
+//! }
+//! assert_eq!(data, [0, 2, 4, 6]);
+//! ```
+
+use std::cell::Cell;
+use std::fmt;
+#[cfg(debug_assertions)]
+use std::mem;
+#[cfg(debug_assertions)]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A `Cell` that the creator has promised is never accessed from two
+/// threads at once.
+///
+/// Created by `assert_sync` and `assert_sync_slice`.
+#[repr(transparent)]
+pub struct RacyCell<T> {
+    cell: Cell<T>,
+}
+
+// the caller of `assert_sync` guarantees that accesses from different
+// threads never overlap, and are ordered by some synchronisation.
+unsafe impl<T: Send> Sync for RacyCell<T> {}
+
+/// Allow `cell` to be shared between threads.
+///
+/// # Safety
+///
+/// For as long as the result (or any copy of it) is usable, every
+/// access to the cell, through either the `RacyCell` or `cell`
+/// itself, must happen-before or happen-after every other access
+/// from a different thread, such as by being separated by a
+/// `Barrier`, a `Mutex`, a channel, or a thread being joined.
+pub unsafe fn assert_sync<T: Send>(cell: &Cell<T>) -> &RacyCell<T> {
+    &*(cell as *const Cell<T> as *const RacyCell<T>)
+}
+
+/// Allow the cells of `cells` to be shared between threads.
+///
+/// # Safety
+///
+/// Each cell must satisfy the contract of `assert_sync`.
+pub unsafe fn assert_sync_slice<T: Send>(cells: &[Cell<T>]) -> &[RacyCell<T>] {
+    &*(cells as *const [Cell<T>] as *const [RacyCell<T>])
+}
+
+#[cfg(debug_assertions)]
+static ACTIVE: [AtomicUsize; 64] = [const { AtomicUsize::new(0) }; 64];
+
+impl<T> RacyCell<T> {
+    /// Run `f` on the cell, checking in debug builds that no other
+    /// thread is in the same section for the same cell.
+    fn access<R, F: FnOnce(&Cell<T>) -> R>(&self, f: F) -> R {
+        #[cfg(debug_assertions)]
+        {
+            struct Release<'s>(&'s AtomicUsize);
+            impl<'s> Drop for Release<'s> {
+                fn drop(&mut self) {
+                    self.0.store(0, Ordering::Release)
+                }
+            }
+
+            let addr = self as *const RacyCell<T> as usize;
+            if mem::size_of::<T>() != 0 {
+                let slot = &ACTIVE[(addr / mem::align_of::<T>()) % ACTIVE.len()];
+                match slot.compare_exchange(0, addr, Ordering::Acquire, Ordering::Relaxed) {
+                    Ok(_) => {
+                        let _release = Release(slot);
+                        return f(&self.cell)
+                    }
+                    Err(a) => assert!(a != addr, "RacyCell: concurrent access from two threads"),
+                }
+            }
+        }
+        f(&self.cell)
+    }
+
+    /// Read the value.
+    pub fn get(&self) -> T
+        where T: Copy
+    {
+        self.access(|c| c.get())
+    }
+
+    /// Write `value`.
+    pub fn set(&self, value: T) {
+        // dropped outside `access`, in case the destructor uses this
+        // cell.
+        drop(self.replace(value))
+    }
+
+    /// Write `value`, returning the old value.
+    pub fn replace(&self, value: T) -> T {
+        self.access(|c| c.replace(value))
+    }
+
+    /// Take the value, leaving `Default::default()` in its place.
+    pub fn take(&self) -> T
+        where T: Default
+    {
+        self.replace(T::default())
+    }
+
+    /// The underlying cell.
+    ///
+    /// Accesses through it aren't checked in debug builds, but are
+    /// still bound by the contract of `assert_sync`.
+    pub fn as_cell(&self) -> &Cell<T> {
+        &self.cell
+    }
+}
+
+impl<T: Copy + fmt::Debug> fmt::Debug for RacyCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("RacyCell").field(&self.get()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::thread;
+    use {one, slice};
+
+    #[test]
+    fn smoke_racy() {
+        let mut x = String::from("a");
+        let mut v = [1u64, 2];
+        {
+            let c = unsafe { assert_sync(one(&mut x)) };
+            let s = unsafe { assert_sync_slice(slice(&mut v)) };
+            let (tx, rx) = mpsc::channel();
+            thread::scope(|sc| {
+                sc.spawn(move || {
+                    c.set(c.take() + "b");
+                    s[0].set(s[1].get() * 10);
+                    tx.send(()).unwrap();
+                });
+                sc.spawn(move || {
+                    rx.recv().unwrap();
+                    s[1].set(s[0].replace(0));
+                    c.as_cell().set(c.take() + "c");
+                });
+            });
+            assert_eq!(format!("{:?}", s[1]), "RacyCell(20)");
+        }
+        assert_eq!((&*x, v), ("abc", [0, 20]));
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "concurrent access")]
+    fn detects_overlap() {
+        let mut x = 0u32;
+        let c = unsafe { assert_sync(one(&mut x)) };
+        // simulate another thread being mid-access.
+        c.access(|_| c.get());
+    }
+}
@@ -0,0 +1,271 @@
+use std::cell::Cell;
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut, Range};
+use std::slice;
+
+/// A slice whose sub-ranges can be claimed either for aliased (`Cell`)
+/// access or for exclusive (`&mut`) access, with overlaps checked at
+/// runtime.
+///
+/// This is like a `RefCell` per range: any number of overlapping
+/// `shared` claims can coexist, but an `exclusive` claim can't
+/// overlap with anything else. It allows occasional bulk operations
+/// that need `&mut [T]` (sorting, `copy_from_slice`, passing to
+/// other libraries) on data that is otherwise shared through cells.
+///
+/// For this to be sound, the locker must be the only way of accessing
+/// the data, which is why it is created from `&mut [T]` rather than a
+/// slice of cells.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::RegionLocker;
+///
+/// let mut data = [5, 3, 1, 4, 2, 0];
+/// let locker = RegionLocker::new(&mut data);
+///
+/// let tail = locker.shared(4..6).unwrap();
+/// tail[0].set(9);
+///
+/// {
+///     let mut head = locker.exclusive(0..4).unwrap();
+///     head.sort();
+///     // overlaps with `head`
+///     assert!(locker.shared(3..5).is_err());
+/// }
+///
+/// assert!(locker.exclusive(3..5).is_err()); // overlaps with `tail`
+/// drop(tail);
+/// let all = locker.shared(0..6).unwrap();
+/// assert_eq!(all.iter().map(|c| c.get()).collect::<Vec<_>>(), [1, 3, 4, 5, 9, 0]);
+/// ```
+pub struct RegionLocker<'a, T: 'a> {
+    ptr: *mut T,
+    len: usize,
+    claims: Cell<Vec<Claim>>,
+    next_id: Cell<u64>,
+    _marker: PhantomData<&'a mut [T]>,
+}
+
+#[derive(Clone, Debug)]
+struct Claim {
+    id: u64,
+    range: Range<usize>,
+    exclusive: bool,
+}
+
+/// The error returned when a claim conflicts with an existing one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Busy {
+    /// The range that was requested.
+    pub requested: Range<usize>,
+    /// An existing claim that it overlaps with.
+    pub conflict: Range<usize>,
+}
+
+impl fmt::Display for Busy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "region {:?} overlaps with claimed region {:?}", self.requested, self.conflict)
+    }
+}
+
+impl Error for Busy {}
+
+impl<'a, T> RegionLocker<'a, T> {
+    /// Manage access to `data` by claimed ranges.
+    pub fn new(data: &'a mut [T]) -> RegionLocker<'a, T> {
+        RegionLocker {
+            ptr: data.as_mut_ptr(),
+            len: data.len(),
+            claims: Cell::new(Vec::new()),
+            next_id: Cell::new(0),
+            _marker: PhantomData,
+        }
+    }
+
+    /// The length of the whole slice.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the slice is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn claim(&self, range: Range<usize>, exclusive: bool) -> Result<u64, Busy> {
+        assert!(range.start <= range.end && range.end <= self.len,
+                "RegionLocker: range {:?} out of bounds for length {}", range, self.len);
+        let mut claims = self.claims.take();
+        let conflict = claims.iter().find(|c| {
+            (exclusive || c.exclusive) && !range.is_empty() && !c.range.is_empty()
+                && c.range.start < range.end && range.start < c.range.end
+        }).map(|c| c.range.clone());
+        let ret = match conflict {
+            Some(conflict) => Err(Busy { requested: range, conflict }),
+            None => {
+                let id = self.next_id.get();
+                self.next_id.set(id + 1);
+                claims.push(Claim { id, range, exclusive });
+                Ok(id)
+            }
+        };
+        self.claims.set(claims);
+        ret
+    }
+
+    fn release(&self, id: u64) {
+        let mut claims = self.claims.take();
+        claims.retain(|c| c.id != id);
+        self.claims.set(claims);
+    }
+
+    /// Claim `range` for aliased access, failing if it overlaps an
+    /// exclusive claim.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds.
+    pub fn shared(&self, range: Range<usize>) -> Result<SharedRegion<'_, 'a, T>, Busy> {
+        let start = range.start;
+        let len = range.len();
+        let id = self.claim(range, false)?;
+        let cells = unsafe {
+            slice::from_raw_parts(self.ptr.add(start) as *const Cell<T>, len)
+        };
+        Ok(SharedRegion { locker: self, id, cells })
+    }
+
+    /// Claim `range` for exclusive access, failing if it overlaps any
+    /// other claim.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds.
+    pub fn exclusive(&self, range: Range<usize>) -> Result<RegionGuard<'_, 'a, T>, Busy> {
+        let start = range.start;
+        let len = range.len();
+        let id = self.claim(range, true)?;
+        let data = unsafe { slice::from_raw_parts_mut(self.ptr.add(start), len) };
+        Ok(RegionGuard { locker: self, id, data })
+    }
+}
+
+impl<'a, T> fmt::Debug for RegionLocker<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let claims = self.claims.take();
+        let ret = f.debug_struct("RegionLocker")
+            .field("len", &self.len)
+            .field("claims", &claims)
+            .finish();
+        self.claims.set(claims);
+        ret
+    }
+}
+
+/// A claimed region with aliased access.
+///
+/// Created by `RegionLocker::shared`.
+pub struct SharedRegion<'l, 'a: 'l, T: 'a> {
+    locker: &'l RegionLocker<'a, T>,
+    id: u64,
+    cells: &'l [Cell<T>],
+}
+
+impl<'l, 'a, T> Deref for SharedRegion<'l, 'a, T> {
+    type Target = [Cell<T>];
+    fn deref(&self) -> &[Cell<T>] {
+        self.cells
+    }
+}
+
+impl<'l, 'a, T> Drop for SharedRegion<'l, 'a, T> {
+    fn drop(&mut self) {
+        self.locker.release(self.id)
+    }
+}
+
+impl<'l, 'a, T: Copy + fmt::Debug> fmt::Debug for SharedRegion<'l, 'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.cells.fmt(f)
+    }
+}
+
+/// A claimed region with exclusive access.
+///
+/// Created by `RegionLocker::exclusive`.
+pub struct RegionGuard<'l, 'a: 'l, T: 'a> {
+    locker: &'l RegionLocker<'a, T>,
+    id: u64,
+    data: &'l mut [T],
+}
+
+impl<'l, 'a, T> Deref for RegionGuard<'l, 'a, T> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        self.data
+    }
+}
+
+impl<'l, 'a, T> DerefMut for RegionGuard<'l, 'a, T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.data
+    }
+}
+
+impl<'l, 'a, T> Drop for RegionGuard<'l, 'a, T> {
+    fn drop(&mut self) {
+        self.locker.release(self.id)
+    }
+}
+
+impl<'l, 'a, T: fmt::Debug> fmt::Debug for RegionGuard<'l, 'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.data.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_regions() {
+        let mut x = [0; 10];
+        {
+            let l = RegionLocker::new(&mut x);
+            let a = l.shared(0..5).unwrap();
+            let b = l.shared(3..8).unwrap();
+            a[4].set(1);
+            assert_eq!(b[1].get(), 1);
+
+            assert_eq!(l.exclusive(7..10).unwrap_err(), Busy { requested: 7..10, conflict: 3..8 });
+            {
+                let mut c = l.exclusive(8..10).unwrap();
+                c.copy_from_slice(&[8, 9]);
+                assert!(l.exclusive(9..10).is_err());
+                assert!(l.shared(9..10).is_err());
+                // empty ranges never conflict
+                assert!(l.exclusive(9..9).is_ok());
+            }
+            drop(a);
+            drop(b);
+            let mut d = l.exclusive(0..10).unwrap();
+            d[0] = 100;
+            assert_eq!(l.shared(0..1).unwrap_err().to_string(),
+                       "region 0..1 overlaps with claimed region 0..10");
+        }
+        assert_eq!(x, [100, 0, 0, 0, 1, 0, 0, 0, 8, 9]);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn out_of_bounds() {
+        let mut x = [0; 3];
+        let l = RegionLocker::new(&mut x);
+        let _ = l.shared(2..4);
+    }
+}
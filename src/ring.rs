@@ -0,0 +1,141 @@
+use std::cell::Cell;
+use std::fmt;
+
+use slice;
+
+/// A fixed-capacity ring buffer over borrowed storage, where every
+/// operation works through shared references.
+///
+/// Unlike `EventQueue`, space is reused as soon as an element is
+/// popped, so a producer and consumer can run indefinitely as long as
+/// the consumer keeps up.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::CellRing;
+///
+/// let mut storage = [0; 4];
+/// let ring = CellRing::new(&mut storage);
+///
+/// let produce = |ring: &CellRing<u32>, n| ring.push(n).is_ok();
+/// let consume = |ring: &CellRing<u32>| ring.pop();
+///
+/// let mut got = vec![];
+/// for tick in 0..10 {
+///     produce(&ring, tick);
+///     if tick % 2 == 1 {
+///         got.extend(consume(&ring));
+///     }
+/// }
+/// assert_eq!(got, [0, 1, 2, 3, 4]);
+/// // 7 and 9 were dropped, as the ring was full
+/// assert_eq!(format!("{:?}", ring), "[5, 6, 8]");
+/// ```
+pub struct CellRing<'a, T: 'a> {
+    slots: &'a [Cell<T>],
+    head: Cell<usize>,
+    len: Cell<usize>,
+}
+
+impl<'a, T: Copy> CellRing<'a, T> {
+    /// Create an empty ring using `storage` to hold the elements; its
+    /// length is the capacity of the ring.
+    pub fn new(storage: &'a mut [T]) -> CellRing<'a, T> {
+        CellRing { slots: slice(storage), head: Cell::new(0), len: Cell::new(0) }
+    }
+
+    /// The maximum number of elements.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// The number of elements.
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+
+    /// Whether there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len.get() == 0
+    }
+
+    /// Whether the ring is at capacity.
+    pub fn is_full(&self) -> bool {
+        self.len.get() == self.slots.len()
+    }
+
+    fn slot(&self, i: usize) -> &'a Cell<T> {
+        let j = self.head.get() + i;
+        let cap = self.slots.len();
+        &self.slots[if j >= cap { j - cap } else { j }]
+    }
+
+    /// Add `value` to the back of the ring, or return it if the ring
+    /// is full.
+    pub fn push(&self, value: T) -> Result<(), T> {
+        let len = self.len.get();
+        if len == self.slots.len() {
+            return Err(value)
+        }
+        self.slot(len).set(value);
+        self.len.set(len + 1);
+        Ok(())
+    }
+
+    /// Remove and return the front element, if any.
+    pub fn pop(&self) -> Option<T> {
+        let len = self.len.get().checked_sub(1)?;
+        let value = self.slot(0).get();
+        let head = self.head.get() + 1;
+        self.head.set(if head == self.slots.len() { 0 } else { head });
+        self.len.set(len);
+        Some(value)
+    }
+
+    /// The front element, if any.
+    pub fn peek(&self) -> Option<T> {
+        if self.is_empty() { None } else { Some(self.slot(0).get()) }
+    }
+
+    /// Remove every element.
+    pub fn clear(&self) {
+        self.head.set(0);
+        self.len.set(0);
+    }
+}
+
+impl<'a, T: Copy + fmt::Debug> fmt::Debug for CellRing<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries((0..self.len()).map(|i| self.slot(i).get())).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_ring() {
+        let mut x = [0; 3];
+        {
+            let r = CellRing::new(&mut x);
+            assert_eq!((r.pop(), r.peek()), (None, None));
+            assert_eq!(r.push(1), Ok(()));
+            assert_eq!(r.push(2), Ok(()));
+            assert_eq!(r.push(3), Ok(()));
+            assert_eq!(r.push(4), Err(4));
+            assert_eq!(r.pop(), Some(1));
+            assert_eq!(r.push(4), Ok(()));
+            assert_eq!(format!("{:?}", r), "[2, 3, 4]");
+            assert_eq!((r.pop(), r.pop(), r.peek()), (Some(2), Some(3), Some(4)));
+            assert_eq!(r.push(5), Ok(()));
+            assert_eq!((r.len(), r.is_full()), (2, false));
+        }
+        assert_eq!(x, [4, 5, 3]);
+
+        let empty = CellRing::<u8>::new(&mut []);
+        assert_eq!((empty.push(1), empty.pop()), (Err(1), None));
+        empty.clear();
+    }
+}
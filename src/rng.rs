@@ -0,0 +1,166 @@
+use std::cell::Cell;
+use std::ops::Range;
+
+use one;
+
+/// A small pseudo-random number generator whose state lives in
+/// aliased memory, so that one generator can be shared by many
+/// handles on the same thread.
+///
+/// This is SplitMix64: fast and of reasonable statistical quality,
+/// but not cryptographically secure. Every `u64` is a valid state,
+/// including zero.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::CellRng;
+///
+/// let mut seed = 42;
+/// let rng = CellRng::new(&mut seed);
+/// let (physics, ai) = (rng, rng);
+///
+/// let a = physics.next_u64();
+/// let b = ai.next_u64();
+/// assert!(a != b);
+///
+/// let roll = ai.gen_range(1..7);
+/// assert!(1 <= roll && roll < 7);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct CellRng<'a> {
+    state: &'a Cell<u64>,
+}
+
+impl<'a> CellRng<'a> {
+    /// Use `state` as the state of a generator, which can then be
+    /// shared while aliased.
+    pub fn new(state: &'a mut u64) -> CellRng<'a> {
+        CellRng { state: one(state) }
+    }
+
+    /// Generate a uniformly distributed `u64`.
+    pub fn next_u64(&self) -> u64 {
+        let s = self.state.get().wrapping_add(0x9E37_79B9_7F4A_7C15);
+        self.state.set(s);
+        let mut z = s;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Generate a uniformly distributed `u32`.
+    pub fn next_u32(&self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// Generate a uniformly distributed value in `range`, without
+    /// modulo bias.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is empty.
+    pub fn gen_range(&self, range: Range<u64>) -> u64 {
+        assert!(range.start < range.end, "CellRng::gen_range: empty range");
+        let n = range.end - range.start;
+        // Lemire's nearly divisionless method.
+        let mut m = self.next_u64() as u128 * n as u128;
+        if (m as u64) < n {
+            let threshold = n.wrapping_neg() % n;
+            while (m as u64) < threshold {
+                m = self.next_u64() as u128 * n as u128;
+            }
+        }
+        range.start + (m >> 64) as u64
+    }
+
+    /// Shuffle `data` in place, with every permutation equally likely.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use alias::CellRng;
+    ///
+    /// let mut seed = 7;
+    /// let mut deck = [1, 2, 3, 4, 5];
+    /// {
+    ///     let rng = CellRng::new(&mut seed);
+    ///     rng.shuffle(alias::slice(&mut deck));
+    /// }
+    /// deck.sort();
+    /// assert_eq!(deck, [1, 2, 3, 4, 5]);
+    /// ```
+    pub fn shuffle<T: Copy>(&self, data: &[Cell<T>]) {
+        self.partial_shuffle(data, data.len());
+    }
+
+    /// Move a uniformly random selection of `amount` elements of
+    /// `data`, in random order, to its start, returning those elements
+    /// and the rest (in no particular order).
+    ///
+    /// This does only `amount` swaps, so is much cheaper than a full
+    /// shuffle for sampling a few elements of a large slice. If
+    /// `amount` is larger than `data`, all of it is shuffled.
+    pub fn partial_shuffle<'b, T: Copy>(&self, data: &'b [Cell<T>], amount: usize)
+                                        -> (&'b [Cell<T>], &'b [Cell<T>]) {
+        let amount = amount.min(data.len());
+        for i in 0..amount {
+            let j = self.gen_range(i as u64..data.len() as u64) as usize;
+            data[i].swap(&data[j]);
+        }
+        data.split_at(amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slice;
+
+    #[test]
+    fn smoke_rng() {
+        let mut s = 0;
+        let mut t = 0;
+        {
+            let a = CellRng::new(&mut s);
+            let b = a;
+            // reference values for SplitMix64 seeded with 0.
+            assert_eq!(a.next_u64(), 0xE220_A839_7B1D_CDAF);
+            assert_eq!(b.next_u64(), 0x6E78_9E6A_A1B9_65F4);
+
+            let c = CellRng::new(&mut t);
+            let mut counts = [0; 6];
+            for _ in 0..6000 {
+                let x = c.gen_range(10..16);
+                counts[(x - 10) as usize] += 1;
+            }
+            assert!(counts.iter().all(|&n| 800 < n && n < 1200), "{:?}", counts);
+            assert_eq!(c.gen_range(5..6), 5);
+            let _ = c.next_u32();
+        }
+        assert_eq!(s, 0x9E37_79B9_7F4A_7C15u64.wrapping_mul(2));
+    }
+
+    #[test]
+    fn smoke_shuffle() {
+        let mut s = 1;
+        let rng = CellRng::new(&mut s);
+        let mut x = [0, 1, 2, 3];
+        let mut firsts = [0; 4];
+        for _ in 0..4000 {
+            let cells = slice(&mut x);
+            let (chosen, rest) = rng.partial_shuffle(cells, 1);
+            assert_eq!((chosen.len(), rest.len()), (1, 3));
+            firsts[chosen[0].get()] += 1;
+            rng.shuffle(cells);
+            let mut sorted = x;
+            sorted.sort();
+            assert_eq!(sorted, [0, 1, 2, 3]);
+        }
+        assert!(firsts.iter().all(|&n| 800 < n && n < 1200), "{:?}", firsts);
+
+        let (all, none) = rng.partial_shuffle(slice(&mut x), 10);
+        assert_eq!((all.len(), none.len()), (4, 0));
+        rng.shuffle::<u8>(&[]);
+    }
+}
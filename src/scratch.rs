@@ -0,0 +1,104 @@
+use std::cell::Cell;
+use std::mem::{self, MaybeUninit};
+use std::slice::from_raw_parts_mut;
+
+use slice;
+
+// Storage is in units of `u128` to get reasonable alignment for most
+// types; anything more aligned gets a fresh allocation.
+type Word = MaybeUninit<u128>;
+
+thread_local!(static SCRATCH: Cell<Vec<Word>> = const { Cell::new(Vec::new()) });
+
+/// Run `f` with an aliased scratch buffer of `len` elements, each
+/// initialised to `T::default()`.
+///
+/// The memory comes from a buffer private to the current thread,
+/// which grows as needed and is reused by later calls, so this doesn't
+/// allocate in the steady state. Nested calls work, but the inner ones
+/// allocate, as the outer call is using the buffer.
+///
+/// # Examples
+///
+/// ```rust
+/// let total = alias::scratch(4, |tmp: &[std::cell::Cell<u32>]| {
+///     for (i, c) in tmp.iter().enumerate() {
+///         c.set(i as u32 * 10);
+///     }
+///     tmp.iter().map(|c| c.get()).sum::<u32>()
+/// });
+/// assert_eq!(total, 60);
+/// ```
+pub fn scratch<T, R, F>(len: usize, f: F) -> R
+    where T: Copy + Default, F: FnOnce(&[Cell<T>]) -> R
+{
+    if mem::align_of::<T>() > mem::align_of::<Word>() || mem::size_of::<T>() == 0 {
+        let mut v = vec![T::default(); len];
+        return f(slice(&mut v))
+    }
+
+    let bytes = len.checked_mul(mem::size_of::<T>()).expect("alias::scratch: size overflow");
+    let words = bytes.div_ceil(mem::size_of::<Word>());
+
+    let mut buf = SCRATCH.with(Cell::take);
+    if buf.len() < words {
+        buf.resize(words, MaybeUninit::uninit());
+    }
+    let ptr = buf.as_mut_ptr() as *mut T;
+    let view = unsafe {
+        for i in 0..len {
+            ptr.add(i).write(T::default());
+        }
+        from_raw_parts_mut(ptr, len)
+    };
+    let ret = f(slice(view));
+
+    // keep whichever buffer is bigger, in case `f` used the scratch
+    // space itself.
+    SCRATCH.with(|s| {
+        let other = s.take();
+        s.set(if other.len() > buf.len() { other } else { buf })
+    });
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_scratch() {
+        let first = scratch(8, |v: &[Cell<u64>]| {
+            assert!(v.iter().all(|c| c.get() == 0));
+            v[7].set(7);
+            v.as_ptr() as usize
+        });
+        // reused, and re-initialised
+        let second = scratch(3, |v: &[Cell<u8>]| {
+            assert_eq!(v.len(), 3);
+            assert!(v.iter().all(|c| c.get() == 0));
+            v.as_ptr() as usize
+        });
+        assert_eq!(first, second);
+
+        // nested
+        let r = scratch(2, |a: &[Cell<i32>]| {
+            a[0].set(1);
+            scratch(2, |b: &[Cell<i32>]| {
+                b[0].set(2);
+                assert!(a.as_ptr() != b.as_ptr());
+            });
+            a[0].get()
+        });
+        assert_eq!(r, 1);
+
+        #[derive(Copy, Clone, Default)]
+        #[repr(align(64))]
+        struct Big(u8);
+        scratch(2, |v: &[Cell<Big>]| {
+            assert_eq!(v.as_ptr() as usize % 64, 0);
+            v[1].set(Big(1));
+            assert_eq!(v[1].get().0 + v[0].get().0, 1);
+        });
+    }
+}
@@ -0,0 +1,173 @@
+//! Aliasing for secret data, which is wiped when the aliasing ends.
+//!
+//! The guards returned by `one` and `slice` hand out `SecretCell`s,
+//! which behave like `Cell` but whose `Debug` implementation never
+//! prints the contents. When the guard is dropped, every element is
+//! overwritten with `T::default()` (zero, for the primitive integer
+//! types) using volatile writes, so that the optimiser cannot remove
+//! the wipe even though nothing reads the memory afterwards.
+//!
+//! # Examples
+//!
+//! ```rust
+//! let mut key = [0x13u8; 16];
+//!
+//! {
+//!     let k = alias::secret::slice(&mut key);
+//!     let (a, b) = (&k[0], &k[1]);
+//!     a.set(b.get() ^ 0xFF);
+//!     assert_eq!(k[0].get(), 0xEC);
+//!
+//!     // the contents never show up in debug output
+//!     assert_eq!(format!("{:?}", k[0]), "SecretCell { .. }");
+//! }
+//!
+//! assert_eq!(key, [0; 16]);
+//! ```
+
+use std::fmt;
+use std::mem;
+use std::ops::Deref;
+use std::ptr;
+use std::cell::Cell;
+use std::sync::atomic::{self, Ordering};
+
+/// A `Cell` that hides its contents from `Debug`.
+#[repr(transparent)]
+pub struct SecretCell<T> {
+    cell: Cell<T>,
+}
+
+impl<T: Copy> SecretCell<T> {
+    /// Returns a copy of the contained value.
+    pub fn get(&self) -> T {
+        self.cell.get()
+    }
+
+    /// Sets the contained value.
+    pub fn set(&self, val: T) {
+        self.cell.set(val)
+    }
+}
+
+impl<T> fmt::Debug for SecretCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SecretCell { .. }")
+    }
+}
+
+/// A guard giving aliased access to a single secret value, wiping it
+/// on drop.
+///
+/// Created by `alias::secret::one`.
+pub struct Secret<'a, T: Copy + Default + 'a> {
+    cell: &'a SecretCell<T>,
+}
+
+/// A guard giving aliased access to a slice of secret values, wiping
+/// them on drop.
+///
+/// Created by `alias::secret::slice`.
+pub struct SecretSlice<'a, T: Copy + Default + 'a> {
+    cells: &'a [SecretCell<T>],
+}
+
+/// Allow the secret `data` to be mutated while aliased, wiping it
+/// when the returned guard is dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut nonce = 0xDEAD_BEEF_u64;
+///
+/// {
+///     let n = alias::secret::one(&mut nonce);
+///     n.set(n.get() + 1);
+///     assert_eq!(n.get(), 0xDEAD_BEF0);
+/// }
+///
+/// assert_eq!(nonce, 0);
+/// ```
+pub fn one<T: Copy + Default>(data: &mut T) -> Secret<'_, T> {
+    Secret { cell: unsafe { mem::transmute::<&mut T, &SecretCell<T>>(data) } }
+}
+
+/// Allow the contents of the secret slice `data` to be mutated while
+/// aliased, wiping them when the returned guard is dropped.
+pub fn slice<T: Copy + Default>(data: &mut [T]) -> SecretSlice<'_, T> {
+    SecretSlice { cells: unsafe { mem::transmute::<&mut [T], &[SecretCell<T>]>(data) } }
+}
+
+fn wipe<T: Copy + Default>(cells: &[SecretCell<T>]) {
+    for c in cells {
+        unsafe { ptr::write_volatile(c.cell.as_ptr(), T::default()) }
+    }
+    atomic::compiler_fence(Ordering::SeqCst);
+}
+
+impl<'a, T: Copy + Default> Deref for Secret<'a, T> {
+    type Target = SecretCell<T>;
+    fn deref(&self) -> &SecretCell<T> {
+        self.cell
+    }
+}
+
+impl<'a, T: Copy + Default> Drop for Secret<'a, T> {
+    fn drop(&mut self) {
+        wipe(::std::slice::from_ref(self.cell))
+    }
+}
+
+impl<'a, T: Copy + Default> fmt::Debug for Secret<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Secret { .. }")
+    }
+}
+
+impl<'a, T: Copy + Default> Deref for SecretSlice<'a, T> {
+    type Target = [SecretCell<T>];
+    fn deref(&self) -> &[SecretCell<T>] {
+        self.cells
+    }
+}
+
+impl<'a, T: Copy + Default> Drop for SecretSlice<'a, T> {
+    fn drop(&mut self) {
+        wipe(self.cells)
+    }
+}
+
+impl<'a, T: Copy + Default> fmt::Debug for SecretSlice<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SecretSlice {{ len: {}, .. }}", self.cells.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_secret() {
+        let mut x = 0x1234_u32;
+        let mut y = [1u8, 2, 3];
+
+        {
+            let a = one(&mut x);
+            let b = slice(&mut y);
+            a.set(a.get() + u32::from(b[2].get()));
+            b[0].set(b[1].get());
+            assert_eq!(a.get(), 0x1237);
+            assert_eq!(b[0].get(), 2);
+
+            assert_eq!(format!("{:?}", a), "Secret { .. }");
+            assert_eq!(format!("{:?}", *a), "SecretCell { .. }");
+            assert_eq!(format!("{:?}", b), "SecretSlice { len: 3, .. }");
+            assert_eq!(format!("{:?}", &b[..]),
+                       "[SecretCell { .. }, SecretCell { .. }, SecretCell { .. }]");
+        }
+
+        assert_eq!(x, 0);
+        assert_eq!(y, [0; 3]);
+    }
+}
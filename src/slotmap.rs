@@ -0,0 +1,227 @@
+use std::cell::Cell;
+use std::fmt;
+use std::ptr;
+
+use slice;
+
+/// A handle to a value in a `CellSlotMap`.
+///
+/// Keys are invalidated when their value is removed, even if the slot
+/// is later reused for another value.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct SlotKey {
+    index: u32,
+    generation: u32,
+}
+
+/// A generational slot map over borrowed storage, where every
+/// operation works through shared references.
+///
+/// Each slot of the storage is a `(generation, value)` pair. An odd
+/// generation marks an occupied slot, and an even one a free slot;
+/// the generation is bumped on every insertion and removal, so a key
+/// only matches the exact value it was created for.
+///
+/// Rather than let a generation wrap around and match stale keys
+/// again, a slot is retired and never reused once it reaches
+/// `u32::MAX - 1`, after about 2³¹ insertions. A map whose slots are
+/// all retired or occupied is full.
+///
+/// Inserting searches for a free slot starting just after the most
+/// recently used one, so is `O(capacity)` in the worst case but
+/// typically fast when the map isn't nearly full.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::CellSlotMap;
+///
+/// let mut storage = [(0, 0.0); 4];
+/// let map = CellSlotMap::new(&mut storage);
+///
+/// let a = map.insert(1.5).unwrap();
+/// let b = map.insert(2.5).unwrap();
+///
+/// let cell = map.get(a).unwrap();
+/// cell.set(cell.get() * 2.0);
+/// assert_eq!(map.get(a).unwrap().get(), 3.0);
+///
+/// assert_eq!(map.remove(b), Some(2.5));
+/// assert!(map.get(b).is_none());
+///
+/// // the slot is reused, but the old key stays dead
+/// let c = map.insert(9.0).unwrap();
+/// assert!(map.get(b).is_none());
+/// assert_eq!(map.get(c).unwrap().get(), 9.0);
+/// ```
+pub struct CellSlotMap<'a, T: 'a> {
+    slots: &'a [Cell<(u32, T)>],
+    next: Cell<usize>,
+    len: Cell<usize>,
+}
+
+/// The generation of a slot that can't be used again.
+const RETIRED: u32 = u32::MAX - 1;
+
+fn generation_of<T>(slot: &Cell<(u32, T)>) -> &Cell<u32> {
+    unsafe { &*(ptr::addr_of!((*slot.as_ptr()).0) as *const Cell<u32>) }
+}
+fn value_of<T>(slot: &Cell<(u32, T)>) -> &Cell<T> {
+    unsafe { &*(ptr::addr_of!((*slot.as_ptr()).1) as *const Cell<T>) }
+}
+
+impl<'a, T: Copy> CellSlotMap<'a, T> {
+    /// Create an empty slot map using `storage` for its slots.
+    ///
+    /// Any slots marked as occupied are freed, bumping their
+    /// generation (or retiring them, if it would wrap), so keys from a
+    /// previous map over the same storage are invalid in this one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `storage` has more than `u32::MAX` slots.
+    pub fn new(storage: &'a mut [(u32, T)]) -> CellSlotMap<'a, T> {
+        assert!(storage.len() <= u32::MAX as usize, "CellSlotMap::new: too many slots");
+        for &mut (ref mut gen, _) in storage.iter_mut() {
+            if *gen == u32::MAX {
+                *gen = RETIRED;
+            } else if *gen & 1 == 1 {
+                *gen += 1;
+            }
+        }
+        CellSlotMap { slots: slice(storage), next: Cell::new(0), len: Cell::new(0) }
+    }
+
+    /// The number of slots.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// The number of values.
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+
+    /// Whether there are no values.
+    pub fn is_empty(&self) -> bool {
+        self.len.get() == 0
+    }
+
+    /// Store `value` in a free slot, returning its key, or `None` if
+    /// every slot is occupied or retired.
+    pub fn insert(&self, value: T) -> Option<SlotKey> {
+        let n = self.slots.len();
+        if self.len.get() == n {
+            return None
+        }
+        let start = self.next.get();
+        for i in (start..n).chain(0..start) {
+            let gen = generation_of(&self.slots[i]);
+            if gen.get() & 1 == 0 && gen.get() != RETIRED {
+                // at most `RETIRED - 1`, so occupied slots can always
+                // be freed without wrapping.
+                let g = gen.get() + 1;
+                gen.set(g);
+                value_of(&self.slots[i]).set(value);
+                self.next.set(if i + 1 == n { 0 } else { i + 1 });
+                self.len.set(self.len.get() + 1);
+                return Some(SlotKey { index: i as u32, generation: g })
+            }
+        }
+        None
+    }
+
+    /// The value for `key`, if it is still present.
+    pub fn get(&self, key: SlotKey) -> Option<&'a Cell<T>> {
+        let slot = self.slots.get(key.index as usize)?;
+        if generation_of(slot).get() == key.generation {
+            Some(value_of(slot))
+        } else {
+            None
+        }
+    }
+
+    /// Whether `key` refers to a value that is still present.
+    pub fn contains(&self, key: SlotKey) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Remove and return the value for `key`, if it is still present.
+    pub fn remove(&self, key: SlotKey) -> Option<T> {
+        let v = self.get(key)?.get();
+        let gen = generation_of(&self.slots[key.index as usize]);
+        gen.set(gen.get() + 1);
+        self.len.set(self.len.get() - 1);
+        Some(v)
+    }
+
+    /// Iterate over the keys and values of every occupied slot.
+    pub fn iter(&self) -> impl Iterator<Item = (SlotKey, &'a Cell<T>)> {
+        self.slots.iter().enumerate().filter_map(|(i, slot)| {
+            let g = generation_of(slot).get();
+            if g & 1 == 1 {
+                Some((SlotKey { index: i as u32, generation: g }, value_of(slot)))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+impl<'a, T: Copy + fmt::Debug> fmt::Debug for CellSlotMap<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter().map(|(k, v)| (k, v.get()))).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_slotmap() {
+        let mut x = [(0u32, 'x'); 3];
+        let (a, c);
+        {
+            let m = CellSlotMap::new(&mut x);
+            a = m.insert('a').unwrap();
+            let b = m.insert('b').unwrap();
+            c = m.insert('c').unwrap();
+            assert_eq!(m.insert('d'), None);
+            assert_eq!(m.len(), 3);
+
+            assert_eq!(m.remove(b), Some('b'));
+            assert_eq!(m.remove(b), None);
+            let d = m.insert('d').unwrap();
+            assert!(d != b);
+            assert_eq!(m.get(b), None);
+
+            m.get(a).unwrap().set('A');
+            assert_eq!(m.iter().map(|(_, v)| v.get()).collect::<String>(), "Adc");
+            assert_eq!(m.len(), 3);
+        }
+        assert_eq!(x, [(1, 'A'), (3, 'd'), (1, 'c')]);
+
+        // reusing the storage frees everything and invalidates old keys
+        let m = CellSlotMap::new(&mut x);
+        assert!(m.is_empty());
+        assert!(!m.contains(a));
+        let e = m.insert('e').unwrap();
+        assert!(e != a && e != c);
+    }
+
+    #[test]
+    fn retire() {
+        // about to reach the last generation, and one left occupied
+        // with it
+        let mut x = [(RETIRED - 2, 0u8), (u32::MAX, 0)];
+        {
+            let m = CellSlotMap::new(&mut x);
+            let k = m.insert(1).unwrap();
+            assert_eq!(m.remove(k), Some(1));
+            assert_eq!(m.insert(2), None);
+            assert!(m.is_empty() && !m.contains(k));
+        }
+        assert_eq!(x, [(RETIRED, 1), (RETIRED, 0)]);
+    }
+}
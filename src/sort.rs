@@ -0,0 +1,260 @@
+//! Sorting aliased slices.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use alias::sort;
+//!
+//! let mut names = ["delta", "Alpha", "charlie", "Bravo"];
+//! sort::sort_by_cached_key(alias::slice(&mut names), |s| s.to_lowercase());
+//! assert_eq!(names, ["Alpha", "Bravo", "charlie", "delta"]);
+//! assert!(sort::is_sorted_by_key(alias::slice(&mut names), |s| s.to_lowercase()));
+//! ```
+
+use std::cell::Cell;
+use std::fmt;
+use std::mem;
+
+use permute::rotate_cycle;
+use CellSliceExt;
+
+/// Sort `data` by the key `f` computes for each element, calling `f`
+/// exactly once per element.
+///
+/// The sort is stable. The keys and a permutation are computed into
+/// scratch space and then applied through the cells, so no copy of
+/// the elements themselves is made; this is worthwhile when `f` is
+/// expensive, or the elements are large.
+///
+/// `f` may read `data` (through other references), but if it writes
+/// to it, the elements end up in an unspecified order.
+pub fn sort_by_cached_key<T, K, F>(data: &[Cell<T>], mut f: F)
+    where T: Copy, K: Ord, F: FnMut(T) -> K
+{
+    let mut keys: Vec<(K, usize)> = data.iter().enumerate().map(|(i, c)| (f(c.get()), i)).collect();
+    keys.sort_unstable();
+    let mut perm: Vec<usize> = keys.into_iter().map(|(_, i)| i).collect();
+
+    // follow each cycle, marking elements as done by making them fixed
+    // points of `perm`.
+    for start in 0..perm.len() {
+        if perm[start] != start {
+            rotate_cycle(data, start, |j| mem::replace(&mut perm[j], j));
+        }
+    }
+}
+
+/// Sort `data` in place, without allocating.
+///
+/// The sort is an unstable heapsort working through the cells, so it
+/// is `O(n log n)` and other references to `data` stay valid, though
+/// they'll see the elements move.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut x = [5, 1, 4, 1, 3];
+/// let cells = alias::slice(&mut x);
+/// let first = &cells[0];
+///
+/// alias::sort::sort_unstable_copy(cells);
+/// assert_eq!(first.get(), 1);
+/// assert_eq!(x, [1, 1, 3, 4, 5]);
+/// ```
+pub fn sort_unstable_copy<T: Copy + Ord>(data: &[Cell<T>]) {
+    fn sift_down<T: Copy + Ord>(data: &[Cell<T>], mut root: usize) {
+        loop {
+            let mut child = 2 * root + 1;
+            if child >= data.len() {
+                break
+            }
+            if child + 1 < data.len() && data[child].get() < data[child + 1].get() {
+                child += 1;
+            }
+            if data[root].get() >= data[child].get() {
+                break
+            }
+            data[root].swap(&data[child]);
+            root = child;
+        }
+    }
+
+    for i in (0..data.len() / 2).rev() {
+        sift_down(data, i);
+    }
+    for end in (1..data.len()).rev() {
+        data[0].swap(&data[end]);
+        sift_down(&data[..end], 0);
+    }
+}
+
+/// Sort `data` by the key `f` computes for each element.
+///
+/// The sort is stable. It sorts a copy of the elements and then
+/// writes them back, so `f` may read `data` (through other
+/// references), but if it writes to it, the writes are lost.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut x = [(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd')];
+/// alias::sort::sort_by_key_copy(alias::slice(&mut x), |p| p.0);
+/// assert_eq!(x, [(1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')]);
+/// ```
+pub fn sort_by_key_copy<T, K, F>(data: &[Cell<T>], f: F)
+    where T: Copy, K: Ord, F: FnMut(&T) -> K
+{
+    let mut copy = data.to_plain_vec();
+    copy.sort_by_key(f);
+    data.copy_from(&copy);
+}
+
+/// Whether the elements of `data` are in non-decreasing order.
+pub fn is_sorted<T: Copy + PartialOrd>(data: &[Cell<T>]) -> bool {
+    is_sorted_by(data, |a, b| a <= b)
+}
+
+/// Whether `in_order` holds for every pair of adjacent elements of
+/// `data`.
+pub fn is_sorted_by<T, F>(data: &[Cell<T>], mut in_order: F) -> bool
+    where T: Copy, F: FnMut(T, T) -> bool
+{
+    data.windows(2).all(|w| in_order(w[0].get(), w[1].get()))
+}
+
+/// Whether the keys of the elements of `data` are in non-decreasing
+/// order.
+pub fn is_sorted_by_key<T, K, F>(data: &[Cell<T>], mut f: F) -> bool
+    where T: Copy, K: PartialOrd, F: FnMut(T) -> K
+{
+    is_sorted_by(data, |a, b| f(a) <= f(b))
+}
+
+/// An iterator over the maximal runs of equal adjacent elements of
+/// `data`.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut x = [1, 1, 2, 3, 3, 3, 1];
+/// let lengths: Vec<_> = alias::sort::runs(alias::slice(&mut x))
+///     .map(|run| (run[0].get(), run.len()))
+///     .collect();
+/// assert_eq!(lengths, [(1, 2), (2, 1), (3, 3), (1, 1)]);
+/// ```
+pub fn runs<T: Copy + PartialEq>(data: &[Cell<T>]) -> GroupBy<'_, T, fn(T, T) -> bool> {
+    group_by(data, |a, b| a == b)
+}
+
+/// An iterator over the maximal sub-slices of `data` where `related`
+/// holds for every pair of adjacent elements.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut x = [1, 2, 3, 2, 3, 4, 0];
+/// let ascending: Vec<usize> = alias::sort::group_by(alias::slice(&mut x), |a, b| a < b)
+///     .map(|run| run.len())
+///     .collect();
+/// assert_eq!(ascending, [3, 3, 1]);
+/// ```
+pub fn group_by<T, F>(data: &[Cell<T>], related: F) -> GroupBy<'_, T, F>
+    where T: Copy, F: FnMut(T, T) -> bool
+{
+    GroupBy { data, related }
+}
+
+/// The iterator returned by `runs` and `group_by`.
+pub struct GroupBy<'a, T: 'a, F> {
+    data: &'a [Cell<T>],
+    related: F,
+}
+
+impl<'a, T, F> Iterator for GroupBy<'a, T, F>
+    where T: Copy, F: FnMut(T, T) -> bool
+{
+    type Item = &'a [Cell<T>];
+
+    fn next(&mut self) -> Option<&'a [Cell<T>]> {
+        if self.data.is_empty() {
+            return None
+        }
+        let mut len = 1;
+        while len < self.data.len()
+            && (self.related)(self.data[len - 1].get(), self.data[len].get()) {
+            len += 1;
+        }
+        let (run, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(run)
+    }
+}
+
+impl<'a, T, F> fmt::Debug for GroupBy<'a, T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("GroupBy").field("remaining", &self.data.len()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slice;
+
+    #[test]
+    fn smoke_sort_copy() {
+        let mut x: Vec<u32> = (0..100).map(|i| (i * 37 + 11) % 41).collect();
+        let mut expected = x.clone();
+        expected.sort();
+        sort_unstable_copy(slice(&mut x));
+        assert_eq!(x, expected);
+        sort_unstable_copy(slice(&mut x[..1]));
+        sort_unstable_copy::<u8>(&[]);
+
+        let mut y = [(3, 0), (1, 1), (3, 2), (0, 3)];
+        {
+            let c = slice(&mut y);
+            // the key can read the data
+            sort_by_key_copy(c, |p| c[p.1].get().0);
+        }
+        assert_eq!(y, [(0, 3), (1, 1), (3, 0), (3, 2)]);
+    }
+
+    #[test]
+    fn smoke_cached_key() {
+        let mut x = [(3, 'a'), (1, 'b'), (3, 'c'), (0, 'd'), (1, 'e'), (2, 'f')];
+        let mut calls = 0;
+        sort_by_cached_key(slice(&mut x), |(k, _)| { calls += 1; k });
+        assert_eq!(calls, 6);
+        // stable
+        assert_eq!(x, [(0, 'd'), (1, 'b'), (1, 'e'), (2, 'f'), (3, 'a'), (3, 'c')]);
+
+        let mut y: Vec<u32> = (0..100).map(|i| (i * 37) % 101).collect();
+        sort_by_cached_key(slice(&mut y), std::cmp::Reverse);
+        assert!(y.windows(2).all(|w| w[0] > w[1]));
+
+        sort_by_cached_key::<u8, u8, _>(&[], |v| v);
+    }
+
+    #[test]
+    fn smoke_sorted_runs() {
+        let mut x = [1, 2, 2, 5, 4];
+        let c = slice(&mut x);
+        assert!(!is_sorted(c));
+        assert!(is_sorted(&c[..4]));
+        assert!(is_sorted::<u8>(&[]));
+        assert!(is_sorted_by(&c[3..], |a, b| a > b));
+        assert!(is_sorted_by_key(c, |v| v / 4));
+
+        let runs: Vec<_> = runs(c).map(|r| r.len()).collect();
+        assert_eq!(runs, [1, 2, 1, 1]);
+        // a run is live, and spans the group at the time it was found
+        let mut it = group_by(c, |a, b| a <= b);
+        let first = it.next().unwrap();
+        assert_eq!(first.len(), 4);
+        first[3].set(0);
+        assert_eq!(it.next().unwrap().len(), 1);
+        assert!(it.next().is_none());
+        assert_eq!(c[3].get(), 0);
+    }
+}
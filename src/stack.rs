@@ -0,0 +1,122 @@
+use std::cell::Cell;
+use std::fmt;
+
+use slice;
+
+/// A fixed-capacity stack over borrowed storage, where every operation
+/// works through shared references.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::CellStack;
+///
+/// let mut storage = [0; 16];
+/// let undo = CellStack::new(&mut storage);
+/// let (editor, menu) = (&undo, &undo);
+///
+/// editor.push(1).unwrap();
+/// editor.push(2).unwrap();
+/// assert_eq!(menu.peek(), Some(2));
+/// assert_eq!(menu.pop(), Some(2));
+/// assert_eq!(editor.len(), 1);
+/// ```
+pub struct CellStack<'a, T: 'a> {
+    slots: &'a [Cell<T>],
+    len: Cell<usize>,
+}
+
+impl<'a, T: Copy> CellStack<'a, T> {
+    /// Create an empty stack using `storage` to hold the elements; its
+    /// length is the capacity of the stack.
+    pub fn new(storage: &'a mut [T]) -> CellStack<'a, T> {
+        CellStack { slots: slice(storage), len: Cell::new(0) }
+    }
+
+    /// The maximum number of elements.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// The number of elements.
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+
+    /// Whether there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len.get() == 0
+    }
+
+    /// Push `value` onto the top of the stack, or return it if the
+    /// stack is full.
+    pub fn push(&self, value: T) -> Result<(), T> {
+        let len = self.len.get();
+        match self.slots.get(len) {
+            Some(c) => {
+                c.set(value);
+                self.len.set(len + 1);
+                Ok(())
+            }
+            None => Err(value),
+        }
+    }
+
+    /// Remove and return the top element, if any.
+    pub fn pop(&self) -> Option<T> {
+        let len = self.len.get().checked_sub(1)?;
+        self.len.set(len);
+        Some(self.slots[len].get())
+    }
+
+    /// The top element, if any.
+    pub fn peek(&self) -> Option<T> {
+        self.len.get().checked_sub(1).map(|i| self.slots[i].get())
+    }
+
+    /// Remove every element.
+    pub fn clear(&self) {
+        self.len.set(0)
+    }
+
+    /// The elements currently on the stack, from bottom to top.
+    ///
+    /// The returned slice doesn't change length if elements are pushed
+    /// or popped later.
+    pub fn as_slice(&self) -> &'a [Cell<T>] {
+        &self.slots[..self.len.get()]
+    }
+}
+
+impl<'a, T: Copy + fmt::Debug> fmt::Debug for CellStack<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.as_slice().iter().map(Cell::get)).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_stack() {
+        let mut x = [0; 3];
+        {
+            let s = CellStack::new(&mut x);
+            assert_eq!(s.pop(), None);
+            assert_eq!(s.peek(), None);
+            assert_eq!(s.push(1), Ok(()));
+            assert_eq!(s.push(2), Ok(()));
+            assert_eq!(s.push(3), Ok(()));
+            assert_eq!(s.push(4), Err(4));
+            assert_eq!(format!("{:?}", s), "[1, 2, 3]");
+
+            assert_eq!(s.pop(), Some(3));
+            s.as_slice()[0].set(10);
+            assert_eq!(s.pop(), Some(2));
+            assert_eq!(s.peek(), Some(10));
+            assert_eq!(s.push(5), Ok(()));
+        }
+        assert_eq!(x, [10, 5, 3]);
+    }
+}
@@ -0,0 +1,115 @@
+use std::cell::{Cell, UnsafeCell};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Storage for a `static` that can be claimed once as a
+/// `&'static Cell<T>`.
+///
+/// A `Cell` can't be put in a `static` directly, since it isn't
+/// `Sync`. This type is, because only the first call to `take` gets
+/// the `Cell`, and a `&Cell` can't leave the thread that claimed it.
+/// The `static_cell!` macro declares one of these and claims it.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::StaticCell;
+///
+/// static TICKS: StaticCell<u32> = StaticCell::new(0);
+///
+/// let ticks = TICKS.take().unwrap();
+/// let on_timer = || ticks.set(ticks.get() + 1);
+/// on_timer();
+/// on_timer();
+/// assert_eq!(ticks.get(), 2);
+///
+/// assert!(TICKS.take().is_none());
+/// ```
+pub struct StaticCell<T> {
+    taken: AtomicBool,
+    value: UnsafeCell<T>,
+}
+
+// the value is only reachable by the single thread that claims it.
+unsafe impl<T: Send> Sync for StaticCell<T> {}
+
+impl<T> StaticCell<T> {
+    /// Create the storage, holding `value`.
+    pub const fn new(value: T) -> StaticCell<T> {
+        StaticCell { taken: AtomicBool::new(false), value: UnsafeCell::new(value) }
+    }
+
+    /// Claim the value, or return `None` if it has already been
+    /// claimed.
+    pub fn take(&'static self) -> Option<&'static Cell<T>> {
+        if self.taken.swap(true, Ordering::AcqRel) {
+            return None
+        }
+        // this is the only reference to the value that will ever
+        // exist.
+        Some(::one(unsafe { &mut *self.value.get() }))
+    }
+
+    /// Whether the value has been claimed.
+    pub fn is_taken(&self) -> bool {
+        self.taken.load(Ordering::Acquire)
+    }
+}
+
+impl<T> fmt::Debug for StaticCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StaticCell").field("taken", &self.is_taken()).finish()
+    }
+}
+
+/// Declare a `static` holding `$init` and claim it as a
+/// `&'static Cell<$ty>`.
+///
+/// `static_cell!(T = init)` evaluates to `Some(cell)` the first time
+/// it runs and `None` after that, like a singleton. No `static mut`
+/// or `unsafe` is needed at the use site.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[macro_use] extern crate alias;
+/// # fn main() {
+/// fn setup() -> Option<&'static std::cell::Cell<[u8; 4]>> {
+///     static_cell!([u8; 4] = [0; 4])
+/// }
+///
+/// let buffer = setup().unwrap();
+/// let fill = |b: u8| buffer.set([b; 4]);
+/// fill(7);
+/// assert_eq!(buffer.get(), [7; 4]);
+///
+/// assert!(setup().is_none());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! static_cell {
+    ($ty: ty = $init: expr) => {{
+        static CELL: $crate::StaticCell<$ty> = $crate::StaticCell::new($init);
+        CELL.take()
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_static_cell() {
+        let claims = (0..3).map(|_| static_cell!(Option<&'static str> = None)).collect::<Vec<_>>();
+        assert_eq!(claims.iter().filter(|c| c.is_some()).count(), 1);
+
+        let c = claims[0].unwrap();
+        c.set(Some("x"));
+        assert_eq!(c.take(), Some("x"));
+
+        static S: StaticCell<i32> = StaticCell::new(1);
+        assert_eq!(format!("{:?}", S), "StaticCell { taken: false }");
+        S.take().unwrap().set(5);
+        assert!(S.is_taken() && S.take().is_none());
+    }
+}
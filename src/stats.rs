@@ -0,0 +1,131 @@
+//! Summary statistics over aliased numeric slices.
+//!
+//! Sums are accumulated in a wider type than the elements (see
+//! `Numeric::Acc`), so that summing many small integers doesn't
+//! overflow, and the floating point reductions accumulate in `f64`.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use alias::stats;
+//!
+//! let mut x = [200u8, 100, 250, 50];
+//! let x = alias::slice(&mut x);
+//!
+//! assert_eq!(stats::sum(x), 600u64);
+//! assert_eq!(stats::mean(x), Some(150.0));
+//! assert_eq!(stats::variance(x), Some(6250.0));
+//! assert_eq!(stats::minmax(x), Some((50, 250)));
+//! ```
+
+use std::cell::Cell;
+use std::ops::Add;
+
+/// Numeric types that can be summarised by this module.
+pub trait Numeric: Copy {
+    /// The type in which sums are accumulated.
+    type Acc: Copy + Add<Output = Self::Acc>;
+
+    /// The additive identity of the accumulator.
+    fn zero() -> Self::Acc;
+
+    /// Convert the value into the accumulator type.
+    fn widen(self) -> Self::Acc;
+
+    /// Convert the value to a float, possibly losing precision.
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! numeric_impl {
+    ($($T: ty => $Acc: ty),*) => {
+        $(
+            impl Numeric for $T {
+                type Acc = $Acc;
+                fn zero() -> $Acc { 0 as $Acc }
+                fn widen(self) -> $Acc { self as $Acc }
+                fn to_f64(self) -> f64 { self as f64 }
+            }
+        )*
+    }
+}
+
+numeric_impl! {
+    u8 => u64, u16 => u64, u32 => u64, u64 => u128, usize => u128,
+    i8 => i64, i16 => i64, i32 => i64, i64 => i128, isize => i128,
+    f32 => f64, f64 => f64
+}
+
+/// The sum of every element, computed in the wider accumulator type.
+pub fn sum<T: Numeric>(data: &[Cell<T>]) -> T::Acc {
+    data.iter().fold(T::zero(), |acc, c| acc + c.get().widen())
+}
+
+/// The arithmetic mean of the elements, or `None` if there are none.
+pub fn mean<T: Numeric>(data: &[Cell<T>]) -> Option<f64> {
+    mean_variance(data).map(|(m, _)| m)
+}
+
+/// The population variance of the elements, or `None` if there are
+/// none.
+///
+/// This uses Welford's algorithm, which is numerically stable even
+/// when the variance is small relative to the mean.
+pub fn variance<T: Numeric>(data: &[Cell<T>]) -> Option<f64> {
+    mean_variance(data).map(|(_, v)| v)
+}
+
+fn mean_variance<T: Numeric>(data: &[Cell<T>]) -> Option<(f64, f64)> {
+    if data.is_empty() {
+        return None
+    }
+    let mut mean = 0.0;
+    let mut m2 = 0.0;
+    for (i, c) in data.iter().enumerate() {
+        let x = c.get().to_f64();
+        let delta = x - mean;
+        mean += delta / (i + 1) as f64;
+        m2 += delta * (x - mean);
+    }
+    Some((mean, m2 / data.len() as f64))
+}
+
+/// The smallest and largest elements, or `None` if there are none.
+///
+/// Elements that are unordered with respect to the current extremes
+/// (such as NaN) are skipped, unless they are the first element.
+pub fn minmax<T: Copy + PartialOrd>(data: &[Cell<T>]) -> Option<(T, T)> {
+    let mut iter = data.iter().map(Cell::get);
+    let first = iter.next()?;
+    Some(iter.fold((first, first), |(lo, hi), x| {
+        (if x < lo { x } else { lo }, if x > hi { x } else { hi })
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slice;
+
+    #[test]
+    fn smoke_stats() {
+        let mut x = [i32::MAX, i32::MAX, i32::MIN, -1];
+        let x = slice(&mut x);
+        assert_eq!(sum(x), i32::MAX as i64 - 2);
+        assert_eq!(minmax(x), Some((i32::MIN, i32::MAX)));
+
+        let mut y = [1.0f32, 2.0, 3.0, 4.0];
+        let y = slice(&mut y);
+        assert_eq!(sum(y), 10.0f64);
+        assert_eq!(mean(y), Some(2.5));
+        assert_eq!(variance(y), Some(1.25));
+
+        y[0].set(5.0);
+        assert_eq!(minmax(y), Some((2.0, 5.0)));
+
+        let empty: &[Cell<u8>] = &[];
+        assert_eq!(sum(empty), 0);
+        assert_eq!(mean(empty), None);
+        assert_eq!(variance(empty), None);
+        assert_eq!(minmax(empty), None);
+    }
+}
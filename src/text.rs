@@ -0,0 +1,416 @@
+use std::cell::Cell;
+use std::fmt;
+use std::str;
+
+use {one, slice};
+use vec::with_taken;
+
+/// An aliased view of a string, that only allows modifications that
+/// keep it valid UTF-8.
+///
+/// Created by `alias::cell_str`.
+#[derive(Clone, Copy)]
+pub struct CellStr<'a> {
+    bytes: &'a [Cell<u8>],
+}
+
+/// Allow the string `data` to be mutated while aliased, in ways that
+/// keep it valid UTF-8.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut s = String::from("  hello, wörld ");
+///
+/// {
+///     let a = alias::cell_str(&mut s);
+///     let b = a;
+///
+///     a.make_ascii_uppercase();
+///     assert_eq!(b.replace_byte(b',', b';'), 1);
+///     assert_eq!(b.trim_in_place_with_fill(b'.'), 13);
+/// }
+///
+/// assert_eq!(s, "HELLO; WöRLD...");
+/// ```
+pub fn cell_str(data: &mut str) -> CellStr<'_> {
+    // only ASCII bytes are ever written over ASCII bytes, or whole
+    // valid UTF-8 sequences moved, so the string stays valid.
+    let bytes = unsafe { data.as_bytes_mut() };
+    CellStr { bytes: slice(bytes) }
+}
+
+impl<'a> CellStr<'a> {
+    /// The length of the string, in bytes.
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Whether the string is empty.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Read the byte at index `i`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds.
+    pub fn byte(&self, i: usize) -> u8 {
+        self.bytes[i].get()
+    }
+
+    /// Replace the ASCII character at byte index `i` with the ASCII
+    /// character `b`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds, or either the current byte or
+    /// `b` is not ASCII.
+    pub fn set_ascii(&self, i: usize, b: u8) {
+        let c = &self.bytes[i];
+        assert!(c.get().is_ascii() && b.is_ascii(),
+                "CellStr::set_ascii: non-ASCII byte");
+        c.set(b)
+    }
+
+    /// Copy the current contents into a new `String`.
+    #[allow(clippy::inherent_to_string_shadow_display)]
+    pub fn to_string(&self) -> String {
+        self.with_str(|s| s.to_owned())
+    }
+
+    fn with_str<R, F: FnOnce(&str) -> R>(&self, f: F) -> R {
+        let bytes: Vec<u8> = self.bytes.iter().map(Cell::get).collect();
+        f(unsafe { str::from_utf8_unchecked(&bytes) })
+    }
+
+    /// Convert every ASCII lowercase letter to uppercase, leaving
+    /// everything else untouched.
+    pub fn make_ascii_uppercase(&self) {
+        for c in self.bytes {
+            c.set(c.get().to_ascii_uppercase())
+        }
+    }
+
+    /// Convert every ASCII uppercase letter to lowercase, leaving
+    /// everything else untouched.
+    pub fn make_ascii_lowercase(&self) {
+        for c in self.bytes {
+            c.set(c.get().to_ascii_lowercase())
+        }
+    }
+
+    /// Replace every occurrence of the ASCII character `old` with the
+    /// ASCII character `new`, returning the number replaced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either `old` or `new` is not ASCII.
+    pub fn replace_byte(&self, old: u8, new: u8) -> usize {
+        assert!(old.is_ascii() && new.is_ascii(), "CellStr::replace_byte: non-ASCII byte");
+        let mut count = 0;
+        for c in self.bytes {
+            if c.get() == old {
+                c.set(new);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Remove leading and trailing whitespace (as for `str::trim`) by
+    /// moving the remaining text to the start of the string, and
+    /// padding the end with the ASCII character `fill`. Returns the
+    /// length of the trimmed text.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fill` is not ASCII.
+    pub fn trim_in_place_with_fill(&self, fill: u8) -> usize {
+        assert!(fill.is_ascii(), "CellStr::trim_in_place_with_fill: non-ASCII fill");
+        let (start, len) = self.with_str(|s| {
+            let t = s.trim();
+            (t.as_ptr() as usize - s.as_ptr() as usize, t.len())
+        });
+        for i in 0..len {
+            self.bytes[i].set(self.bytes[start + i].get());
+        }
+        for c in &self.bytes[len..] {
+            c.set(fill);
+        }
+        len
+    }
+}
+
+impl<'a> fmt::Debug for CellStr<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.with_str(|s| fmt::Debug::fmt(s, f))
+    }
+}
+
+impl<'a> fmt::Display for CellStr<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.with_str(|s| fmt::Display::fmt(s, f))
+    }
+}
+
+/// A byte of an aliased string, that can only be changed while it
+/// is, and to, an ASCII character.
+///
+/// Created by `alias::str_ascii`.
+#[repr(transparent)]
+pub struct AsciiCell {
+    byte: Cell<u8>,
+}
+
+/// Allow the ASCII characters of the string `data` to be mutated
+/// while aliased, as one cell per byte.
+///
+/// Bytes that are part of a multibyte character can be read but not
+/// changed, so the string stays valid UTF-8.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut s = String::from("héllo wörld");
+///
+/// {
+///     let cells = alias::str_ascii(&mut s);
+///     let capitalise = |c: &alias::AsciiCell| c.make_ascii_uppercase();
+///     capitalise(&cells[0]);
+///     capitalise(&cells[7]);
+///     cells[5].swap(&cells[6]);
+///     assert!(!cells[1].is_ascii());
+/// }
+///
+/// assert_eq!(s, "Héll oWörld");
+/// ```
+pub fn str_ascii(data: &mut str) -> &[AsciiCell] {
+    // `AsciiCell` only ever writes ASCII bytes over ASCII bytes.
+    let bytes = slice(unsafe { data.as_bytes_mut() });
+    unsafe { &*(bytes as *const [Cell<u8>] as *const [AsciiCell]) }
+}
+
+impl AsciiCell {
+    /// The current byte.
+    pub fn get(&self) -> u8 {
+        self.byte.get()
+    }
+
+    /// Whether the current byte is ASCII, and so can be changed.
+    pub fn is_ascii(&self) -> bool {
+        self.byte.get().is_ascii()
+    }
+
+    /// Replace the current ASCII character with the ASCII character
+    /// `b`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either the current byte or `b` is not ASCII.
+    pub fn set(&self, b: u8) {
+        assert!(self.is_ascii() && b.is_ascii(), "AsciiCell::set: non-ASCII byte");
+        self.byte.set(b)
+    }
+
+    /// Swap the ASCII characters in `self` and `other`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either byte is not ASCII.
+    pub fn swap(&self, other: &AsciiCell) {
+        assert!(self.is_ascii() && other.is_ascii(), "AsciiCell::swap: non-ASCII byte");
+        self.byte.swap(&other.byte)
+    }
+
+    /// Convert an ASCII lowercase letter to uppercase, leaving anything
+    /// else untouched.
+    pub fn make_ascii_uppercase(&self) {
+        self.byte.set(self.byte.get().to_ascii_uppercase())
+    }
+
+    /// Convert an ASCII uppercase letter to lowercase, leaving anything
+    /// else untouched.
+    pub fn make_ascii_lowercase(&self) {
+        self.byte.set(self.byte.get().to_ascii_lowercase())
+    }
+}
+
+impl fmt::Debug for AsciiCell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("AsciiCell").field(&self.get()).finish()
+    }
+}
+
+/// An aliased view of a string that can be appended to through
+/// shared references.
+///
+/// This also implements `fmt::Write`, and is `Copy`, so `write!` works
+/// on any copy of it.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::fmt::Write;
+/// use alias::CellString;
+///
+/// let mut out = String::from("log:");
+/// {
+///     let s = CellString::new(&mut out);
+///     let item = |n: u32| { let mut w = s; write!(w, " {}", n).unwrap(); };
+///     let sep = || s.push(',');
+///
+///     item(1);
+///     sep();
+///     item(2);
+///     s.push_str(" end");
+///     assert_eq!(s.len(), 13);
+/// }
+/// assert_eq!(out, "log: 1, 2 end");
+/// ```
+#[derive(Clone, Copy)]
+pub struct CellString<'a> {
+    string: &'a Cell<String>,
+}
+
+impl<'a> CellString<'a> {
+    /// Create a view that appends to `data`.
+    pub fn new(data: &'a mut String) -> CellString<'a> {
+        CellString { string: one(data) }
+    }
+
+    fn with<R, F: FnOnce(&mut String) -> R>(&self, f: F) -> R {
+        with_taken(self.string, f)
+    }
+
+    /// The length of the string, in bytes.
+    pub fn len(&self) -> usize {
+        self.with(|s| s.len())
+    }
+
+    /// Whether the string is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Append the character `c`.
+    pub fn push(&self, c: char) {
+        self.with(|s| s.push(c))
+    }
+
+    /// Append the string `t`.
+    pub fn push_str(&self, t: &str) {
+        self.with(|s| s.push_str(t))
+    }
+
+    /// Copy the current contents into a new `String`.
+    #[allow(clippy::inherent_to_string_shadow_display)]
+    pub fn to_string(&self) -> String {
+        self.with(|s| s.clone())
+    }
+}
+
+impl<'a> fmt::Write for CellString<'a> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+// these format a copy, since the formatter may be writing to this
+// very string, which is empty while it is taken out.
+impl<'a> fmt::Debug for CellString<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.to_string(), f)
+    }
+}
+
+impl<'a> fmt::Display for CellString<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.to_string(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_cell_str() {
+        let mut s = String::from("\u{3000} Ünïcödé\tTeXt \n");
+        {
+            let a = cell_str(&mut s);
+            let b = a;
+            assert_eq!(a.len(), 22);
+
+            b.make_ascii_lowercase();
+            assert_eq!(a.to_string(), "\u{3000} Ünïcödé\ttext \n");
+            a.make_ascii_uppercase();
+            assert_eq!(format!("{}", b), "\u{3000} ÜNïCöDé\tTEXT \n");
+
+            assert_eq!(a.replace_byte(b'\t', b' '), 1);
+            a.set_ascii(6, b'u');
+            assert_eq!(format!("{:?}", b), "\"\\u{3000} ÜuïCöDé TEXT \\n\"");
+
+            assert_eq!(a.trim_in_place_with_fill(b'_'), 16);
+            assert_eq!(b.to_string(), "ÜuïCöDé TEXT______");
+        }
+        assert_eq!(s, "ÜuïCöDé TEXT______");
+    }
+
+    #[test]
+    #[should_panic(expected = "non-ASCII")]
+    fn set_ascii_over_multibyte() {
+        let mut s = String::from("é");
+        cell_str(&mut s).set_ascii(0, b'e');
+    }
+
+    #[test]
+    fn smoke_cell_string() {
+        use std::fmt::Write;
+        let mut s = String::new();
+        {
+            let a = CellString::new(&mut s);
+            let mut b = a;
+            assert!(a.is_empty());
+            a.push('é');
+            write!(b, "{}-{}", 1, 2).unwrap();
+            b.push_str("!");
+            assert_eq!((a.len(), a.to_string()), (6, "é1-2!".to_string()));
+            assert_eq!(format!("{:?} {}", b, a), "\"é1-2!\" é1-2!");
+        }
+        assert_eq!(s, "é1-2!");
+
+        // formatting into itself
+        let mut s = String::from("ab");
+        {
+            let a = CellString::new(&mut s);
+            let mut w = a;
+            write!(w, "{}{:?}", a, a).unwrap();
+        }
+        assert_eq!(s, "abab\"abab\"");
+    }
+
+    #[test]
+    fn smoke_str_ascii() {
+        let mut s = String::from("aé!");
+        {
+            let c = str_ascii(&mut s);
+            assert_eq!(c.len(), 4);
+            c[0].set(b'?');
+            c[3].swap(&c[0]);
+            c[1].make_ascii_uppercase();
+            assert_eq!((c[1].get(), format!("{:?}", c[0])), (0xC3, "AsciiCell(33)".to_string()));
+        }
+        assert_eq!(s, "!é?");
+    }
+
+    #[test]
+    #[should_panic(expected = "non-ASCII")]
+    fn ascii_swap_multibyte() {
+        let mut s = String::from("aé");
+        let c = str_ascii(&mut s);
+        c[0].swap(&c[1]);
+    }
+}
@@ -0,0 +1,131 @@
+use std::cell::Cell;
+use std::fmt;
+use std::panic::Location;
+
+use one;
+
+/// A single access through a `TracedCell`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Access<T> {
+    /// Where the access happened.
+    pub location: &'static Location<'static>,
+    /// The value before the access.
+    pub old: T,
+    /// The value written, or `None` for a read.
+    pub new: Option<T>,
+}
+
+/// An aliased cell that reports every read and write to a sink, for
+/// tracking down which of many sharers changed a value.
+///
+/// Created by `alias::traced`. This needs the `trace` feature.
+pub struct TracedCell<'a, T: 'a> {
+    cell: &'a Cell<T>,
+    sink: &'a dyn Fn(Access<T>),
+}
+
+/// Allow `data` to be mutated while aliased, calling `sink` with the
+/// caller's location and the old and new values on every access.
+///
+/// This needs the `trace` feature.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::cell::RefCell;
+///
+/// let log = RefCell::new(vec![]);
+/// let sink = |a: alias::Access<i32>| log.borrow_mut().push((a.location.line(), a.old, a.new));
+///
+/// let mut balance = 100;
+/// {
+///     let b = alias::traced(&mut balance, &sink);
+///     let deposit = |n| b.set(b.get() + n);
+///     let withdraw = |n| b.set(b.get() - n);
+///     deposit(10);
+///     withdraw(30);
+/// }
+/// assert_eq!(balance, 80);
+///
+/// let log = log.into_inner();
+/// assert_eq!(log.len(), 4);
+/// // the last write, from `withdraw`
+/// assert_eq!(log[3].1..=log[3].2.unwrap(), 110..=80);
+/// assert_eq!(log[0].0, log[1].0);
+/// ```
+pub fn traced<'a, T: Copy>(data: &'a mut T, sink: &'a dyn Fn(Access<T>)) -> TracedCell<'a, T> {
+    TracedCell::new(one(data), sink)
+}
+
+impl<'a, T: Copy> TracedCell<'a, T> {
+    /// Trace the accesses to the aliased `cell` made through this
+    /// view.
+    pub fn new(cell: &'a Cell<T>, sink: &'a dyn Fn(Access<T>)) -> TracedCell<'a, T> {
+        TracedCell { cell, sink }
+    }
+
+    /// Read the value.
+    #[track_caller]
+    pub fn get(&self) -> T {
+        let value = self.cell.get();
+        (self.sink)(Access { location: Location::caller(), old: value, new: None });
+        value
+    }
+
+    /// Write `value`.
+    #[track_caller]
+    pub fn set(&self, value: T) {
+        self.replace(value);
+    }
+
+    /// Write `value`, returning the old value.
+    #[track_caller]
+    pub fn replace(&self, value: T) -> T {
+        let old = self.cell.replace(value);
+        (self.sink)(Access { location: Location::caller(), old, new: Some(value) });
+        old
+    }
+
+    /// The underlying cell, for untraced access.
+    pub fn as_cell(&self) -> &'a Cell<T> {
+        self.cell
+    }
+}
+
+impl<'a, T> Clone for TracedCell<'a, T> {
+    fn clone(&self) -> TracedCell<'a, T> {
+        *self
+    }
+}
+
+impl<'a, T> Copy for TracedCell<'a, T> {}
+
+impl<'a, T: Copy + fmt::Debug> fmt::Debug for TracedCell<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("TracedCell").field(&self.cell.get()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_traced() {
+        let seen = Cell::new((0, 0, None));
+        let sink = |a: Access<u8>| seen.set((a.location.line(), a.old, a.new));
+        let mut x = 1;
+        {
+            let t = traced(&mut x, &sink);
+            let u = t;
+            assert_eq!(u.replace(5), 1);
+            let line = line!() - 1;
+            assert_eq!(seen.get(), (line, 1, Some(5)));
+            t.as_cell().set(7);
+            assert_eq!(seen.get().2, Some(5));
+            assert_eq!((t.get(), seen.get().2), (7, None));
+            assert_eq!(format!("{:?}", u), "TracedCell(7)");
+        }
+        assert_eq!(x, 7);
+    }
+}
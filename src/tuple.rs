@@ -0,0 +1,191 @@
+use std::cell::Cell;
+
+use {one, slice};
+
+/// Tuples that can be split into aliased references to each element,
+/// by `alias::tuple`.
+///
+/// This is implemented for tuples of up to 12 elements.
+pub trait Tuple<'a> {
+    /// A tuple of a `&'a Cell` for each element.
+    type Cells;
+
+    /// Allow each element of `self` to be mutated while aliased,
+    /// independently.
+    fn cells(&'a mut self) -> Self::Cells;
+}
+
+/// Allow each element of the tuple `data` to be mutated while aliased,
+/// independently.
+///
+/// Unlike `alias::one(data)`, which gives a single `Cell` of the whole
+/// tuple, this gives a separate `Cell` for each element, so they can
+/// be shared and updated without a wrapper struct.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut state = (0u32, 'a', false);
+/// {
+///     let (count, last, seen) = alias::tuple(&mut state);
+///     let record = |c: char| {
+///         count.set(count.get() + 1);
+///         last.set(c);
+///     };
+///     let check = |c: char| seen.set(seen.get() || c == 'z');
+///
+///     for c in "xyz".chars() {
+///         record(c);
+///         check(c);
+///     }
+/// }
+/// assert_eq!(state, (3, 'z', true));
+/// ```
+pub fn tuple<'a, T: Tuple<'a>>(data: &'a mut T) -> T::Cells {
+    data.cells()
+}
+
+macro_rules! tuples {
+    ($(($($t: ident $x: ident),*))*) => {
+        $(
+            impl<'a, $($t: 'a),*> Tuple<'a> for ($($t,)*) {
+                type Cells = ($(&'a Cell<$t>,)*);
+                #[allow(clippy::unused_unit)]
+                fn cells(&'a mut self) -> Self::Cells {
+                    let ($(ref mut $x,)*) = *self;
+                    ($(one($x),)*)
+                }
+            }
+        )*
+    }
+}
+tuples! {
+    ()
+    (A a)
+    (A a, B b)
+    (A a, B b, C c)
+    (A a, B b, C c, D d)
+    (A a, B b, C c, D d, E e)
+    (A a, B b, C c, D d, E e, F f)
+    (A a, B b, C c, D d, E e, F f, G g)
+    (A a, B b, C c, D d, E e, F f, G g, H h)
+    (A a, B b, C c, D d, E e, F f, G g, H h, I i)
+    (A a, B b, C c, D d, E e, F f, G g, H h, I i, J j)
+    (A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k)
+    (A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k, L l)
+}
+
+/// Tuples of mutable slices that can all be aliased at once, by
+/// `alias::slices` and `alias::columns`.
+///
+/// This is implemented for tuples of up to 8 slices.
+pub trait Slices<'a> {
+    /// A tuple of a `&'a [Cell]` for each slice.
+    type Cells;
+
+    /// Allow the contents of each slice in `self` to be mutated while
+    /// aliased.
+    fn cells(self) -> Self::Cells;
+
+    /// The length of each slice, in order.
+    #[doc(hidden)]
+    fn lens(&self) -> Vec<usize>;
+}
+
+/// Allow the contents of each slice in the tuple `data` to be mutated
+/// while aliased.
+///
+/// This is `alias::slice` applied to each element, with every result
+/// sharing the one lifetime.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut pos = vec![0.0, 10.0];
+/// let mut vel = vec![1.0, -2.0];
+/// let mut mass = [1.0, 4.0];
+/// {
+///     let (pos, vel, mass) = alias::slices((&mut *pos, &mut *vel, &mut mass[..]));
+///     let integrate = |dt: f64| for (p, v) in pos.iter().zip(vel) {
+///         p.set(p.get() + v.get() * dt);
+///     };
+///     let damp = || for (v, m) in vel.iter().zip(mass) {
+///         v.set(v.get() / m.get());
+///     };
+///     damp();
+///     integrate(2.0);
+/// }
+/// assert_eq!(pos, [2.0, 9.0]);
+/// ```
+pub fn slices<'a, T: Slices<'a>>(data: T) -> T::Cells {
+    data.cells()
+}
+
+macro_rules! slices {
+    ($(($($t: ident $x: ident),*))*) => {
+        $(
+            impl<'a, $($t: 'a),*> Slices<'a> for ($(&'a mut [$t],)*) {
+                type Cells = ($(&'a [Cell<$t>],)*);
+                fn cells(self) -> Self::Cells {
+                    let ($($x,)*) = self;
+                    ($(slice($x),)*)
+                }
+                fn lens(&self) -> Vec<usize> {
+                    let ($($x,)*) = self;
+                    vec![$($x.len()),*]
+                }
+            }
+        )*
+    }
+}
+slices! {
+    (A a)
+    (A a, B b)
+    (A a, B b, C c)
+    (A a, B b, C c, D d)
+    (A a, B b, C c, D d, E e)
+    (A a, B b, C c, D d, E e, F f)
+    (A a, B b, C c, D d, E e, F f, G g)
+    (A a, B b, C c, D d, E e, F f, G g, H h)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_tuple() {
+        let mut t = (1u8, "a".to_string(), [1, 2]);
+        {
+            let (a, b, c) = tuple(&mut t);
+            let (a2, c2) = (a, c);
+            a2.set(a.get() + 1);
+            b.set(b.take() + "b");
+            c2.set([c.get()[1], 0]);
+        }
+        assert_eq!(t, (2, "ab".to_string(), [2, 0]));
+
+        let mut big = (0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11);
+        {
+            let cells = tuple(&mut big);
+            cells.0.swap(cells.11);
+        }
+        assert_eq!((big.0, big.11), (11, 0));
+        let () = tuple(&mut ());
+    }
+
+    #[test]
+    fn smoke_slices() {
+        let mut a = [1, 2];
+        let mut b = vec!['x'];
+        let mut c = [String::new()];
+        {
+            let (a, b, c) = slices((&mut a[..], &mut *b, &mut c[..]));
+            a[0].swap(&a[1]);
+            c[0].set(b[0].get().to_string());
+        }
+        assert_eq!((a, &*c[0]), ([2, 1], "x"));
+        let (single,) = slices((&mut b[..0],));
+        assert!(single.is_empty());
+    }
+}
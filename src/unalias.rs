@@ -0,0 +1,111 @@
+//! Converting aliased views back to plain references.
+//!
+//! These are the inverses of `alias::one`, `alias::slice` and
+//! `alias::array`. A unique `&mut` to the cells proves that nothing
+//! else can be using them, so they can be viewed as plain mutable
+//! data again, such as to sort them or pass them to code expecting
+//! `&mut [T]`.
+//!
+//! A shared `&[Cell<T>]` can't safely be viewed as `&[T]`, because
+//! another copy of the same reference might write through its cells
+//! while the `&[T]` is alive, breaking the guarantee that it doesn't
+//! change. `slice_ref` does that conversion, but is `unsafe`.
+//!
+//! # Examples
+//!
+//! ```rust
+//! let mut cells: Vec<_> = [3, 1, 2].iter().map(|&x| std::cell::Cell::new(x)).collect();
+//!
+//! // aliasing phase
+//! for c in &cells {
+//!     c.set(c.get() * 10);
+//! }
+//! // plain phase
+//! alias::unalias::slice(&mut cells).sort();
+//!
+//! let plain: Vec<_> = cells.iter().map(|c| c.get()).collect();
+//! assert_eq!(plain, [10, 20, 30]);
+//! ```
+
+use std::cell::Cell;
+
+/// View the unique reference to a cell as a unique reference to its
+/// contents.
+///
+/// This is the inverse of `alias::one`, and the same as
+/// `Cell::get_mut`.
+pub fn one<T>(data: &mut Cell<T>) -> &mut T {
+    data.get_mut()
+}
+
+/// View the unique reference to a slice of cells as a unique reference
+/// to their contents.
+///
+/// This is the inverse of `alias::slice`.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut x = [5, 4, 3];
+/// let mut copy = alias::slice(&mut x).to_vec();
+/// copy[0].set(0);
+/// alias::unalias::slice(&mut copy).sort();
+/// assert_eq!(copy.iter().map(|c| c.get()).collect::<Vec<_>>(), [0, 3, 4]);
+/// ```
+pub fn slice<T>(data: &mut [Cell<T>]) -> &mut [T] {
+    // `Cell<T>` has the same layout as `T`, and `data` is unique.
+    unsafe { &mut *(data as *mut [Cell<T>] as *mut [T]) }
+}
+
+/// View the unique reference to an array of cells as a unique
+/// reference to their contents.
+///
+/// This is the inverse of `alias::array`.
+pub fn array<const N: usize, T>(data: &mut [Cell<T>; N]) -> &mut [T; N] {
+    unsafe { &mut *(data as *mut [Cell<T>; N] as *mut [T; N]) }
+}
+
+/// View a shared slice of cells as a shared slice of their contents,
+/// for reading.
+///
+/// # Safety
+///
+/// None of the cells in `data` may be written, through any
+/// reference, while the returned slice is alive.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut x = [1u8, 2, 3];
+/// let cells = alias::slice(&mut x);
+/// cells[0].set(4);
+///
+/// // nothing writes to `cells` while `plain` is used
+/// let plain = unsafe { alias::unalias::slice_ref(cells) };
+/// assert_eq!(plain.iter().sum::<u8>(), 9);
+/// ```
+pub unsafe fn slice_ref<T>(data: &[Cell<T>]) -> &[T] {
+    &*(data as *const [Cell<T>] as *const [T])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_unalias() {
+        let mut x = [3, 1, 2];
+        {
+            let mut cells = ::array(&mut x).clone();
+            cells[0].set(0);
+            array(&mut cells).sort();
+            *one(&mut cells[2]) += 1;
+            assert_eq!(*array(&mut cells), [0, 1, 3]);
+
+            let s = ::slice(&mut x);
+            s[2].set(s[0].get());
+            assert_eq!(unsafe { slice_ref(s) }, [3, 1, 3]);
+        }
+        assert_eq!(x, [3, 1, 3]);
+    }
+}
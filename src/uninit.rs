@@ -0,0 +1,336 @@
+use std::cell::Cell;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::{self, ManuallyDrop, MaybeUninit};
+use std::ptr;
+use std::slice;
+
+use vec::with_taken;
+
+/// Allow the contents of the possibly-uninitialised slice `data` to
+/// be mutated while aliased.
+///
+/// This is `alias::slice` specialised to `MaybeUninit`; reading the
+/// values back still needs `unsafe`. `UninitTracker` does the
+/// bookkeeping to avoid that.
+pub fn uninit_slice<T>(data: &mut [MaybeUninit<T>]) -> &[Cell<MaybeUninit<T>>] {
+    ::slice(data)
+}
+
+/// A possibly-uninitialised slice that can be filled in through
+/// shared references, tracking which elements have been written.
+///
+/// Once every element is initialised, `assume_init_slice` safely
+/// gives back the whole slice as `&mut [T]`. Elements that have been
+/// initialised are dropped if the tracker is dropped first.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::mem::MaybeUninit;
+/// use alias::UninitTracker;
+///
+/// let mut buf = [const { MaybeUninit::<String>::uninit() }; 4];
+/// let t = UninitTracker::new(&mut buf);
+///
+/// let evens = |t: &UninitTracker<String>| for i in (0..4).step_by(2) {
+///     t.set(i, format!("even {}", i));
+/// };
+/// let odds = |t: &UninitTracker<String>| for i in (1..4).step_by(2) {
+///     t.set(i, format!("odd {}", i));
+/// };
+///
+/// evens(&t);
+/// assert!(!t.is_complete());
+/// odds(&t);
+///
+/// let strings: &mut [String] = t.assume_init_slice().unwrap();
+/// assert_eq!(strings[3], "odd 3");
+/// ```
+pub struct UninitTracker<'a, T: 'a> {
+    ptr: *mut MaybeUninit<T>,
+    len: usize,
+    init: Cell<Init>,
+    _marker: PhantomData<&'a mut [T]>,
+}
+
+impl<'a, T> UninitTracker<'a, T> {
+    /// Track the initialisation of `data`, treating every element as
+    /// uninitialised.
+    pub fn new(data: &'a mut [MaybeUninit<T>]) -> UninitTracker<'a, T> {
+        UninitTracker {
+            ptr: data.as_mut_ptr(),
+            len: data.len(),
+            init: Cell::new(Init::new::<T>(data.len())),
+            _marker: PhantomData,
+        }
+    }
+
+    /// The number of elements.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether element `i` has been initialised.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds.
+    pub fn is_init(&self, i: usize) -> bool {
+        assert!(i < self.len, "UninitTracker::is_init: index {} out of bounds for length {}",
+                i, self.len);
+        with_taken(&self.init, |init| init.contains(i))
+    }
+
+    /// Whether every element has been initialised.
+    pub fn is_complete(&self) -> bool {
+        with_taken(&self.init, |init| init.count() == self.len)
+    }
+
+    /// Set element `i` to `value`, dropping the old value if it was
+    /// already initialised.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds.
+    pub fn set(&self, i: usize, value: T) {
+        assert!(i < self.len, "UninitTracker::set: index {} out of bounds for length {}",
+                i, self.len);
+        let p = unsafe { self.ptr.add(i) };
+        // the old value is dropped after everything is consistent
+        // again, in case its destructor uses this tracker.
+        let old = with_taken(&self.init, |init| {
+            let old = if init.insert(i) {
+                Some(unsafe { ptr::read(p).assume_init() })
+            } else {
+                None
+            };
+            unsafe { ptr::write(p, MaybeUninit::new(value)) }
+            old
+        });
+        drop(old);
+    }
+
+    /// A copy of element `i`, or `None` if it hasn't been initialised.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds.
+    pub fn get(&self, i: usize) -> Option<T>
+        where T: Copy
+    {
+        if self.is_init(i) {
+            Some(unsafe { (*self.ptr.add(i)).assume_init() })
+        } else {
+            None
+        }
+    }
+
+    /// The whole slice, now that it is initialised, or `Err(self)` if
+    /// some element isn't.
+    pub fn assume_init_slice(self) -> Result<&'a mut [T], UninitTracker<'a, T>> {
+        if !self.is_complete() {
+            return Err(self)
+        }
+        let this = ManuallyDrop::new(self);
+        drop(this.init.take());
+        // every element is initialised, and the tracker has unique
+        // access for `'a`.
+        Ok(unsafe { slice::from_raw_parts_mut(this.ptr as *mut T, this.len) })
+    }
+
+    /// Drop every initialised element after the first uninitialised
+    /// one, returning the length of the initialised prefix, which is
+    /// left in place.
+    pub(crate) fn into_init_prefix(self) -> usize {
+        let this = ManuallyDrop::new(self);
+        let init = this.init.take();
+        let prefix = init.prefix();
+        init.for_each(|i| if i >= prefix {
+            unsafe { ptr::drop_in_place((*this.ptr.add(i)).as_mut_ptr()) }
+        });
+        prefix
+    }
+}
+
+impl<'a, T> Drop for UninitTracker<'a, T> {
+    fn drop(&mut self) {
+        self.init.take().for_each(|i| unsafe {
+            ptr::drop_in_place((*self.ptr.add(i)).as_mut_ptr())
+        })
+    }
+}
+
+impl<'a, T> fmt::Debug for UninitTracker<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let count = with_taken(&self.init, |init| init.count());
+        f.debug_struct("UninitTracker")
+            .field("len", &self.len)
+            .field("initialised", &count)
+            .finish()
+    }
+}
+
+/// Which elements of an `UninitTracker` are initialised.
+enum Init {
+    /// One bit per element.
+    Bits { words: Vec<u64>, count: usize },
+    /// The sorted indices of the initialised elements, for zero-sized
+    /// types, whose length can be up to `usize::MAX`.
+    Sparse(Vec<usize>),
+}
+
+impl Default for Init {
+    fn default() -> Init {
+        Init::Sparse(Vec::new())
+    }
+}
+
+impl Init {
+    fn new<T>(len: usize) -> Init {
+        if mem::size_of::<T>() == 0 {
+            Init::Sparse(Vec::new())
+        } else {
+            Init::Bits { words: vec![0; len.div_ceil(64)], count: 0 }
+        }
+    }
+
+    fn contains(&self, i: usize) -> bool {
+        match *self {
+            Init::Bits { ref words, .. } => words[i / 64] & (1 << (i % 64)) != 0,
+            Init::Sparse(ref indices) => indices.binary_search(&i).is_ok(),
+        }
+    }
+
+    /// Mark element `i` as initialised, returning whether it already
+    /// was.
+    fn insert(&mut self, i: usize) -> bool {
+        match *self {
+            Init::Bits { ref mut words, ref mut count } => {
+                let (w, bit) = (&mut words[i / 64], 1 << (i % 64));
+                let was = *w & bit != 0;
+                if !was {
+                    *w |= bit;
+                    *count += 1;
+                }
+                was
+            }
+            Init::Sparse(ref mut indices) => match indices.binary_search(&i) {
+                Ok(_) => true,
+                Err(pos) => {
+                    indices.insert(pos, i);
+                    false
+                }
+            },
+        }
+    }
+
+    fn count(&self) -> usize {
+        match *self {
+            Init::Bits { count, .. } => count,
+            Init::Sparse(ref indices) => indices.len(),
+        }
+    }
+
+    /// The number of initialised elements before the first
+    /// uninitialised one.
+    fn prefix(&self) -> usize {
+        match *self {
+            Init::Bits { ref words, .. } => {
+                let full = words.iter().take_while(|&&w| w == !0).count();
+                full * 64 + words.get(full).map_or(0, |w| w.trailing_ones() as usize)
+            }
+            Init::Sparse(ref indices) => {
+                indices.iter().enumerate().take_while(|&(k, &i)| k == i).count()
+            }
+        }
+    }
+
+    /// Call `f` on the index of each initialised element, in order.
+    fn for_each<F: FnMut(usize)>(&self, mut f: F) {
+        match *self {
+            Init::Bits { ref words, .. } => {
+                for (w, &word) in words.iter().enumerate() {
+                    let mut bits = word;
+                    while bits != 0 {
+                        f(w * 64 + bits.trailing_zeros() as usize);
+                        bits &= bits - 1;
+                    }
+                }
+            }
+            Init::Sparse(ref indices) => indices.iter().cloned().for_each(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn smoke_uninit() {
+        let mut buf = [MaybeUninit::<u32>::uninit(); 3];
+        {
+            let cells = uninit_slice(&mut buf);
+            cells[1].set(MaybeUninit::new(7));
+        }
+        let t = UninitTracker::new(&mut buf);
+        assert_eq!((t.len(), t.get(1)), (3, None));
+        t.set(1, 1);
+        t.set(0, 0);
+        assert_eq!(t.get(1), Some(1));
+        let t = t.assume_init_slice().unwrap_err();
+        t.set(2, 2);
+        assert_eq!(t.assume_init_slice().unwrap(), [0, 1, 2]);
+    }
+
+    #[test]
+    fn drops() {
+        let rc = Rc::new(());
+        let mut buf: [MaybeUninit<Rc<()>>; 3] = [const { MaybeUninit::uninit() }; 3];
+        {
+            let t = UninitTracker::new(&mut buf);
+            t.set(0, rc.clone());
+            t.set(0, rc.clone());
+            t.set(2, rc.clone());
+            assert_eq!(Rc::strong_count(&rc), 3);
+        }
+        assert_eq!(Rc::strong_count(&rc), 1);
+
+        let t = UninitTracker::new(&mut buf);
+        for i in 0..3 {
+            t.set(i, rc.clone());
+        }
+        let s = t.assume_init_slice().unwrap();
+        assert_eq!(Rc::strong_count(&rc), 4);
+        for r in s {
+            unsafe { ptr::drop_in_place(r) }
+        }
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+
+    #[test]
+    fn fill_backwards() {
+        let mut buf = vec![MaybeUninit::<u16>::uninit(); 200];
+        let t = UninitTracker::new(&mut buf);
+        for i in (0..200).rev() {
+            assert!(!t.is_complete());
+            t.set(i, i as u16);
+        }
+        assert_eq!(format!("{:?}", t), "UninitTracker { len: 200, initialised: 200 }");
+        let s = t.assume_init_slice().unwrap();
+        assert!(s.iter().enumerate().all(|(i, &x)| x as usize == i));
+
+        let t = UninitTracker::new(&mut buf);
+        for i in (0..130).chain(150..160) {
+            t.set(i, 0);
+        }
+        assert_eq!(t.into_init_prefix(), 130);
+    }
+}
@@ -0,0 +1,147 @@
+use std::cell::Cell;
+use std::error::Error;
+use std::fmt;
+
+use slice;
+
+/// A uniquely-held slice, which can hand out either an aliased view
+/// or several disjoint `&mut` references.
+///
+/// Created by `alias::unique`.
+#[derive(Debug)]
+pub struct UniqueSlice<'a, T: 'a> {
+    data: &'a mut [T],
+}
+
+/// The reason `UniqueSlice::get_disjoint_mut` failed.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum DisjointError {
+    /// One of the indices was not less than the length of the slice.
+    IndexOutOfBounds,
+    /// The same index appeared more than once.
+    OverlappingIndices,
+}
+
+impl fmt::Display for DisjointError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            DisjointError::IndexOutOfBounds => "index out of bounds",
+            DisjointError::OverlappingIndices => "indices overlap",
+        })
+    }
+}
+
+impl Error for DisjointError {}
+
+/// Hold `data` uniquely, choosing per-use between aliased (`Cell`)
+/// and disjoint unique (`&mut`) access.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut x = [1, 2, 3, 4];
+/// let mut u = alias::unique(&mut x);
+///
+/// {
+///     let [a, b] = u.get_disjoint_mut([3, 0]).unwrap();
+///     std::mem::swap(a, b);
+/// }
+///
+/// let cells = u.cells();
+/// cells[1].set(cells[0].get() + cells[3].get());
+///
+/// assert_eq!(x, [4, 5, 3, 1]);
+/// ```
+pub fn unique<T>(data: &mut [T]) -> UniqueSlice<'_, T> {
+    UniqueSlice { data }
+}
+
+impl<'a, T> UniqueSlice<'a, T> {
+    /// The number of elements in the slice.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Whether the slice has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Allow the contents to be mutated while aliased, for as long as
+    /// the returned view is live.
+    pub fn cells(&mut self) -> &[Cell<T>] {
+        slice(self.data)
+    }
+
+    /// Retrieve `&mut` references to the elements at each of
+    /// `indices`, checking that they are in bounds and pairwise
+    /// distinct.
+    pub fn get_disjoint_mut<const N: usize>(&mut self, indices: [usize; N])
+                                            -> Result<[&mut T; N], DisjointError> {
+        let len = self.data.len();
+        for (i, &idx) in indices.iter().enumerate() {
+            if idx >= len {
+                return Err(DisjointError::IndexOutOfBounds)
+            }
+            if indices[..i].contains(&idx) {
+                return Err(DisjointError::OverlappingIndices)
+            }
+        }
+        let ptr = self.data.as_mut_ptr();
+        // the indices are in bounds and distinct, so these references
+        // never overlap.
+        Ok(indices.map(|idx| unsafe { &mut *ptr.add(idx) }))
+    }
+
+    /// Give up the unique hold, returning the original slice.
+    pub fn into_inner(self) -> &'a mut [T] {
+        self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_disjoint() {
+        let mut x = [String::from("a"), String::from("b"), String::from("c")];
+        {
+            let mut u = unique(&mut x);
+            assert_eq!(u.len(), 3);
+
+            {
+                let [a, c] = u.get_disjoint_mut([0, 2]).unwrap();
+                a.push('x');
+                c.push('y');
+            }
+            assert_eq!(u.get_disjoint_mut([0, 3]).err(), Some(DisjointError::IndexOutOfBounds));
+            assert_eq!(u.get_disjoint_mut([1, 2, 1]).err(),
+                       Some(DisjointError::OverlappingIndices));
+            assert!(u.get_disjoint_mut([]).is_ok());
+        }
+        assert_eq!(x, ["ax", "b", "cy"]);
+    }
+
+    #[test]
+    fn smoke_cells() {
+        let mut x = [0, 1, 2];
+        {
+            let mut u = unique(&mut x);
+            {
+                let c = u.cells();
+                c[0].set(c[2].get());
+            }
+            *u.get_disjoint_mut([1]).unwrap()[0] += 10;
+        }
+        assert_eq!(x, [2, 11, 2]);
+
+        let mut y = [String::from("a"), String::from("b")];
+        {
+            let mut u = unique(&mut y);
+            let c = u.cells();
+            c[0].swap(&c[1]);
+        }
+        assert_eq!(y, ["b", "a"]);
+    }
+}
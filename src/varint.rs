@@ -0,0 +1,119 @@
+use std::cell::Cell;
+use std::error::Error;
+use std::fmt;
+
+/// The ways reading or writing a varint can fail.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VarintError {
+    /// The buffer ended before the varint did, or is too short to hold
+    /// the value being written.
+    OutOfBounds,
+    /// The encoded value doesn't fit in a `u64`.
+    Overflow,
+}
+
+impl fmt::Display for VarintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            VarintError::OutOfBounds => "varint extends past the end of the buffer",
+            VarintError::Overflow => "varint does not fit in 64 bits",
+        })
+    }
+}
+
+impl Error for VarintError {}
+
+/// The number of bytes `write_varint` uses for `value`.
+pub fn varint_len(value: u64) -> usize {
+    (64 - (value | 1).leading_zeros() as usize).div_ceil(7)
+}
+
+/// Read the unsigned LEB128 (protobuf-style) varint starting at
+/// `offset`, returning it along with the number of bytes it occupies.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut buf = [0xAC, 0x02, 0xFF];
+/// let bytes = alias::slice(&mut buf);
+/// assert_eq!(alias::read_varint(bytes, 0), Ok((300, 2)));
+/// assert!(alias::read_varint(bytes, 2).is_err());
+/// ```
+pub fn read_varint(bytes: &[Cell<u8>], offset: usize) -> Result<(u64, usize), VarintError> {
+    let mut value = 0;
+    for (i, c) in bytes.get(offset..).unwrap_or(&[]).iter().enumerate() {
+        let b = c.get();
+        let shift = 7 * i as u32;
+        if shift >= 64 || (shift == 63 && b & 0x7F > 1) {
+            return Err(VarintError::Overflow)
+        }
+        value |= ((b & 0x7F) as u64) << shift;
+        if b & 0x80 == 0 {
+            return Ok((value, i + 1))
+        }
+    }
+    Err(VarintError::OutOfBounds)
+}
+
+/// Write `value` as an unsigned LEB128 varint starting at `offset`,
+/// returning the number of bytes written.
+///
+/// Nothing is written if the buffer is too short. This always uses
+/// the shortest encoding, so patching a length prefix in place only
+/// works if the new value has the same `varint_len` as the old.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut buf = [0u8; 4];
+/// {
+///     let bytes = alias::slice(&mut buf);
+///     assert_eq!(alias::write_varint(bytes, 1, 300), Ok(2));
+///     assert!(alias::write_varint(bytes, 3, 300).is_err());
+/// }
+/// assert_eq!(buf, [0, 0xAC, 0x02, 0]);
+/// ```
+pub fn write_varint(bytes: &[Cell<u8>], offset: usize, value: u64) -> Result<usize, VarintError> {
+    let len = varint_len(value);
+    let out = match offset.checked_add(len).and_then(|end| bytes.get(offset..end)) {
+        Some(out) => out,
+        None => return Err(VarintError::OutOfBounds),
+    };
+    for (i, c) in out.iter().enumerate() {
+        let more = if i + 1 < len { 0x80 } else { 0 };
+        c.set((value >> (7 * i)) as u8 & 0x7F | more);
+    }
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slice;
+
+    #[test]
+    fn smoke_varint() {
+        let mut x = [0u8; 12];
+        let b = slice(&mut x);
+        for &v in &[0, 1, 127, 128, 16383, 16384, u32::MAX as u64, u64::MAX] {
+            let n = write_varint(b, 1, v).unwrap();
+            assert_eq!(n, varint_len(v));
+            assert_eq!(read_varint(b, 1), Ok((v, n)));
+        }
+        assert_eq!(varint_len(0), 1);
+        assert_eq!(varint_len(u64::MAX), 10);
+        assert_eq!(write_varint(b, 3, u64::MAX), Err(VarintError::OutOfBounds));
+        assert_eq!(write_varint(b, usize::MAX, 0), Err(VarintError::OutOfBounds));
+        assert_eq!(read_varint(b, 100), Err(VarintError::OutOfBounds));
+
+        // too many continuation bytes, and a 10th byte with too many bits
+        for c in b.iter() {
+            c.set(0x80);
+        }
+        assert_eq!(read_varint(b, 0), Err(VarintError::Overflow));
+        b[9].set(0x02);
+        assert_eq!(read_varint(b, 0), Err(VarintError::Overflow));
+        b[9].set(0x01);
+        assert_eq!(read_varint(b, 0), Ok((1 << 63, 10)));
+    }
+}
@@ -0,0 +1,422 @@
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::iter::Chain;
+use std::mem::{self, MaybeUninit};
+use std::ops::Index;
+use std::slice;
+
+use {one, UninitTracker};
+
+/// Allow the elements and the spare capacity of the vector `data` to
+/// be mutated while aliased.
+///
+/// The first slice holds the `data.len()` initialised elements, the
+/// second the `data.capacity() - data.len()` slots after them. Writes
+/// to the spare capacity aren't part of the vector until its length
+/// is updated, which `vec_append` does safely.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::mem::MaybeUninit;
+///
+/// let mut v = Vec::with_capacity(4);
+/// v.push(1);
+///
+/// let (init, spare) = alias::vec_parts(&mut v);
+/// assert_eq!((init.len(), spare.len()), (1, 3));
+/// init[0].set(10);
+/// spare[0].set(MaybeUninit::new(2));
+///
+/// assert_eq!(v, [10]);
+/// ```
+pub fn vec_parts<T>(data: &mut Vec<T>) -> (&[Cell<T>], &[Cell<MaybeUninit<T>>]) {
+    let len = data.len();
+    let spare = data.capacity() - len;
+    let p = data.as_mut_ptr();
+    // the two halves are disjoint and within the allocation, which is
+    // uniquely borrowed for the lifetime of the result.
+    unsafe {
+        (::slice(slice::from_raw_parts_mut(p, len)),
+         ::slice(slice::from_raw_parts_mut(p.add(len) as *mut MaybeUninit<T>, spare)))
+    }
+}
+
+/// Append to `data` by filling in its spare capacity through shared
+/// references within the closure `f`.
+///
+/// `f` gets the existing elements and a tracker over the spare
+/// capacity, so reserve space first. Once `f` returns, the longest
+/// initialised prefix of the spare capacity becomes part of the
+/// vector, and any elements written after a gap are dropped. If `f`
+/// panics, every element it wrote is dropped and `data` is unchanged.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut out = vec![0];
+/// out.reserve(4);
+///
+/// let n = alias::vec_append(&mut out, |init, spare| {
+///     let squares = |t: &alias::UninitTracker<i32>| for i in 0..2 {
+///         t.set(i, (i * i) as i32);
+///     };
+///     let offsets = |t: &alias::UninitTracker<i32>| t.set(2, init[0].get() + 100);
+///     squares(spare);
+///     offsets(spare);
+///     spare.len()
+/// });
+///
+/// assert!(n >= 4);
+/// assert_eq!(out, [0, 0, 1, 100]);
+/// ```
+pub fn vec_append<T, R, F>(data: &mut Vec<T>, f: F) -> R
+    where F: FnOnce(&[Cell<T>], &UninitTracker<T>) -> R
+{
+    let len = data.len();
+    // for zero-sized types this is `usize::MAX - len`, which the
+    // tracker handles without allocating per slot.
+    let spare = data.capacity() - len;
+    let p = data.as_mut_ptr();
+    let (ret, added) = unsafe {
+        let init = ::slice(slice::from_raw_parts_mut(p, len));
+        let tracker = UninitTracker::new(
+            slice::from_raw_parts_mut(p.add(len) as *mut MaybeUninit<T>, spare));
+        let ret = f(init, &tracker);
+        (ret, tracker.into_init_prefix())
+    };
+    // the `added` elements after `len` were initialised by `f`.
+    unsafe { data.set_len(len + added) }
+    ret
+}
+
+/// An aliased view of a vector that can be appended to through shared
+/// references.
+///
+/// References to the elements are never handed out, only copies, so
+/// a `push` that reallocates can't invalidate anything.
+///
+/// # Examples
+///
+/// ```rust
+/// use alias::CellVec;
+///
+/// let mut log = vec!["start"];
+/// {
+///     let v = CellVec::new(&mut log);
+///     let on_click = |v: &CellVec<&str>| v.push("click");
+///     let on_key = |v: &CellVec<&str>| v.push("key");
+///
+///     on_click(&v);
+///     on_key(&v);
+///     on_click(&v);
+///     v.set(0, "begin");
+///     assert_eq!((v.len(), v.get(1)), (4, Some("click")));
+/// }
+/// assert_eq!(log, ["begin", "click", "key", "click"]);
+/// ```
+pub struct CellVec<'a, T: 'a> {
+    vec: &'a Cell<Vec<T>>,
+}
+
+impl<'a, T> CellVec<'a, T> {
+    /// Create a view that appends to `data`.
+    pub fn new(data: &'a mut Vec<T>) -> CellVec<'a, T> {
+        CellVec { vec: one(data) }
+    }
+
+    fn with<R, F: FnOnce(&mut Vec<T>) -> R>(&self, f: F) -> R {
+        with_taken(self.vec, f)
+    }
+
+    /// The number of elements.
+    pub fn len(&self) -> usize {
+        self.with(|v| v.len())
+    }
+
+    /// Whether there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Append `value` to the end of the vector.
+    pub fn push(&self, value: T) {
+        self.with(|v| v.push(value))
+    }
+
+    /// A copy of element `i`, or `None` if it is out of bounds.
+    pub fn get(&self, i: usize) -> Option<T>
+        where T: Copy
+    {
+        self.with(|v| v.get(i).copied())
+    }
+
+    /// Set element `i` to `value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds.
+    pub fn set(&self, i: usize, value: T) {
+        // the old value is dropped once the vector is back in place,
+        // in case its destructor uses this view.
+        let old = self.with(|v| v.get_mut(i).map(|x| mem::replace(x, value)));
+        match old {
+            Some(old) => drop(old),
+            None => panic!("CellVec::set: index {} out of bounds for length {}", i, self.len()),
+        }
+    }
+}
+
+// the elements' `Debug` impls may use this view, so format a copy
+// rather than the vector while it's taken out.
+impl<'a, T: Clone + fmt::Debug> fmt::Debug for CellVec<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let v = self.with(|v| v.clone());
+        f.debug_tuple("CellVec").field(&v).finish()
+    }
+}
+
+/// Call `f` on the value taken out of `cell`, putting it back
+/// afterwards, even if `f` panics.
+pub(crate) fn with_taken<T: Default, R, F: FnOnce(&mut T) -> R>(cell: &Cell<T>, f: F) -> R {
+    struct Restore<'c, T: 'c + Default>(&'c Cell<T>, T);
+    impl<'c, T: Default> Drop for Restore<'c, T> {
+        fn drop(&mut self) {
+            self.0.set(mem::take(&mut self.1))
+        }
+    }
+
+    let mut restore = Restore(cell, cell.take());
+    f(&mut restore.1)
+}
+
+/// Allow the elements of the double-ended queue `data` to be mutated
+/// while aliased, as its two contiguous halves.
+///
+/// Element `i` of the queue is in the first slice if `i` is less
+/// than its length, and in the second otherwise. `CellDeque` hides
+/// the split.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::collections::VecDeque;
+///
+/// let mut q = VecDeque::with_capacity(4);
+/// q.extend([1, 2, 3]);
+/// q.pop_front();
+/// q.push_back(4);
+/// q.push_back(5);
+///
+/// let (front, back) = alias::deque(&mut q);
+/// assert_eq!(front.len() + back.len(), 4);
+/// for c in front.iter().chain(back) {
+///     c.set(c.get() * 10);
+/// }
+/// assert_eq!(q, [20, 30, 40, 50]);
+/// ```
+pub fn deque<T>(data: &mut VecDeque<T>) -> (&[Cell<T>], &[Cell<T>]) {
+    let (front, back) = data.as_mut_slices();
+    (::slice(front), ::slice(back))
+}
+
+/// An aliased view of the elements of a `VecDeque`, indexed in queue
+/// order.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::collections::VecDeque;
+/// use alias::CellDeque;
+///
+/// let mut jobs: VecDeque<(u32, bool)> = VecDeque::new();
+/// jobs.extend([(1, false), (2, false), (3, false)]);
+/// {
+///     let q = CellDeque::new(&mut jobs);
+///     let finish = |id: u32| for job in q.iter() {
+///         if job.get().0 == id {
+///             job.set((id, true));
+///         }
+///     };
+///     let retry_last = || q[q.len() - 1].set((3, false));
+///
+///     finish(2);
+///     finish(3);
+///     retry_last();
+/// }
+/// assert_eq!(jobs, [(1, false), (2, true), (3, false)]);
+/// ```
+pub struct CellDeque<'a, T: 'a> {
+    front: &'a [Cell<T>],
+    back: &'a [Cell<T>],
+}
+
+impl<'a, T> Clone for CellDeque<'a, T> {
+    fn clone(&self) -> CellDeque<'a, T> {
+        *self
+    }
+}
+impl<'a, T> Copy for CellDeque<'a, T> {}
+
+impl<'a, T> CellDeque<'a, T> {
+    /// Create a view of the elements of `data`.
+    pub fn new(data: &'a mut VecDeque<T>) -> CellDeque<'a, T> {
+        let (front, back) = deque(data);
+        CellDeque { front, back }
+    }
+
+    /// The two contiguous halves, as for `alias::deque`.
+    pub fn as_slices(&self) -> (&'a [Cell<T>], &'a [Cell<T>]) {
+        (self.front, self.back)
+    }
+
+    /// The number of elements.
+    pub fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+
+    /// Whether there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Element `i`, or `None` if it is out of bounds.
+    pub fn get(&self, i: usize) -> Option<&'a Cell<T>> {
+        match i.checked_sub(self.front.len()) {
+            None => Some(&self.front[i]),
+            Some(j) => self.back.get(j),
+        }
+    }
+
+    /// The first element, or `None` if it is empty.
+    pub fn front(&self) -> Option<&'a Cell<T>> {
+        self.get(0)
+    }
+
+    /// The last element, or `None` if it is empty.
+    pub fn back(&self) -> Option<&'a Cell<T>> {
+        self.back.last().or_else(|| self.front.last())
+    }
+
+    /// Iterate over the elements, front to back.
+    pub fn iter(&self) -> Chain<slice::Iter<'a, Cell<T>>, slice::Iter<'a, Cell<T>>> {
+        self.front.iter().chain(self.back)
+    }
+}
+
+impl<'a, T> Index<usize> for CellDeque<'a, T> {
+    type Output = Cell<T>;
+
+    fn index(&self, i: usize) -> &Cell<T> {
+        match self.get(i) {
+            Some(c) => c,
+            None => panic!("CellDeque: index {} out of bounds for length {}", i, self.len()),
+        }
+    }
+}
+
+impl<'a, T: Copy + fmt::Debug> fmt::Debug for CellDeque<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter().map(Cell::get)).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::rc::Rc;
+
+    #[test]
+    fn smoke_vec_parts() {
+        let mut v = Vec::with_capacity(3);
+        v.extend([1u8, 2]);
+        {
+            let (init, spare) = vec_parts(&mut v);
+            init[0].swap(&init[1]);
+            assert!(!spare.is_empty());
+        }
+        assert_eq!(v, [2, 1]);
+
+        let n = vec_append(&mut v, |init, spare| {
+            spare.set(0, init[0].get() + init[1].get());
+            spare.len()
+        });
+        assert_eq!((n, &*v), (v.capacity() - 2, &[2, 1, 3][..]));
+    }
+
+    #[test]
+    fn append_drops_after_gap() {
+        let rc = Rc::new(());
+        let mut v = vec![rc.clone()];
+        v.reserve(3);
+        vec_append(&mut v, |_, spare| {
+            spare.set(0, rc.clone());
+            spare.set(2, rc.clone());
+        });
+        assert_eq!((v.len(), Rc::strong_count(&rc)), (2, 3));
+    }
+
+    #[test]
+    fn append_zero_sized() {
+        // the spare capacity is `usize::MAX`
+        let mut v = vec![(); 2];
+        let n = vec_append(&mut v, |init, spare| {
+            spare.set(0, ());
+            spare.set(1, ());
+            spare.set(5, ());
+            (init.len(), spare.len())
+        });
+        assert_eq!((n, v.len()), ((2, usize::MAX - 2), 4));
+        let mut empty = Vec::<()>::new();
+        vec_append(&mut empty, |_, _| ());
+        assert!(empty.is_empty() && !vec_append(&mut empty, |_, spare| spare.is_complete()));
+    }
+
+    #[test]
+    fn smoke_cell_vec() {
+        let mut v = vec![String::from("a")];
+        {
+            let c = CellVec::new(&mut v);
+            let d = &c;
+            c.push(String::from("b"));
+            d.push(String::from("c"));
+            d.set(0, String::from("z"));
+            assert_eq!((c.len(), format!("{:?}", d)), (3, r#"CellVec(["z", "b", "c"])"#.to_string()));
+        }
+        assert_eq!(v, ["z", "b", "c"]);
+
+        // still there after a panic while taken out
+        #[derive(Debug)]
+        struct NoClone;
+        impl Clone for NoClone {
+            fn clone(&self) -> NoClone {
+                panic!("no clone")
+            }
+        }
+        let mut v = vec![NoClone];
+        let c = CellVec::new(&mut v);
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| format!("{:?}", c))).is_err());
+        assert_eq!(c.len(), 1);
+    }
+
+    #[test]
+    fn smoke_deque() {
+        let mut q = VecDeque::with_capacity(4);
+        q.extend([0, 1, 2, 3]);
+        q.pop_front();
+        q.pop_front();
+        q.extend([4, 5]);
+        {
+            let d = CellDeque::new(&mut q);
+            let (a, b) = d.as_slices();
+            assert_eq!(a.len() + b.len(), d.len());
+            assert_eq!((d.len(), d.front().map(Cell::get), d.back().map(Cell::get)), (4, Some(2), Some(5)));
+            d[2].swap(&d[1]);
+            assert!(d.get(4).is_none());
+            assert_eq!(format!("{:?}", d), "[2, 4, 3, 5]");
+        }
+        assert_eq!(q, [2, 4, 3, 5]);
+    }
+}
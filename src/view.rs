@@ -0,0 +1,207 @@
+use std::cell::Cell;
+use std::fmt;
+use std::ops::{Deref, Index, RangeBounds};
+use std::slice::SliceIndex;
+
+use slice;
+
+/// An aliased slice that can be printed and compared directly.
+///
+/// This dereferences to `[Cell<T>]`, so everything that works on
+/// `alias::slice`'s result works here too, but `Debug` shows the
+/// values rather than `Cell { value: .. }`, and it can be compared
+/// against plain slices and arrays.
+///
+/// Created by `alias::slice_view`.
+pub struct AliasedSlice<'a, T: 'a> {
+    cells: &'a [Cell<T>],
+}
+
+/// Allow the contents of `data` to be mutated while aliased, as an
+/// `AliasedSlice`.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut x = [1, 2, 3];
+/// {
+///     let v = alias::slice_view(&mut x);
+///     let w = v;
+///     w[0].set(v[2].get() * 10);
+///     assert_eq!(v, [30, 2, 3]);
+///     assert_eq!(alias::AliasedSlice::new(&w[1..]), [2, 3]);
+///     assert_eq!(format!("{:?}", v), "[30, 2, 3]");
+/// }
+/// ```
+pub fn slice_view<T>(data: &mut [T]) -> AliasedSlice<'_, T> {
+    AliasedSlice::new(slice(data))
+}
+
+/// View the aliased slice `cells` so that it can be printed.
+///
+/// This is `AliasedSlice::new`, for inspecting cells in tests and log
+/// statements: the values are read when it is formatted.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut buf = [0u8; 4];
+/// let cells = alias::slice(&mut buf);
+/// cells[1].set(7);
+/// let line = format!("buf = {:?}", alias::debug(cells));
+/// assert_eq!(line, "buf = [0, 7, 0, 0]");
+/// assert_eq!(alias::debug(&cells[1..3]).snapshot(), [7, 0]);
+/// ```
+pub fn debug<T: Copy + fmt::Debug>(cells: &[Cell<T>]) -> AliasedSlice<'_, T> {
+    AliasedSlice::new(cells)
+}
+
+impl<'a, T> AliasedSlice<'a, T> {
+    /// View the aliased slice `cells`.
+    pub fn new(cells: &'a [Cell<T>]) -> AliasedSlice<'a, T> {
+        AliasedSlice { cells }
+    }
+
+    /// The underlying cells.
+    pub fn as_cells(&self) -> &'a [Cell<T>] {
+        self.cells
+    }
+
+    /// The number of elements.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Whether there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// A view of the elements in `range`, for the same lifetime.
+    ///
+    /// Unlike `split_at_mut` on a `&mut [T]`, any number of subviews,
+    /// overlapping or not, can be used at once, since every write goes
+    /// through a `Cell`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let mut x = [1, 2, 3, 4, 5, 6];
+    /// {
+    ///     let v = alias::slice_view(&mut x);
+    ///     let (all, back) = (v.subview(..), v.subview(3..));
+    ///     let middle = all.subview(2..5);
+    ///
+    ///     // shift the back half left by one, through the middle
+    ///     for i in 0..middle.len() {
+    ///         middle[i].set(back.get(i).map_or(0, |c| c.get()));
+    ///     }
+    ///     assert_eq!(back, [5, 6, 6]);
+    /// }
+    /// assert_eq!(x, [1, 2, 4, 5, 6, 6]);
+    /// ```
+    pub fn subview<R: RangeBounds<usize>>(&self, range: R) -> AliasedSlice<'a, T> {
+        let cells = self.cells;
+        AliasedSlice::new(&cells[(range.start_bound().cloned(), range.end_bound().cloned())])
+    }
+
+    /// Copy the current values into a new `Vec`.
+    pub fn snapshot(&self) -> Vec<T>
+        where T: Copy
+    {
+        self.cells.iter().map(Cell::get).collect()
+    }
+}
+
+impl<'a, T> Clone for AliasedSlice<'a, T> {
+    fn clone(&self) -> AliasedSlice<'a, T> {
+        *self
+    }
+}
+
+impl<'a, T> Copy for AliasedSlice<'a, T> {}
+
+impl<'a, T> Deref for AliasedSlice<'a, T> {
+    type Target = [Cell<T>];
+
+    fn deref(&self) -> &[Cell<T>] {
+        self.cells
+    }
+}
+
+impl<'a, T, I: SliceIndex<[Cell<T>]>> Index<I> for AliasedSlice<'a, T> {
+    type Output = I::Output;
+
+    fn index(&self, i: I) -> &I::Output {
+        &self.cells[i]
+    }
+}
+
+impl<'a, T: Copy + PartialEq> PartialEq<[T]> for AliasedSlice<'a, T> {
+    fn eq(&self, other: &[T]) -> bool {
+        self.len() == other.len() && self.cells.iter().zip(other).all(|(c, x)| c.get() == *x)
+    }
+}
+
+impl<'a, 'b, T: Copy + PartialEq> PartialEq<&'b [T]> for AliasedSlice<'a, T> {
+    fn eq(&self, other: &&'b [T]) -> bool {
+        *self == **other
+    }
+}
+
+impl<'a, const N: usize, T: Copy + PartialEq> PartialEq<[T; N]> for AliasedSlice<'a, T> {
+    fn eq(&self, other: &[T; N]) -> bool {
+        *self == other[..]
+    }
+}
+
+impl<'a, 'b, T: Copy + PartialEq> PartialEq<AliasedSlice<'b, T>> for AliasedSlice<'a, T> {
+    fn eq(&self, other: &AliasedSlice<'b, T>) -> bool {
+        self.len() == other.len()
+            && self.cells.iter().zip(other.cells).all(|(a, b)| a.get() == b.get())
+    }
+}
+
+impl<'a, T: Copy + fmt::Debug> fmt::Debug for AliasedSlice<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.cells.iter().map(Cell::get)).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_view() {
+        let mut x = vec![1u8, 2, 3, 4];
+        {
+            let v = slice_view(&mut x);
+            assert_eq!((v.len(), v.is_empty()), (4, false));
+            v[0].swap(&v[2]);
+            v[1].swap(&v[3]);
+            assert_eq!(v, [3, 4, 1, 2]);
+            assert_eq!(v, &[3, 4, 1, 2][..]);
+            assert!(v != [3, 4, 1]);
+            let w = AliasedSlice::new(&v.as_cells()[2..]);
+            assert_eq!(w, AliasedSlice::new(&v[2..]));
+            assert!(w != v);
+            assert_eq!(format!("{:?}", w), "[1, 2]");
+            let before = debug(&v).snapshot();
+            v[0].set(9);
+            assert_eq!((before, format!("{:?}", debug(&v[..1]))), (vec![3, 4, 1, 2], "[9]".to_string()));
+            v[0].set(3);
+            let (a, b) = (v.subview(..3), v.subview(1..=2));
+            b[1].set(a[0].get());
+            assert_eq!((a, b.len()), (AliasedSlice::new(&v[..3]), 2));
+            assert_eq!(v, [3, 4, 3, 2]);
+            v[2].set(1);
+            assert_eq!(v.subview(4..).len(), 0);
+        }
+        assert_eq!(x, [3, 4, 1, 2]);
+    }
+}
@@ -0,0 +1,108 @@
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::ptr;
+
+/// A cell whose reads and writes are volatile, so they are never
+/// elided or merged by the optimiser.
+///
+/// Created by `alias::volatile` and `alias::volatile_slice`.
+#[repr(transparent)]
+pub struct VolatileCell<T> {
+    value: UnsafeCell<T>,
+}
+
+/// Allow `data` to be mutated while aliased, with every access
+/// volatile.
+///
+/// This is `alias::one` for memory-mapped registers and similar,
+/// where each load and store must really happen.
+///
+/// # Examples
+///
+/// ```rust
+/// #[derive(Clone, Copy)]
+/// #[repr(C)]
+/// struct Uart { data: u32, status: u32 }
+///
+/// let mut regs = Uart { data: 0, status: 1 };
+/// {
+///     let r = alias::volatile(&mut regs);
+///     let (driver, poll) = (r, r);
+///     driver.set(Uart { data: b'x' as u32, ..driver.get() });
+///     assert_eq!(poll.get().status, 1);
+/// }
+/// assert_eq!(regs.data, b'x' as u32);
+/// ```
+pub fn volatile<T: Copy>(data: &mut T) -> &VolatileCell<T> {
+    unsafe { &*(data as *mut T as *const VolatileCell<T>) }
+}
+
+/// Allow the contents of `data` to be mutated while aliased, with
+/// every access volatile.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut block = [0u32; 4];
+/// {
+///     let regs = alias::volatile_slice(&mut block);
+///     regs[0].set(0xFF);
+///     regs[3].set(regs[0].get() >> 4);
+/// }
+/// assert_eq!(block, [0xFF, 0, 0, 0xF]);
+/// ```
+pub fn volatile_slice<T: Copy>(data: &mut [T]) -> &[VolatileCell<T>] {
+    unsafe { &*(data as *mut [T] as *const [VolatileCell<T>]) }
+}
+
+impl<T: Copy> VolatileCell<T> {
+    /// Read the value, with a volatile load.
+    pub fn get(&self) -> T {
+        unsafe { ptr::read_volatile(self.value.get()) }
+    }
+
+    /// Write `value`, with a volatile store.
+    pub fn set(&self, value: T) {
+        unsafe { ptr::write_volatile(self.value.get(), value) }
+    }
+
+    /// Read the value, apply `f`, and write the result back, as a
+    /// volatile load followed by a volatile store.
+    pub fn update<F: FnOnce(T) -> T>(&self, f: F) {
+        self.set(f(self.get()))
+    }
+
+    /// A raw pointer to the value.
+    pub fn as_ptr(&self) -> *mut T {
+        self.value.get()
+    }
+}
+
+impl<T: Copy + fmt::Debug> fmt::Debug for VolatileCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("VolatileCell").field(&self.get()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_volatile() {
+        let mut x = 1u8;
+        let mut v = [1u16, 2, 3];
+        {
+            let a = volatile(&mut x);
+            let b = a;
+            a.update(|x| x + 1);
+            assert_eq!((b.get(), format!("{:?}", b)), (2, "VolatileCell(2)".to_string()));
+
+            let s = volatile_slice(&mut v);
+            s[0].set(s[2].get() * 10);
+            s[1].update(|x| x - 2);
+            assert_eq!(s[1].as_ptr() as usize - s[0].as_ptr() as usize, 2);
+        }
+        assert_eq!((x, v), (2, [30, 0, 3]));
+    }
+}
@@ -0,0 +1,130 @@
+use std::cell::Cell;
+
+/// Allow the mutable slice `data` to be mutated while aliased, as a
+/// whole.
+///
+/// Unlike `alias::slice`, this doesn't commit to a per-element view:
+/// the result can be split into differently-sized sub-regions with
+/// `split_at` as needed, and viewed element-wise with `as_cells`.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut buf = [0u8; 8];
+/// {
+///     let whole = alias::slice_cell(&mut buf);
+///     let (header, body) = alias::split_at(whole, 2);
+///     let (body, trailer) = alias::split_at(body, 4);
+///
+///     alias::as_cells(header)[0].set(4);
+///     for c in alias::as_cells(body) {
+///         c.set(0xAA);
+///     }
+///     alias::as_cells(trailer)[1].set(1);
+///     // the regions still alias the whole
+///     alias::as_cells(whole)[1].set(2);
+/// }
+/// assert_eq!(buf, [4, 2, 0xAA, 0xAA, 0xAA, 0xAA, 0, 1]);
+/// ```
+pub fn slice_cell<T>(data: &mut [T]) -> &Cell<[T]> {
+    Cell::from_mut(data)
+}
+
+/// Allow `data`, which may be unsized, to be mutated while aliased,
+/// as a single cell.
+///
+/// This is `alias::one` without the `Sized` bound: `slice_cell` is
+/// the case of `[T]`, and the result can be viewed element-wise with
+/// `as_cells`. Unsized cells can't be read or written as a whole, so
+/// this is mostly useful for slices, and for passing an aliased view
+/// through code that is generic over `T: ?Sized`.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::cell::Cell;
+///
+/// fn halves<T: ?Sized>(c: &Cell<T>) -> (&Cell<T>, &Cell<T>) {
+///     (c, c)
+/// }
+///
+/// let mut buf = vec![1, 2, 3];
+/// {
+///     let (a, b) = halves(alias::unsized_one(&mut buf[..]));
+///     alias::as_cells(a)[0].swap(&alias::as_cells(b)[2]);
+/// }
+/// assert_eq!(buf, [3, 2, 1]);
+/// ```
+pub fn unsized_one<T: ?Sized>(data: &mut T) -> &Cell<T> {
+    Cell::from_mut(data)
+}
+
+/// Split `data` into the elements before `mid` and those from `mid`
+/// on.
+///
+/// # Panics
+///
+/// Panics if `mid > data.len()`.
+pub fn split_at<T>(data: &Cell<[T]>, mid: usize) -> (&Cell<[T]>, &Cell<[T]>) {
+    let (a, b) = as_cells(data).split_at(mid);
+    (from_cells(a), from_cells(b))
+}
+
+/// View a whole-slice cell element-wise.
+///
+/// This is the same as `Cell::as_slice_of_cells`.
+pub fn as_cells<T>(data: &Cell<[T]>) -> &[Cell<T>] {
+    data.as_slice_of_cells()
+}
+
+/// View a slice of cells as a whole-slice cell.
+///
+/// This is the inverse of `as_cells`.
+pub fn from_cells<T>(data: &[Cell<T>]) -> &Cell<[T]> {
+    // `Cell<[T]>` and `[Cell<T>]` have the same layout, and
+    // `Cell<[T]>` allows nothing more than element-wise access.
+    unsafe { &*(data as *const [Cell<T>] as *const Cell<[T]>) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_whole() {
+        let mut x = vec![String::new(); 5];
+        {
+            let w = slice_cell(&mut x);
+            let (a, b) = split_at(w, 0);
+            assert_eq!((as_cells(a).len(), as_cells(b).len()), (0, 5));
+            let (c, d) = split_at(b, 5);
+            assert_eq!((as_cells(c).len(), as_cells(d).len()), (5, 0));
+
+            let (l, r) = split_at(w, 2);
+            as_cells(l)[1].set("l".to_string());
+            as_cells(r)[0].set("r".to_string());
+            let back = from_cells(&as_cells(w)[1..3]);
+            as_cells(back)[0].swap(&as_cells(back)[1]);
+        }
+        assert_eq!(x, ["", "r", "l", "", ""]);
+    }
+
+    #[test]
+    fn smoke_unsized_one() {
+        let mut x = [1, 2];
+        let mut y = 3;
+        {
+            let a: &Cell<[i32]> = unsized_one(&mut x[..]);
+            let b = unsized_one(&mut y);
+            as_cells(a)[1].swap(b);
+        }
+        assert_eq!((x, y), ([1, 3], 2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_past_end() {
+        let mut x = [1, 2];
+        split_at(slice_cell(&mut x), 3);
+    }
+}
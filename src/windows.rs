@@ -0,0 +1,204 @@
+use std::cell::Cell;
+use std::fmt;
+use std::slice as std_slice;
+
+/// An iterator over the overlapping windows of `N` consecutive
+/// elements of `data`, as arrays.
+///
+/// Unlike `windows`, the window size is known at compile time, so
+/// windows can be destructured and loops over them unrolled.
+///
+/// # Panics
+///
+/// Panics if `N` is zero.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut x = [1, 2, 4, 8, 16];
+/// let cells = alias::slice(&mut x);
+///
+/// // an in-place stencil, seeing earlier updates
+/// for [a, b, c] in alias::array_windows(cells) {
+///     b.set(a.get() + b.get() + c.get());
+/// }
+/// assert_eq!(x, [1, 7, 19, 43, 16]);
+/// ```
+pub fn array_windows<const N: usize, T>(data: &[Cell<T>]) -> ArrayWindows<'_, N, T> {
+    assert!(N != 0, "alias::array_windows: window size must be non-zero");
+    ArrayWindows { data }
+}
+
+/// The iterator returned by `array_windows`.
+pub struct ArrayWindows<'a, const N: usize, T: 'a> {
+    data: &'a [Cell<T>],
+}
+
+impl<'a, const N: usize, T> ArrayWindows<'a, N, T> {
+    fn window(&self, i: usize) -> &'a [Cell<T>; N] {
+        let w = &self.data[i..i + N];
+        unsafe { &*(w.as_ptr() as *const [Cell<T>; N]) }
+    }
+}
+
+impl<'a, const N: usize, T> Iterator for ArrayWindows<'a, N, T> {
+    type Item = &'a [Cell<T>; N];
+
+    fn next(&mut self) -> Option<&'a [Cell<T>; N]> {
+        if self.data.len() < N {
+            return None
+        }
+        let w = self.window(0);
+        self.data = &self.data[1..];
+        Some(w)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // `N` is non-zero, and this can't overflow for a slice of
+        // `usize::MAX` zero-sized elements.
+        let n = self.data.len().saturating_sub(N - 1);
+        (n, Some(n))
+    }
+}
+
+impl<'a, const N: usize, T> DoubleEndedIterator for ArrayWindows<'a, N, T> {
+    fn next_back(&mut self) -> Option<&'a [Cell<T>; N]> {
+        let len = self.data.len();
+        if len < N {
+            return None
+        }
+        let w = self.window(len - N);
+        self.data = &self.data[..len - 1];
+        Some(w)
+    }
+}
+
+impl<'a, const N: usize, T> ExactSizeIterator for ArrayWindows<'a, N, T> {}
+
+impl<'a, const N: usize, T> fmt::Debug for ArrayWindows<'a, N, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ArrayWindows").field("remaining", &self.len()).finish()
+    }
+}
+
+/// Split `data` into chunks of `N` elements, as arrays, and the
+/// remainder of fewer than `N` elements.
+///
+/// This is `slice::as_chunks` for aliased slices.
+///
+/// # Panics
+///
+/// Panics if `N` is zero.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut framebuffer = [10u8, 20, 30, 255, 40, 50, 60, 255, 7];
+/// let cells = alias::slice(&mut framebuffer);
+///
+/// let (pixels, rest) = alias::as_array_chunks::<4, _>(cells);
+/// assert_eq!((pixels.len(), rest.len()), (2, 1));
+/// for [r, _g, b, a] in pixels {
+///     r.swap(b);
+///     a.set(a.get() / 2);
+/// }
+/// assert_eq!(framebuffer, [30, 20, 10, 127, 60, 50, 40, 127, 7]);
+/// ```
+pub fn as_array_chunks<const N: usize, T>(data: &[Cell<T>]) -> (&[[Cell<T>; N]], &[Cell<T>]) {
+    assert!(N != 0, "alias::as_array_chunks: chunk size must be non-zero");
+    let n = data.len() / N;
+    let (chunks, rest) = data.split_at(n * N);
+    // `[[X; N]]` with `n` elements has the same layout as `[X]` with
+    // `n * N`.
+    (unsafe { std_slice::from_raw_parts(chunks.as_ptr() as *const [Cell<T>; N], n) }, rest)
+}
+
+/// Split `data` into chunks of `N` elements, each as a single cell of
+/// an array, and the remainder of fewer than `N` elements.
+///
+/// Each chunk can then be read and written as a whole, or viewed
+/// element-wise again with `Cell::as_array_of_cells`.
+///
+/// # Panics
+///
+/// Panics if `N` is zero.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut samples = [1i16, 2, 3, 4, 5];
+/// let cells = alias::slice(&mut samples);
+///
+/// let (frames, rest) = alias::as_chunk_cells::<2, _>(cells);
+/// for f in frames {
+///     let [l, r] = f.get();
+///     f.set([r, l]);
+/// }
+/// rest[0].set(0);
+/// assert_eq!(samples, [2, 1, 4, 3, 0]);
+/// ```
+pub fn as_chunk_cells<const N: usize, T>(data: &[Cell<T>]) -> (&[Cell<[T; N]>], &[Cell<T>]) {
+    let (chunks, rest) = as_array_chunks::<N, T>(data);
+    // `[Cell<T>; N]` and `Cell<[T; N]>` have the same layout, and a
+    // `Cell` of the whole array allows nothing that per-element
+    // `Cell`s don't.
+    (unsafe { &*(chunks as *const [[Cell<T>; N]] as *const [Cell<[T; N]>]) }, rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slice;
+
+    #[test]
+    fn smoke_array_windows() {
+        let mut x = [0, 1, 2, 3];
+        let c = slice(&mut x);
+        let mut it = array_windows::<2, _>(c);
+        assert_eq!(it.len(), 3);
+        assert_eq!(it.next().unwrap()[1].get(), 1);
+        assert_eq!(it.next_back().unwrap()[0].get(), 2);
+        let [a, b] = it.next().unwrap();
+        assert_eq!((a.get(), b.get()), (1, 2));
+        assert!(it.next().is_none());
+        assert!(it.next_back().is_none());
+
+        assert_eq!(array_windows::<4, _>(c).count(), 1);
+        assert_eq!(array_windows::<5, _>(c).len(), 0);
+        assert_eq!(array_windows::<1, _>(c).rev().map(|[v]| v.get()).collect::<Vec<_>>(),
+                   [3, 2, 1, 0]);
+
+        let mut z = vec![(); usize::MAX];
+        assert_eq!(array_windows::<3, _>(slice(&mut z)).len(), usize::MAX - 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "non-zero")]
+    fn zero_size() {
+        let _ = array_windows::<0, u8>(&[]);
+    }
+
+    #[test]
+    fn smoke_chunks() {
+        let mut x = [0u8, 1, 2, 3, 4, 5, 6];
+        {
+            let c = slice(&mut x);
+            let (a, rest) = as_array_chunks::<3, _>(c);
+            assert_eq!((a.len(), rest.len()), (2, 1));
+            let (w, rest2) = as_chunk_cells::<3, _>(c);
+            assert_eq!(w[1].get(), [3, 4, 5]);
+            a[1][0].set(9);
+            assert_eq!(w[1].replace([7, 7, 7]), [9, 4, 5]);
+            assert_eq!(a[1][2].get(), 7);
+            assert_eq!(rest2[0].get(), 6);
+            assert_eq!(as_array_chunks::<8, _>(c).0.len(), 0);
+        }
+        assert_eq!(x, [0, 1, 2, 7, 7, 7, 6]);
+    }
+
+    #[test]
+    #[should_panic(expected = "non-zero")]
+    fn zero_chunk_size() {
+        let _ = as_chunk_cells::<0, u8>(&[]);
+    }
+}
@@ -0,0 +1,125 @@
+use std::cell::Cell;
+use std::fmt;
+use std::iter::FusedIterator;
+
+/// Iterate over the elements of `a` and `b` in lockstep.
+///
+/// Like `Iterator::zip`, this stops at the end of the shorter slice.
+/// The two may alias each other.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut pos = [0.0, 10.0, 20.0];
+/// let mut vel = [1.0, -1.0, 0.5];
+/// {
+///     let (p, v) = (alias::slice(&mut pos), alias::slice(&mut vel));
+///     let step = |dt: f64| alias::zip(p, v).for_each_copied(|x, dx| (x + dx * dt, dx));
+///     let bounce = || alias::zip(p, v).for_each_copied(|x, dx| {
+///         if x < 0.0 { (-x, -dx) } else { (x, dx) }
+///     });
+///
+///     step(2.0);
+///     v[0].set(-1.0);
+///     step(3.0);
+///     bounce();
+/// }
+/// assert_eq!(pos, [1.0, 5.0, 22.5]);
+/// assert_eq!(vel, [1.0, -1.0, 0.5]);
+/// ```
+pub fn zip<'a, A, B>(a: &'a [Cell<A>], b: &'a [Cell<B>]) -> Zip<'a, A, B> {
+    let len = a.len().min(b.len());
+    Zip { a: &a[..len], b: &b[..len], start: 0, end: len }
+}
+
+/// The iterator returned by `zip`.
+pub struct Zip<'a, A: 'a, B: 'a> {
+    a: &'a [Cell<A>],
+    b: &'a [Cell<B>],
+    start: usize,
+    end: usize,
+}
+
+impl<'a, A, B> Clone for Zip<'a, A, B> {
+    fn clone(&self) -> Zip<'a, A, B> {
+        Zip { a: self.a, b: self.b, start: self.start, end: self.end }
+    }
+}
+
+impl<'a, A: Copy, B: Copy> Zip<'a, A, B> {
+    /// Replace each remaining pair with the result of calling `f` on
+    /// copies of its values.
+    ///
+    /// The first value is written back before the second, so if the
+    /// two slices alias, the second wins.
+    pub fn for_each_copied<F: FnMut(A, B) -> (A, B)>(self, mut f: F) {
+        for (a, b) in self {
+            let (x, y) = f(a.get(), b.get());
+            a.set(x);
+            b.set(y);
+        }
+    }
+}
+
+impl<'a, A, B> Iterator for Zip<'a, A, B> {
+    type Item = (&'a Cell<A>, &'a Cell<B>);
+
+    fn next(&mut self) -> Option<(&'a Cell<A>, &'a Cell<B>)> {
+        if self.start == self.end {
+            return None
+        }
+        let i = self.start;
+        self.start += 1;
+        Some((&self.a[i], &self.b[i]))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.end - self.start;
+        (n, Some(n))
+    }
+}
+
+impl<'a, A, B> DoubleEndedIterator for Zip<'a, A, B> {
+    fn next_back(&mut self) -> Option<(&'a Cell<A>, &'a Cell<B>)> {
+        if self.start == self.end {
+            return None
+        }
+        self.end -= 1;
+        Some((&self.a[self.end], &self.b[self.end]))
+    }
+}
+
+impl<'a, A, B> ExactSizeIterator for Zip<'a, A, B> {}
+
+impl<'a, A, B> FusedIterator for Zip<'a, A, B> {}
+
+impl<'a, A, B> fmt::Debug for Zip<'a, A, B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Zip").field("remaining", &(self.end - self.start)).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_zip() {
+        let mut a = [1, 2, 3, 4];
+        let mut b = ['a', 'b', 'c'];
+        {
+            let (a, b) = (::slice(&mut a), ::slice(&mut b));
+            let mut z = zip(a, b);
+            assert_eq!(z.len(), 3);
+            let (x, y) = z.next_back().unwrap();
+            assert_eq!((x.get(), y.get()), (3, 'c'));
+            z.for_each_copied(|x, y| (x * 10, y.to_ascii_uppercase()));
+        }
+        assert_eq!((a, b), ([10, 20, 3, 4], ['A', 'B', 'c']));
+
+        // aliased: the second write wins
+        let c = ::slice(&mut a);
+        zip(c, c).for_each_copied(|x, _| (x + 1, x - 1));
+        assert_eq!(c[0].get(), 9);
+    }
+}